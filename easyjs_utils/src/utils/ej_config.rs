@@ -17,7 +17,13 @@ pub struct EJConfig {
     /// What is the output name.
     pub output: String,
     /// Is this a package installed globally?
-    pub global: bool
+    pub global: bool,
+    /// The target for `@if(target == "...")`, the runtime if not set.
+    #[serde(default)]
+    pub target: Option<String>,
+    /// The features for `@cfg(feature = "...")`.
+    #[serde(default)]
+    pub features: Vec<String>,
 }
 
 /// The Author details of a .ejconfig file.
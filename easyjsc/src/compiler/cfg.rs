@@ -0,0 +1,155 @@
+// Conditional compilation, `@if(target == "node") { ... }` and `@cfg(feature = "x") { ... }`.
+//
+// The parser keeps a condition in source order but gives `and`/`or` a higher precedence than `==`,
+// so the condition is flattened back into source order and evaluated here as not > and > or.
+
+use crate::errors::{cfg_unknown_target, cfg_unsupported_condition};
+use crate::parser::ast::Expression;
+
+/// The targets that `@if(target == "...")` can check for.
+pub const TARGETS: [&str; 5] = ["browser", "node", "deno", "bun", "easyjsr"];
+
+/// A part of a flattened condition.
+enum Part<'a> {
+    Value(&'a Expression),
+    /// == != or =
+    Op(&'a str),
+    And,
+    Or,
+    Not,
+}
+
+/// Evaluate a `@if`/`@cfg` condition for a target and features.
+///
+/// Supports `target == "node"`, `feature = "x"` (or `feature == "x"`), `!=`, `not`, `and`, `or`, `()` and `true`/`false`.
+pub fn eval_cfg(condition: &Expression, target: &str, features: &[String]) -> Result<bool, String> {
+    let mut parts = vec![];
+    flatten(condition, &mut parts);
+
+    let mut evaluator = Evaluator {
+        condition,
+        parts,
+        pos: 0,
+        target,
+        features,
+    };
+    let result = evaluator.or()?;
+    if evaluator.pos < evaluator.parts.len() {
+        return Err(cfg_unsupported_condition(condition.get_token()));
+    }
+
+    Ok(result)
+}
+
+fn flatten<'a>(expr: &'a Expression, parts: &mut Vec<Part<'a>>) {
+    match expr {
+        Expression::InfixExpression(_, left, op, right) if op == "==" || op == "!=" => {
+            flatten(left, parts);
+            parts.push(Part::Op(op));
+            flatten(right, parts);
+        }
+        Expression::AssignExpression(_, left, right) => {
+            flatten(left, parts);
+            parts.push(Part::Op("="));
+            flatten(right, parts);
+        }
+        Expression::AndExpression(_, left, right) => {
+            flatten(left, parts);
+            parts.push(Part::And);
+            flatten(right, parts);
+        }
+        Expression::OrExpression(_, left, right) => {
+            flatten(left, parts);
+            parts.push(Part::Or);
+            flatten(right, parts);
+        }
+        Expression::NotExpression(_, inner) => {
+            parts.push(Part::Not);
+            flatten(inner, parts);
+        }
+        Expression::PrefixExpression(_, op, inner) if op == "!" => {
+            parts.push(Part::Not);
+            flatten(inner, parts);
+        }
+        _ => parts.push(Part::Value(expr)),
+    }
+}
+
+struct Evaluator<'a> {
+    /// The whole condition, for errors.
+    condition: &'a Expression,
+    parts: Vec<Part<'a>>,
+    pos: usize,
+    target: &'a str,
+    features: &'a [String],
+}
+
+impl<'a> Evaluator<'a> {
+    fn or(&mut self) -> Result<bool, String> {
+        let mut result = self.and()?;
+        while matches!(self.parts.get(self.pos), Some(Part::Or)) {
+            self.pos += 1;
+            // no short circuit, so that the right side is always checked.
+            let right = self.and()?;
+            result = result || right;
+        }
+        Ok(result)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut result = self.not()?;
+        while matches!(self.parts.get(self.pos), Some(Part::And)) {
+            self.pos += 1;
+            let right = self.not()?;
+            result = result && right;
+        }
+        Ok(result)
+    }
+
+    fn not(&mut self) -> Result<bool, String> {
+        if matches!(self.parts.get(self.pos), Some(Part::Not)) {
+            self.pos += 1;
+            return Ok(!self.not()?);
+        }
+        self.comparison()
+    }
+
+    /// `true`, `false`, `(condition)` or `name == "value"`.
+    fn comparison(&mut self) -> Result<bool, String> {
+        let Some(Part::Value(name)) = self.parts.get(self.pos) else {
+            return Err(cfg_unsupported_condition(self.condition.get_token()));
+        };
+        let name: &'a Expression = name;
+        self.pos += 1;
+
+        match name {
+            Expression::Boolean(_, value) => return Ok(*value),
+            Expression::GroupedExpression(_, inner) => return eval_cfg(inner, self.target, self.features),
+            _ => {}
+        }
+
+        let (Some(Part::Op(op)), Some(Part::Value(value))) = (self.parts.get(self.pos), self.parts.get(self.pos + 1))
+        else {
+            return Err(cfg_unsupported_condition(name.get_token()));
+        };
+        let is_not = *op == "!=";
+        let is_set = self.option(name, value)?;
+        self.pos += 2;
+
+        Ok(is_set != is_not)
+    }
+
+    /// Is the `target` or `feature` set to the value?
+    fn option(&self, name: &Expression, value: &Expression) -> Result<bool, String> {
+        let (Expression::Identifier(token, name), Expression::StringLiteral(_, value)) = (name, value) else {
+            return Err(cfg_unsupported_condition(name.get_token()));
+        };
+
+        match name.as_str() {
+            "target" if TARGETS.contains(&value.as_str()) => Ok(self.target == value),
+            "target" => Err(cfg_unknown_target(token, value)),
+            "feature" => Ok(self.features.contains(value)),
+            _ => Err(cfg_unsupported_condition(token)),
+        }
+    }
+}
@@ -0,0 +1,458 @@
+// TypeScript declaration (.d.ts) emission.
+// Only `pub` functions, structs, classes, enums, type aliases and variables are declared.
+//
+// int/float become number, array becomes any[] and any type we can not name becomes any.
+
+use crate::lexer::token;
+use crate::parser::ast::{Expression, Statement};
+use crate::typechecker::{
+    StrongValType, bind_type_params, get_literal_type, get_param_type_by_expression_ej, widen,
+};
+
+/// Generic TypeScript types that can be used as is.
+const TS_GENERICS: [&str; 8] = [
+    "Promise",
+    "Map",
+    "Set",
+    "Record",
+    "Partial",
+    "Readonly",
+    "Generator",
+    "AsyncGenerator",
+];
+
+/// Emit the TypeScript declarations for a easyjs program.
+pub fn emit_dts(statements: &[Statement]) -> String {
+    let mut emitter = DtsEmitter::new(statements);
+    for stmt in statements {
+        if let Statement::ExportStatement(_, stmt) = stmt {
+            emitter.emit_stmt(stmt);
+        }
+    }
+
+    emitter.result
+}
+
+struct DtsEmitter {
+    /// The names of every exported struct, class, enum and type alias. Other names become `any`.
+    exported_types: Vec<String>,
+    /// The type paramaters of the function being declared.
+    type_params: Vec<String>,
+    /// The declarations
+    result: String,
+}
+
+impl DtsEmitter {
+    fn new(statements: &[Statement]) -> Self {
+        let mut exported_types = vec![];
+        for stmt in statements {
+            if let Statement::ExportStatement(_, stmt) = stmt {
+                match stmt.as_ref() {
+                    Statement::StructStatement(_, name, _, _, _, _, _, _)
+                    | Statement::ClassStatement(_, name, _, _, _) => {
+                        if let Expression::Identifier(_, name) = name.as_ref() {
+                            exported_types.push(name.to_owned());
+                        }
+                    }
+                    Statement::EnumStatement(_, name, _)
+                    | Statement::TypeAliasStatement(_, name, _) => {
+                        exported_types.push(name.to_owned())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        DtsEmitter {
+            exported_types,
+            type_params: vec![],
+            result: String::new(),
+        }
+    }
+
+    fn emit_stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::ExpressionStatement(_, expr) => match expr.as_ref() {
+                Expression::FunctionLiteral(..) => self.emit_function(expr, false),
+                Expression::AsyncExpression(_, function) => self.emit_function(function, true),
+                _ => {}
+            },
+            Statement::VariableStatement(_, name, var_type, value, _) => {
+                if let Expression::Identifier(_, name) = name.as_ref() {
+                    let val_type = self.get_var_type(var_type, value);
+                    self.result
+                        .push_str(&format!("export declare let {}: {};\n", name, val_type));
+                }
+            }
+            Statement::StructStatement(_, name, constructor_vars, mixins, derives, vars, methods, _) => {
+                if let Expression::Identifier(_, name) = name.as_ref() {
+                    self.emit_struct(
+                        name,
+                        constructor_vars.as_deref().map(Vec::as_slice).unwrap_or_default(),
+                        mixins.as_deref().map(Vec::as_slice).unwrap_or_default(),
+                        derives.as_deref().map(Vec::as_slice).unwrap_or_default(),
+                        vars,
+                        methods,
+                    );
+                }
+            }
+            Statement::ClassStatement(_, name, extends, stmts, _) => {
+                if let Expression::Identifier(_, name) = name.as_ref() {
+                    self.emit_class(name, extends, stmts);
+                }
+            }
+            Statement::EnumStatement(_, name, options) => {
+                let options: Vec<String> = options
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, option)| match option {
+                        Expression::Identifier(_, option) => Some(format!("{} = {}", option, i)),
+                        _ => None,
+                    })
+                    .collect();
+                self.result.push_str(&format!(
+                    "export declare enum {} {{ {} }}\n",
+                    name,
+                    options.join(", ")
+                ));
+            }
+            Statement::TypeAliasStatement(_, name, alias_type) => {
+                let alias_type = self.get_type(alias_type);
+                self.result
+                    .push_str(&format!("export type {} = {};\n", name, alias_type));
+            }
+            _ => {}
+        }
+    }
+
+    /// `export declare function name<T>(params): return_type;`
+    fn emit_function(&mut self, function: &Expression, is_async: bool) {
+        if let Some(signature) = self.get_signature(function, is_async, false) {
+            self.result
+                .push_str(&format!("export declare function {};\n", signature));
+        }
+    }
+
+    /// A struct is a function that returns a object, so it is declared as a interface and a function.
+    fn emit_struct(
+        &mut self,
+        name: &str,
+        constructor_vars: &[Expression],
+        mixins: &[Expression],
+        derives: &[Expression],
+        vars: &[Statement],
+        methods: &[Expression],
+    ) {
+        let mut members = vec![];
+        let mut static_members = vec![];
+
+        for var in constructor_vars.iter() {
+            members.push(format!("{};", self.get_param(var)));
+        }
+        for var in vars {
+            if let Statement::VariableStatement(_, var_name, var_type, value, _) = var
+                && let Expression::Identifier(_, var_name) = var_name.as_ref()
+            {
+                members.push(format!("{}: {};", var_name, self.get_var_type(var_type, value)));
+            }
+        }
+        for method in methods {
+            let (function, is_async) = match method {
+                Expression::AsyncExpression(_, function) => (function.as_ref(), true),
+                _ => (method, false),
+            };
+            let is_static = !has_self_param(function);
+            if let Some(signature) = self.get_signature(function, is_async, !is_static) {
+                if is_static {
+                    static_members.push(format!("function {};", signature));
+                } else {
+                    members.push(format!("{};", signature));
+                }
+            }
+        }
+
+        // derives
+        for derive in derives {
+            if let Expression::Identifier(_, derive) = derive {
+                match derive.as_str() {
+                    "eq" => members.push(format!("equals(other: {}): boolean;", name)),
+                    "clone" => members.push(format!("clone(): {};", name)),
+                    "json" => {
+                        members.push("toJSON(): any;".to_string());
+                        static_members.push(format!("function fromJSON(json: any): {};", name));
+                    }
+                    "debug" => members.push("toString(): string;".to_string()),
+                    _ => {}
+                }
+            }
+        }
+
+        let extends = self.get_extends(mixins);
+        self.result.push_str(&format!(
+            "export interface {}{} {{\n{}}}\n",
+            name,
+            extends,
+            indent(&members)
+        ));
+        let params: Vec<String> = constructor_vars.iter().map(|var| self.get_param(var)).collect();
+        self.result.push_str(&format!(
+            "export declare function {}({}): {};\n",
+            name,
+            params.join(", "),
+            name
+        ));
+        if !static_members.is_empty() {
+            self.result.push_str(&format!(
+                "export declare namespace {} {{\n{}}}\n",
+                name,
+                indent(&static_members)
+            ));
+        }
+    }
+
+    /// Only `pub` members of a class are declared, everything else is private (#).
+    fn emit_class(&mut self, name: &str, extends: &[Expression], stmts: &[Statement]) {
+        let mut members = vec![];
+        for stmt in stmts {
+            let (stmt, is_pub) = match stmt {
+                Statement::ExportStatement(_, stmt) => (stmt.as_ref(), true),
+                _ => (stmt, false),
+            };
+            match stmt {
+                Statement::VariableStatement(_, var_name, var_type, value, _) if is_pub => {
+                    if let Expression::Identifier(_, var_name) = var_name.as_ref() {
+                        members.push(format!("{}: {};", var_name, self.get_var_type(var_type, value)));
+                    }
+                }
+                Statement::ExpressionStatement(_, expr) => {
+                    let (function, is_async) = match expr.as_ref() {
+                        Expression::AsyncExpression(_, function) => (function.as_ref(), true),
+                        _ => (expr.as_ref(), false),
+                    };
+                    let fn_name = match function {
+                        Expression::FunctionLiteral(_, fn_name, _, _, _, _, _) => match fn_name.as_ref() {
+                            Expression::Identifier(_, fn_name) => fn_name.to_owned(),
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    let is_static = !has_self_param(function);
+
+                    if fn_name == "__new__" {
+                        if let Expression::FunctionLiteral(_, _, params, _, _, _, _) = function {
+                            let params = self.get_params(params, true);
+                            members.push(format!("constructor({});", params));
+                        }
+                    } else if is_pub
+                        && let Some(signature) = self.get_signature(function, is_async, !is_static)
+                    {
+                        let tag = if is_static { "static " } else { "" };
+                        members.push(format!("{}{};", tag, signature));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // classes are mixins, so extensions are declared with a merged interface.
+        let extends = self.get_extends(extends);
+        if !extends.is_empty() {
+            self.result
+                .push_str(&format!("export interface {}{} {{}}\n", name, extends));
+        }
+        self.result.push_str(&format!(
+            "export declare class {} {{\n{}}}\n",
+            name,
+            indent(&members)
+        ));
+    }
+
+    /// Get the signature of a function. i.e. `first<T>(xs: T[]): T`
+    fn get_signature(
+        &mut self,
+        function: &Expression,
+        is_async: bool,
+        skip_self: bool,
+    ) -> Option<String> {
+        match function {
+            Expression::FunctionLiteral(tk, name, params, return_type, _, type_params, _) => {
+                let name = match name.as_ref() {
+                    Expression::Identifier(_, name) => name.to_owned(),
+                    _ => return None,
+                };
+                self.type_params = type_params
+                    .iter()
+                    .filter_map(|type_param| match type_param {
+                        Expression::Type(_, name) => Some(name.to_owned()),
+                        _ => None,
+                    })
+                    .collect();
+
+                let type_params = if self.type_params.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", self.type_params.join(", "))
+                };
+                let params = self.get_params(params, skip_self);
+                let mut return_type = self.get_type(return_type);
+                if tk.typ == token::GENERATOR {
+                    let generator = if is_async { "AsyncGenerator" } else { "Generator" };
+                    if !return_type.starts_with(&format!("{}<", generator)) {
+                        return_type = format!("{}<{}>", generator, return_type);
+                    }
+                } else if is_async && !return_type.starts_with("Promise<") {
+                    return_type = format!("Promise<{}>", return_type);
+                }
+                self.type_params.clear();
+
+                Some(format!("{}{}({}): {}", name, type_params, params, return_type))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_params(&self, params: &[Expression], skip_self: bool) -> String {
+        let skip = if skip_self && has_self_param_in(params) { 1 } else { 0 };
+        params
+            .iter()
+            .skip(skip)
+            .map(|param| self.get_param(param))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Get a single paramater. i.e. `name: string`, `retries?: number`, `...args: any[]`
+    fn get_param(&self, param: &Expression) -> String {
+        match param {
+            Expression::Identifier(_, name) => format!("{}: any", name),
+            Expression::IdentifierWithType(_, name, var_type) => {
+                format!("{}: {}", name, self.get_type(var_type))
+            }
+            Expression::AssignExpression(_, left, default_value) => {
+                let (name, val_type) = match left.as_ref() {
+                    Expression::IdentifierWithType(_, name, var_type) => {
+                        (name.to_owned(), self.get_type(var_type))
+                    }
+                    Expression::Identifier(_, name) => {
+                        (name.to_owned(), self.get_value_type(default_value))
+                    }
+                    _ => return "arg?: any".to_string(),
+                };
+                format!("{}?: {}", name, val_type)
+            }
+            Expression::SpreadExpression(_, inner) => match inner.as_ref() {
+                Expression::Identifier(_, name) => format!("...{}: any[]", name),
+                _ => "...args: any[]".to_string(),
+            },
+            _ => "arg: any".to_string(),
+        }
+    }
+
+    /// The type of a variable. Declared or infered from a literal value.
+    fn get_var_type(&self, var_type: &Option<Box<Expression>>, value: &Expression) -> String {
+        match var_type {
+            Some(var_type) => self.get_type(var_type),
+            None => self.get_value_type(value),
+        }
+    }
+
+    fn get_value_type(&self, value: &Expression) -> String {
+        match get_literal_type(value) {
+            Some(literal) => self.ts_type(&widen(&literal)),
+            None => "any".to_string(),
+        }
+    }
+
+    fn get_type(&self, type_expr: &Expression) -> String {
+        let strong = bind_type_params(&get_param_type_by_expression_ej(type_expr), &self.type_params);
+        self.ts_type(&strong)
+    }
+
+    /// ` extends A, B` for exported structs and classes.
+    fn get_extends(&self, extends: &[Expression]) -> String {
+        let names: Vec<String> = extends
+            .iter()
+            .filter_map(|extend| match extend {
+                Expression::Identifier(_, name) if self.exported_types.contains(name) => {
+                    Some(name.to_owned())
+                }
+                _ => None,
+            })
+            .collect();
+
+        if names.is_empty() {
+            String::new()
+        } else {
+            format!(" extends {}", names.join(", "))
+        }
+    }
+
+    /// Map a easyjs type to a TypeScript type.
+    fn ts_type(&self, strong: &StrongValType) -> String {
+        match strong {
+            StrongValType::Int
+            | StrongValType::Float
+            | StrongValType::F64
+            | StrongValType::U8
+            | StrongValType::U32 => "number".to_string(),
+            StrongValType::I64 => "bigint".to_string(),
+            StrongValType::Bool => "boolean".to_string(),
+            StrongValType::String => "string".to_string(),
+            StrongValType::Array => "any[]".to_string(),
+            StrongValType::Null => "null".to_string(),
+            StrongValType::Never => "never".to_string(),
+            StrongValType::TypeParam(name) => name.to_owned(),
+            StrongValType::Literal(_, value) => value.to_owned(),
+            StrongValType::Custom(name) if self.exported_types.contains(name) => name.to_owned(),
+            StrongValType::Generic(name, type_args) if name == "Array" => {
+                let item_type = match type_args.first() {
+                    Some(item_type) => self.ts_type(item_type),
+                    None => "any".to_string(),
+                };
+                if item_type.contains(' ') {
+                    format!("({})[]", item_type)
+                } else {
+                    format!("{}[]", item_type)
+                }
+            }
+            StrongValType::Generic(name, type_args)
+                if TS_GENERICS.contains(&name.as_str()) || self.exported_types.contains(name) =>
+            {
+                format!(
+                    "{}<{}>",
+                    name,
+                    type_args
+                        .iter()
+                        .map(|type_arg| self.ts_type(type_arg))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            }
+            StrongValType::Union(types) => types
+                .iter()
+                .map(|member| self.ts_type(member))
+                .collect::<Vec<String>>()
+                .join(" | "),
+            _ => "any".to_string(),
+        }
+    }
+}
+
+/// Does this function take `self` as the first paramater?
+fn has_self_param(function: &Expression) -> bool {
+    match function {
+        Expression::FunctionLiteral(_, _, params, _, _, _, _) => has_self_param_in(params),
+        _ => false,
+    }
+}
+
+fn has_self_param_in(params: &[Expression]) -> bool {
+    matches!(
+        params.first(),
+        Some(Expression::Identifier(_, name)) if name == "this" || name == "self"
+    )
+}
+
+fn indent(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("    {}\n", line)).collect()
+}
@@ -1,75 +1,626 @@
-use crate::parser::ast;
+use std::collections::HashMap;
+
+use crate::lexer::token::Token;
+use crate::parser::ast::{self, Expression, Statement};
 use easyjsr::EJR;
+use regex::Regex;
 
 #[derive(Debug, Clone)]
 pub struct Macro {
     /// the name of the macro.
     name: String,
-    /// The paramaters to be passed to said macro.
-    pub paramaters: Vec<String>,
+    /// The paramaters as written. i.e. `a`, `b = 1` or `...rest`
+    pub params: Vec<Expression>,
     /// The macro body statement.
     pub body: ast::Statement,
     /// Is this macro hygenic?
-    pub is_hygenic: bool
+    pub is_hygenic: bool,
+    /// Is this a procedural macro? (runs at compile time on the AST)
+    pub is_procedural: bool,
+    /// Is this macro public? (`pub macro`) Only public macros can be used by the modules that import it.
+    pub is_pub: bool,
+    /// The id of the module (namespace) the macro is declared in.
+    pub module: String,
+}
+
+/// A macro call expanded into the macro body.
+pub struct Expansion {
+    /// The body with the arguments in place of the paramaters.
+    pub body: Statement,
+    /// Arguments that are evaluated once before the body. (name, argument)
+    pub temps: Vec<(String, Expression)>,
+    /// The next free id for generated names.
+    pub next_id: usize,
+}
+
+/// What a paramater is bound to.
+#[derive(Clone)]
+enum Binding {
+    One(Expression),
+    /// A `...rest` paramater.
+    Many(Vec<Expression>),
 }
 
 impl Macro {
-    pub fn new(name: String, paramaters: Vec<String>, body: ast::Statement, is_hygenic: bool) -> Macro {
+    pub fn new(
+        name: String,
+        params: Vec<Expression>,
+        body: ast::Statement,
+        is_hygenic: bool,
+        is_procedural: bool,
+    ) -> Macro {
         Macro {
             name,
-            paramaters,
+            params,
             body,
-            is_hygenic
+            is_hygenic,
+            is_procedural,
+            is_pub: false,
+            module: String::new(),
         }
     }
 
-    /// Compile a macro.
-    pub fn compile(&self, arguments: Vec<String>, transpiled_body: String, ejr_ref: &mut EJR) -> String {
-        let mut body = transpiled_body.clone();
+    /// Expand a call of this macro into its body.
+    ///
+    /// `#param` is replaced by the argument's AST, variables declared in the body get a
+    /// generated name and a argument with side effects that is used more than once is bound
+    /// to a temporary first. `to_js` is only called for `javascript {}` blocks and doc comments.
+    pub fn expand(
+        &self,
+        arguments: Vec<Expression>,
+        first_id: usize,
+        to_js: &mut dyn FnMut(&Expression) -> String,
+    ) -> Expansion {
+        let mut bindings = HashMap::new();
+        for (i, param) in self.params.iter().enumerate() {
+            match param {
+                Expression::SpreadExpression(_, name) => {
+                    let rest = arguments.iter().skip(i).cloned().collect();
+                    bindings.insert(param_name(name), Binding::Many(rest));
+                }
+                Expression::AssignExpression(_, name, default) => {
+                    let arg = arguments.get(i).cloned().unwrap_or(default.as_ref().to_owned());
+                    bindings.insert(param_name(name), Binding::One(arg));
+                }
+                Expression::Identifier(tk, name) | Expression::IdentifierWithType(tk, name, _) => {
+                    let arg = arguments
+                        .get(i)
+                        .cloned()
+                        .unwrap_or(Expression::Identifier(tk.to_owned(), "undefined".to_string()));
+                    bindings.insert(name.to_owned(), Binding::One(arg));
+                }
+                _ => {}
+            }
+        }
+
+        // First find the locals and how often each paramater is used.
+        let mut expander = Expander {
+            bindings,
+            renames: HashMap::new(),
+            js: HashMap::new(),
+            collecting: true,
+            declared: vec![],
+            uses: HashMap::new(),
+            has_raw_text: false,
+        };
+        expander.statement(self.body.clone());
 
-        if arguments.len() == 0 {
-            return body;
+        let mut next_id = first_id;
+        for name in std::mem::take(&mut expander.declared) {
+            if !expander.renames.contains_key(&name) {
+                expander.renames.insert(name.to_owned(), gensym(&name, next_id));
+                next_id += 1;
+            }
         }
 
-        if self.paramaters.len() == 0 {
-            return body;
+        let mut temps = vec![];
+        let mut names: Vec<String> = expander.bindings.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            let Some(Binding::One(arg)) = expander.bindings.get(&name).cloned() else {
+                continue;
+            };
+            if expander.uses.get(&name).cloned().unwrap_or(0) > 1 && has_side_effects(&arg) {
+                let temp = gensym(&name, next_id);
+                next_id += 1;
+                let ident = Expression::Identifier(arg.get_token().to_owned(), temp.to_owned());
+                expander.bindings.insert(name, Binding::One(ident));
+                temps.push((temp, arg));
+            }
         }
 
-        // The logic is:
-        // loop through each paramater and replace it where there is a '#' infront
+        if expander.has_raw_text {
+            for (name, binding) in expander.bindings.iter() {
+                let js = match binding {
+                    Binding::One(arg) => to_js(arg),
+                    Binding::Many(args) => args.iter().map(&mut *to_js).collect::<Vec<_>>().join(","),
+                };
+                expander.js.insert(name.to_owned(), js);
+            }
+        }
 
-        for (i, param) in self.paramaters.iter().enumerate() {
-            // Make sure name does not contain ...!
-            let param_name = if param.contains("...") {
-                param.replace("...", "")
-            } else {
-                param.clone()
-            };
-            // Make sure name does not contain key=value
-            let param_name = if param.contains("=") {
-                param.split("=").collect::<Vec<&str>>().get(0).unwrap().to_string()
-            } else {
-                param_name
-            };
+        expander.collecting = false;
+        let body = expander.statement(self.body.clone());
+
+        Expansion { body, temps, next_id }
+    }
+
+    /// Run the expanded body of a compile time macro, it returns the code to use.
+    pub fn compile(&self, script: String, ejr_ref: &mut EJR) -> String {
+        let val = ejr_ref.eval_script(&script, format!("<{}>", self.name).as_str());
+        if val == -1 {
+            return String::from("");
+        }
+
+        ejr_ref.val_to_string(val).unwrap()
+    }
+}
+
+/// The name of a macro paramater.
+fn param_name(param: &Expression) -> String {
+    match param {
+        Expression::Identifier(_, name) | Expression::IdentifierWithType(_, name, _) => name.to_owned(),
+        Expression::SpreadExpression(_, name) | Expression::AssignExpression(_, name, _) => param_name(name),
+        _ => String::new(),
+    }
+}
+
+/// A name that can not clash with the code around the macro call.
+fn gensym(name: &str, id: usize) -> String {
+    format!("__{}_{}", name.trim_start_matches('_'), id)
+}
+
+/// Could evaluating this expression twice do something different?
+fn has_side_effects(expr: &Expression) -> bool {
+    match expr {
+        Expression::Identifier(_, _)
+        | Expression::IntegerLiteral(_, _)
+        | Expression::FloatLiteral(_, _)
+        | Expression::BigIntLiteral(_, _)
+        | Expression::StringLiteral(_, _)
+        | Expression::Boolean(_, _)
+        | Expression::NullExpression(_)
+        | Expression::LambdaLiteral(_, _, _)
+        | Expression::FunctionLiteral(_, _, _, _, _, _, _) => false,
+        Expression::TemplateLiteral(_, _, exprs) => exprs.iter().any(has_side_effects),
+        Expression::ArrayLiteral(_, exprs) => exprs.iter().any(has_side_effects),
+        Expression::ObjectLiteral(_, properties) => properties
+            .iter()
+            .any(|property| property.last().is_some_and(|value| has_side_effects(value))),
+        Expression::PrefixExpression(_, operator, right) => {
+            operator == "++" || operator == "--" || has_side_effects(right)
+        }
+        Expression::GroupedExpression(_, inner)
+        | Expression::NotExpression(_, inner)
+        | Expression::SpreadExpression(_, inner)
+        | Expression::AsExpression(_, inner)
+        | Expression::CastExpression(_, inner, _) => has_side_effects(inner),
+        Expression::InfixExpression(_, left, _, right)
+        | Expression::AndExpression(_, left, right)
+        | Expression::OrExpression(_, left, right)
+        | Expression::DefaultIfNullExpression(_, left, right)
+        | Expression::DotExpression(_, left, right)
+        | Expression::OptionalDotExpression(_, left, right)
+        | Expression::IndexExpression(_, left, right)
+        | Expression::OptionalIndexExpression(_, left, right)
+        | Expression::IsExpression(_, left, right) => has_side_effects(left) || has_side_effects(right),
+        _ => true,
+    }
+}
+
+/// Walks a macro body, first to collect the locals and paramater uses then to expand it.
+struct Expander {
+    bindings: HashMap<String, Binding>,
+    /// locals declared in the body and their generated names.
+    renames: HashMap<String, String>,
+    /// The paramaters as JS, for `javascript {}` blocks and doc comments.
+    js: HashMap<String, String>,
+    collecting: bool,
+    declared: Vec<String>,
+    uses: HashMap<String, usize>,
+    has_raw_text: bool,
+}
+
+impl Expander {
+    fn declare(&mut self, name: &Expression) {
+        if !self.collecting {
+            return;
+        }
+        match name {
+            Expression::Identifier(_, name) | Expression::IdentifierWithType(_, name, _)
+                if !name.contains('#') && !self.declared.contains(name) =>
+            {
+                self.declared.push(name.to_owned());
+            }
+            Expression::ArrayLiteral(_, names) => {
+                for name in names.iter() {
+                    self.declare(name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn statements(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        stmts.into_iter().map(|stmt| self.statement(stmt)).collect()
+    }
+
+    fn statement(&mut self, stmt: Statement) -> Statement {
+        match stmt {
+            Statement::VariableStatement(tk, name, val_type, value, infer) => {
+                self.declare(&name);
+                Statement::VariableStatement(tk, self.boxed(name), val_type, self.boxed(value), infer)
+            }
+            Statement::ReturnStatement(tk, expr) => Statement::ReturnStatement(tk, self.boxed(expr)),
+            Statement::ExpressionStatement(tk, expr) => Statement::ExpressionStatement(tk, self.boxed(expr)),
+            Statement::BlockStatement(tk, stmts) => {
+                Statement::BlockStatement(tk, Box::new(self.statements(*stmts)))
+            }
+            Statement::ForStatement(tk, condition, body) => {
+                if let Expression::InExpression(_, item, _) | Expression::OfExpression(_, item, _) =
+                    condition.as_ref()
+                {
+                    self.declare(item);
+                }
+                Statement::ForStatement(tk, self.boxed(condition), self.boxed_statement(body))
+            }
+            Statement::DoWhileStatement(tk, body, condition) => {
+                Statement::DoWhileStatement(tk, self.boxed_statement(body), self.boxed(condition))
+            }
+            Statement::LabeledStatement(tk, label, body) => {
+                Statement::LabeledStatement(tk, label, self.boxed_statement(body))
+            }
+            Statement::JavaScriptStatement(tk, js) => Statement::JavaScriptStatement(tk, self.text(&js)),
+            Statement::ExportStatement(tk, stmt) => Statement::ExportStatement(tk, self.boxed_statement(stmt)),
+            Statement::AsyncBlockStatement(tk, body) => {
+                Statement::AsyncBlockStatement(tk, self.boxed_statement(body))
+            }
+            Statement::MatchStatement(tk, expr, arms) => {
+                let expr = self.boxed(expr);
+                let arms = arms
+                    .into_iter()
+                    .map(|(pattern, body)| (self.expression(pattern), self.statement(body)))
+                    .collect();
+                Statement::MatchStatement(tk, expr, Box::new(arms))
+            }
+            // The condition is only target and features.
+            Statement::CfgStatement(tk, condition, consequence, alternative) => Statement::CfgStatement(
+                tk,
+                condition,
+                self.boxed_statement(consequence),
+                self.boxed_statement(alternative),
+            ),
+            _ => stmt,
+        }
+    }
+
+    fn boxed(&mut self, mut expr: Box<Expression>) -> Box<Expression> {
+        *expr = self.expression(*expr);
+        expr
+    }
+
+    fn boxed_statement(&mut self, mut stmt: Box<Statement>) -> Box<Statement> {
+        *stmt = self.statement(*stmt);
+        stmt
+    }
+
+    /// Expand a list of expressions, a `...rest` paramater is spread into the list.
+    fn expressions(&mut self, exprs: Vec<Expression>) -> Vec<Expression> {
+        let mut result = vec![];
+        for expr in exprs {
+            if let Expression::Identifier(_, name) = &expr
+                && let Some(param) = name.strip_prefix('#')
+            {
+                match self.bindings.get(param).cloned() {
+                    Some(Binding::Many(args)) => {
+                        self.used(param);
+                        result.extend(args);
+                        continue;
+                    }
+                    // no need for () in a list.
+                    Some(Binding::One(arg)) => {
+                        self.used(param);
+                        result.push(arg);
+                        continue;
+                    }
+                    None => {}
+                }
+            }
+            result.push(self.expression(expr));
+        }
+        result
+    }
 
-            let param_name = param_name.trim();
+    fn used(&mut self, name: &str) {
+        if self.collecting {
+            *self.uses.entry(name.to_owned()).or_insert(0) += 1;
+        }
+    }
 
-            let replacement = arguments.get(i).cloned().unwrap_or_default();
-            let needle = format!("#{}", param_name);
+    fn expression(&mut self, expr: Expression) -> Expression {
+        match expr {
+            Expression::Identifier(tk, name) => self.identifier(tk, name, true),
+            Expression::IdentifierWithType(tk, name, val_type) => {
+                match self.identifier(tk.to_owned(), name, true) {
+                    Expression::Identifier(_, name) => Expression::IdentifierWithType(tk, name, val_type),
+                    expr => expr,
+                }
+            }
+            Expression::PrefixExpression(tk, operator, right) => {
+                Expression::PrefixExpression(tk, operator, self.boxed(right))
+            }
+            Expression::PostfixExpression(tk, left, operator) => {
+                Expression::PostfixExpression(tk, self.boxed(left), operator)
+            }
+            Expression::TemplateLiteral(tk, strings, exprs) => {
+                Expression::TemplateLiteral(tk, strings, self.expressions(exprs))
+            }
+            Expression::InfixExpression(tk, left, operator, right) => {
+                Expression::InfixExpression(tk, self.boxed(left), operator, self.boxed(right))
+            }
+            Expression::IfExpression(tk, condition, consequence, elseif, else_) => Expression::IfExpression(
+                tk,
+                self.boxed(condition),
+                self.boxed_statement(consequence),
+                self.boxed(elseif),
+                self.boxed_statement(else_),
+            ),
+            Expression::AsyncExpression(tk, expr) => Expression::AsyncExpression(tk, self.boxed(expr)),
+            Expression::AwaitExpression(tk, expr) => Expression::AwaitExpression(tk, self.boxed(expr)),
+            Expression::YieldExpression(tk, expr, delegate) => {
+                Expression::YieldExpression(tk, self.boxed(expr), delegate)
+            }
+            Expression::FunctionLiteral(tk, name, params, return_type, body, type_params, attributes) => {
+                let name = Box::new(self.member(*name));
+                Expression::FunctionLiteral(
+                    tk,
+                    name,
+                    Box::new(self.expressions(*params)),
+                    return_type,
+                    self.boxed_statement(body),
+                    type_params,
+                    Box::new(self.expressions(*attributes)),
+                )
+            }
+            Expression::LambdaLiteral(tk, params, body) => {
+                Expression::LambdaLiteral(tk, Box::new(self.expressions(*params)), self.boxed_statement(body))
+            }
+            Expression::CallExpression(tk, callee, args) => {
+                Expression::CallExpression(tk, self.boxed(callee), Box::new(self.expressions(*args)))
+            }
+            Expression::OptionalCallExpression(tk, callee, args) => {
+                Expression::OptionalCallExpression(tk, self.boxed(callee), Box::new(self.expressions(*args)))
+            }
+            Expression::InExpression(tk, left, right) => {
+                Expression::InExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::OfExpression(tk, left, right) => {
+                Expression::OfExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::RangeExpression(tk, start, end, step) => {
+                Expression::RangeExpression(tk, self.boxed(start), self.boxed(end), self.boxed(step))
+            }
+            Expression::DotExpression(tk, left, right) => {
+                let left = self.boxed(left);
+                Expression::DotExpression(tk, left, Box::new(self.member(*right)))
+            }
+            Expression::OptionalDotExpression(tk, left, right) => {
+                let left = self.boxed(left);
+                Expression::OptionalDotExpression(tk, left, Box::new(self.member(*right)))
+            }
+            Expression::DotIfExpression(tk, left, body) => {
+                Expression::DotIfExpression(tk, self.boxed(left), self.boxed_statement(body))
+            }
+            Expression::ArrayLiteral(tk, elements) => Expression::ArrayLiteral(tk, Box::new(self.expressions(*elements))),
+            Expression::IndexExpression(tk, left, index) => {
+                Expression::IndexExpression(tk, self.boxed(left), self.boxed(index))
+            }
+            Expression::OptionalIndexExpression(tk, left, index) => {
+                Expression::OptionalIndexExpression(tk, self.boxed(left), self.boxed(index))
+            }
+            Expression::ObjectLiteral(tk, properties) => {
+                let properties = properties
+                    .into_iter()
+                    .map(|property| {
+                        // `{ key: value }`, shorthand keys are left alone.
+                        if property.len() == 2 && property[0].get_token() != property[1].get_token() {
+                            let mut property = property.into_iter();
+                            let key = self.member(*property.next().unwrap());
+                            let value = self.expression(*property.next().unwrap());
+                            vec![Box::new(key), Box::new(value)]
+                        } else if property.len() == 2 {
+                            // `{ local }` becomes `{ local: __local_0 }` when the local is renamed.
+                            match self.expression(property[1].as_ref().to_owned()) {
+                                Expression::Identifier(mut tk, name) if tk.literal != name => {
+                                    // the transpiler writes `key: value` when the tokens are not the same.
+                                    tk.literal = name.to_owned();
+                                    vec![property[0].to_owned(), Box::new(Expression::Identifier(tk, name))]
+                                }
+                                _ => property,
+                            }
+                        } else {
+                            property
+                        }
+                    })
+                    .collect();
+                Expression::ObjectLiteral(tk, properties)
+            }
+            Expression::AssignExpression(tk, left, right) => {
+                Expression::AssignExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::NotExpression(tk, expr) => Expression::NotExpression(tk, self.boxed(expr)),
+            Expression::AsExpression(tk, expr) => Expression::AsExpression(tk, self.boxed(expr)),
+            Expression::MacroExpression(tk, name, args) => {
+                Expression::MacroExpression(tk, name, Box::new(self.expressions(*args)))
+            }
+            Expression::AndExpression(tk, left, right) => {
+                Expression::AndExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::OrExpression(tk, left, right) => {
+                Expression::OrExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::DefaultIfNullExpression(tk, left, right) => {
+                Expression::DefaultIfNullExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::NewClassExpression(tk, expr) => Expression::NewClassExpression(tk, self.boxed(expr)),
+            Expression::GroupedExpression(tk, expr) => Expression::GroupedExpression(tk, self.boxed(expr)),
+            Expression::IsExpression(tk, left, right) => {
+                Expression::IsExpression(tk, self.boxed(left), self.boxed(right))
+            }
+            Expression::CastExpression(tk, left, val_type) => {
+                Expression::CastExpression(tk, self.boxed(left), val_type)
+            }
+            Expression::BuiltinCall(tk, args) => Expression::BuiltinCall(tk, Box::new(self.expressions(*args))),
+            Expression::IIFE(tk, body) => Expression::IIFE(tk, self.boxed_statement(body)),
+            Expression::ComptimeExpression(tk, body) => Expression::ComptimeExpression(tk, self.boxed_statement(body)),
+            Expression::SpreadExpression(tk, expr) => Expression::SpreadExpression(tk, self.boxed(expr)),
+            Expression::DocCommentExpression(tk, lines) => {
+                let lines = lines.iter().map(|line| self.text(line)).collect();
+                Expression::DocCommentExpression(tk, lines)
+            }
+            _ => expr,
+        }
+    }
 
-            // Replace all occurrences of "#param"
-            body = body.replace(&needle, &replacement);
+    /// The right side of a `.` or a object key, these are names and not variables.
+    fn member(&mut self, expr: Expression) -> Expression {
+        match expr {
+            Expression::Identifier(tk, name) => self.identifier(tk, name, false),
+            Expression::CallExpression(tk, callee, args) => {
+                let callee = Box::new(self.member(*callee));
+                Expression::CallExpression(tk, callee, Box::new(self.expressions(*args)))
+            }
+            Expression::DotExpression(tk, left, right) => {
+                let left = Box::new(self.member(*left));
+                Expression::DotExpression(tk, left, Box::new(self.member(*right)))
+            }
+            Expression::IndexExpression(tk, left, index) => {
+                let left = Box::new(self.member(*left));
+                Expression::IndexExpression(tk, left, self.boxed(index))
+            }
+            // The right side of a dot can go on, i.e. `a.b ?? 1`. Only the start is a name.
+            Expression::InfixExpression(tk, left, operator, right) => {
+                let left = Box::new(self.member(*left));
+                Expression::InfixExpression(tk, left, operator, self.boxed(right))
+            }
+            Expression::DefaultIfNullExpression(tk, left, right) => {
+                let left = Box::new(self.member(*left));
+                Expression::DefaultIfNullExpression(tk, left, self.boxed(right))
+            }
+            Expression::AndExpression(tk, left, right) => {
+                let left = Box::new(self.member(*left));
+                Expression::AndExpression(tk, left, self.boxed(right))
+            }
+            Expression::OrExpression(tk, left, right) => {
+                let left = Box::new(self.member(*left));
+                Expression::OrExpression(tk, left, self.boxed(right))
+            }
+            Expression::AssignExpression(tk, left, right) => {
+                let left = Box::new(self.member(*left));
+                Expression::AssignExpression(tk, left, self.boxed(right))
+            }
+            Expression::PostfixExpression(tk, left, operator) => {
+                Expression::PostfixExpression(tk, Box::new(self.member(*left)), operator)
+            }
+            _ => self.expression(expr),
         }
+    }
 
-        if self.is_hygenic {
-            let val = ejr_ref.eval_script(&body, format!("<{}>", self.name).as_str());
-            if val == -1 {
-                return String::from("");
+    fn identifier(&mut self, tk: Token, name: String, is_variable: bool) -> Expression {
+        if let Some(param) = name.strip_prefix('#')
+            && let Some(binding) = self.bindings.get(param).cloned()
+        {
+            self.used(param);
+            return match binding {
+                Binding::One(arg) if needs_grouping(&arg) => {
+                    Expression::GroupedExpression(tk, Box::new(arg))
+                }
+                Binding::One(arg) => arg,
+                Binding::Many(args) => Expression::ArrayLiteral(tk, Box::new(args)),
+            };
+        }
+
+        // #name_suffix pastes the argument into the name.
+        if name.contains('#') {
+            return Expression::Identifier(tk, self.paste(&name));
+        }
+
+        if is_variable && let Some(renamed) = self.renames.get(&name) {
+            return Expression::Identifier(tk, renamed.to_owned());
+        }
+        Expression::Identifier(tk, name)
+    }
+
+    /// Replace `#param` within a name. i.e. `#name_to_string`
+    fn paste(&mut self, name: &str) -> String {
+        let mut params: Vec<String> = self.bindings.keys().cloned().collect();
+        params.sort_by_key(|param| std::cmp::Reverse(param.len()));
+
+        let mut result = name.to_owned();
+        for param in params {
+            let needle = format!("#{}", param);
+            if !result.contains(&needle) {
+                continue;
             }
+            self.used(&param);
+            let value = match self.bindings.get(&param) {
+                Some(Binding::One(Expression::Identifier(_, value))) => value.to_owned(),
+                Some(Binding::One(Expression::StringLiteral(_, value))) => value.to_owned(),
+                _ => continue,
+            };
+            result = result.replace(&needle, &value);
+        }
+        result
+    }
 
-            return ejr_ref.val_to_string(val).unwrap();
+    /// Expand raw text. i.e. a `javascript {}` block or a doc comment.
+    fn text(&mut self, text: &str) -> String {
+        if self.collecting {
+            self.has_raw_text = true;
+            for param in self.bindings.keys().cloned().collect::<Vec<_>>() {
+                for _ in text.matches(&format!("#{}", param)) {
+                    self.used(&param);
+                }
+            }
+            return text.to_owned();
         }
 
-        body
+        let mut params: Vec<&String> = self.js.keys().collect();
+        params.sort_by_key(|param| std::cmp::Reverse(param.len()));
+
+        let mut result = text.to_owned();
+        for param in params {
+            result = result.replace(&format!("#{}", param), &self.js[param]);
+        }
+        for (name, renamed) in self.renames.iter() {
+            let word = Regex::new(&format!(r"\b{}\b", regex::escape(name))).unwrap();
+            result = word.replace_all(&result, renamed.as_str()).to_string();
+        }
+        result
     }
 }
+
+/// Does this argument need `()` to keep its meaning within the macro body? i.e. `#a * 2`
+fn needs_grouping(arg: &Expression) -> bool {
+    !matches!(
+        arg,
+        Expression::Identifier(_, _)
+            | Expression::IntegerLiteral(_, _)
+            | Expression::FloatLiteral(_, _)
+            | Expression::BigIntLiteral(_, _)
+            | Expression::StringLiteral(_, _)
+            | Expression::TemplateLiteral(_, _, _)
+            | Expression::Boolean(_, _)
+            | Expression::NullExpression(_)
+            | Expression::ArrayLiteral(_, _)
+            | Expression::ObjectLiteral(_, _)
+            | Expression::CallExpression(_, _, _)
+            | Expression::OptionalCallExpression(_, _, _)
+            | Expression::DotExpression(_, _, _)
+            | Expression::OptionalDotExpression(_, _, _)
+            | Expression::IndexExpression(_, _, _)
+            | Expression::OptionalIndexExpression(_, _, _)
+            | Expression::GroupedExpression(_, _)
+            | Expression::MacroExpression(_, _, _)
+    )
+}
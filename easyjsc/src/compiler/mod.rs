@@ -1,6 +1,7 @@
 pub mod transpile;
+pub mod dts;
+pub mod cfg;
 mod import;
 mod macros;
 mod native;
-mod runes;
-mod namespaces;
\ No newline at end of file
+pub mod namespaces;
\ No newline at end of file
@@ -14,10 +14,13 @@
 
 use std::collections::HashMap;
 
-use crate::typechecker::StrongValType;
+use crate::typechecker::{StrongValType, make_union};
 
 pub const NAMESPACE_PREFIX: &str = "_";
 
+/// How deep a alias can point to other aliases before we give up. i.e. `type A = B`, `type B = A`
+const MAX_ALIAS_DEPTH: usize = 32;
+
 #[derive(Debug, Clone)]
 /// easyjs variables. Not native variables.
 pub struct Variable {
@@ -38,6 +41,8 @@ pub struct Function {
     pub params: Vec<Variable>,
     /// The function return type
     pub return_type: StrongValType,
+    /// The function type paramaters. i.e. `T` in `fn first<T>(xs: Array<T>): T`
+    pub type_params: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +58,8 @@ pub struct Struct {
     pub methods: Vec<Function>,
     /// The static methods of the struct
     pub static_methods: Vec<Function>,
+    /// The mixins of the struct
+    pub mixins: Vec<String>,
 }
 
 // /// Used only in transpiler and type checker.
@@ -66,12 +73,24 @@ pub struct Struct {
 //     name: String,
 // }
 
+#[derive(Debug, Clone)]
+/// A native struct. Used to convert it to and from JS objects.
+pub struct NativeStruct {
+    /// The name of the struct
+    pub name: String,
+    /// The fields in the order they are laid out in memory
+    pub fields: Vec<Variable>,
+    /// The methods that can be called from JS, only when the struct is `pub`.
+    pub methods: Vec<String>,
+}
+
 /// Used only in transpiler and type checker.
 /// Holds all native for project.
 #[derive(Debug, Clone)]
 pub struct Native {
     pub functions: Vec<Function>,
     pub variables: Vec<Variable>,
+    pub structs: Vec<NativeStruct>,
 }
 
 #[derive(Debug, Clone)]
@@ -89,6 +108,12 @@ pub struct Namespace {
     pub structs: Vec<Struct>,
     /// The macros associated with the namespace. In order to access a macro you have to use id.@macro
     pub macros: HashMap<String, crate::compiler::macros::Macro>,
+    /// The type aliases associated with the namespace. i.e. `type UserId = int`
+    pub type_aliases: HashMap<String, StrongValType>,
+    /// The names of the classes associated with the namespace.
+    pub classes: Vec<String>,
+    /// The names of the enums associated with the namespace.
+    pub enums: Vec<String>,
     /// The native context of this namespace
     pub native_ctx: Native,
 }
@@ -103,9 +128,13 @@ impl Namespace {
             functions: vec![],
             structs: vec![],
             macros: HashMap::new(),
+            type_aliases: HashMap::new(),
+            classes: vec![],
+            enums: vec![],
             native_ctx: Native {
                 functions: vec![],
                 variables: vec![],
+                structs: vec![],
             },
         }
     }
@@ -137,6 +166,66 @@ impl Namespace {
         }
     }
 
+    /// Resolve a type written in this namespace.
+    ///
+    /// Aliases become the type they alias, structs, classes and enums get their namespaced name
+    /// and `alias.Name` is looked up in the imported module. Anything else is left as is.
+    pub fn resolve_type(&self, strong: &StrongValType, modules: &[Namespace]) -> StrongValType {
+        self.resolve_type_at_depth(strong, modules, 0)
+    }
+
+    fn resolve_type_at_depth(
+        &self,
+        strong: &StrongValType,
+        modules: &[Namespace],
+        depth: usize,
+    ) -> StrongValType {
+        if depth > MAX_ALIAS_DEPTH {
+            return StrongValType::None;
+        }
+
+        match strong {
+            StrongValType::Custom(name) => {
+                if let Some((alias, name)) = name.split_once('.') {
+                    return match modules.iter().find(|m| m.has_name(&alias.to_string())) {
+                        Some(module) => module.resolve_type_at_depth(
+                            &StrongValType::Custom(name.to_string()),
+                            modules,
+                            depth + 1,
+                        ),
+                        None => strong.to_owned(),
+                    };
+                }
+
+                let full_name = self.get_obj_name(name);
+                if let Some(aliased) = self.type_aliases.get(&full_name) {
+                    return self.resolve_type_at_depth(aliased, modules, depth + 1);
+                }
+                if self.struct_exists(full_name.clone())
+                    || self.classes.contains(&full_name)
+                    || self.enums.contains(&full_name)
+                {
+                    return StrongValType::Custom(full_name);
+                }
+                strong.to_owned()
+            }
+            StrongValType::Generic(name, type_args) => StrongValType::Generic(
+                name.to_owned(),
+                type_args
+                    .iter()
+                    .map(|type_arg| self.resolve_type_at_depth(type_arg, modules, depth + 1))
+                    .collect(),
+            ),
+            StrongValType::Union(types) => make_union(
+                types
+                    .iter()
+                    .map(|member| self.resolve_type_at_depth(member, modules, depth + 1))
+                    .collect(),
+            ),
+            _ => strong.to_owned(),
+        }
+    }
+
     /// Check if this namespace has said name
     pub fn has_name(&self, name: &String) -> bool {
         if &self.alias == name {
@@ -12,37 +12,53 @@ use crate::{
             native_arr_store_length,
         },
         builtins::{
-            ALLOCATE_STRING_IDX, ARR_ALLOCATE_IDX, ARR_GET_ITEM_IDX, ARR_PUSH_ARRAY_IDX,
+            ALLOCATE_STRING_IDX, ARR_ALLOCATE_IDX, ARR_GET_ITEM_IDX, ARR_GET_LEN_IDX, ARR_PUSH_ARRAY_IDX,
             ARR_PUSH_FLOAT_IDX, ARR_PUSH_INT_IDX, ARR_PUSH_STRING_IDX, ARR_STORE_CAPACITY_IDX,
             ARR_STORE_LENGTH_IDX, STORE_STRING_LENGTH_IDX, STR_CONCAT_IDX, STR_GET_LEN_IDX,
             STR_INDEX_IDX, STR_STORE_BYTE_IDX,
         },
         instruction_generator::{EasyInstructions, set_local_string},
+        numbers::{
+            convert_number, get_assign_operator, is_comparison_operator, is_implicit_conversion,
+            is_number_type, number_one, number_operation, parse_i64_literal, promote_number_types,
+        },
         signatures::{
-            EasyNativeBlock, EasyNativeFN, EasyNativeVar, FunctionSignature, create_type_section,
+            EasyNativeBlock, EasyNativeBlockKind, EasyNativeFN, EasyNativeField, EasyNativeStruct,
+            EasyNativeVar, FunctionSignature, create_type_section,
         },
         strings::{
             allocate_string, native_str_char_code_at, native_str_concat, native_str_get_len,
             native_str_index, native_str_store_byte, store_string_length,
         },
+        structs::{
+            create_struct_layout, load_field, native_struct_alloc, store_field,
+            struct_method_to_function,
+        },
         utils::expression_is_ident,
     },
     errors::{
         native_can_not_compile_raw_expression, native_can_not_get_value_from_expression,
+        native_conversion_needs_cast, native_unsupported_cast,
         native_could_not_parse_function, native_error_compiling_identifier,
-        native_if_expression_must_go_within_functions,
-        native_no_function_provided_for_variable_scope,
+        native_if_expression_must_go_within_functions, native_integer_out_of_range,
+        native_jump_outside_of_loop, native_loop_must_go_within_functions,
+        native_no_function_provided_for_variable_scope, native_struct_field_needs_type,
+        native_struct_has_no_field, native_struct_wrong_number_of_fields,
         native_return_value_does_not_match_function, native_unsupported_builtin_call,
         native_unsupported_expression, native_unsupported_expression_as_value_for_global_variable,
         native_unsupported_expression_in_array, native_unsupported_index_expression,
         native_unsupported_operation, native_unsupported_operator,
-        native_unsupported_prefix_expression, native_unsupported_statement,
+        native_unknown_loop_label, native_unsupported_prefix_expression,
+        native_unsupported_statement,
+    },
+    lexer::{
+        lex::unescape_string,
+        token::{self, Token},
     },
-    lexer::token::{self, Token},
     parser::ast::{Expression, Statement},
     typechecker::{
         I32_TYPE_IDX, StrongValType, get_param_type_by_named_expression, get_param_type_by_string,
-        get_val_type_from_strong,
+        get_string_rep_of_type, get_val_type_from_strong,
     },
 };
 use wasm_encoder::{
@@ -82,6 +98,7 @@ macro_rules! get_left_side_idx {
 /// - -=
 /// - *=
 /// - /*
+/// - %= &= |= ^= <<= >>= >>>=
 ///
 /// Params:
 /// - `left:Expression` The left side expression
@@ -360,6 +377,15 @@ struct NativeContext {
 
     /// The current imported modules
     imported_modules: Vec<Namespace>,
+
+    /// The structs and their memory layout.
+    structs: Vec<EasyNativeStruct>,
+
+    /// The idx of __struct_alloc, it is only added once a struct is declared.
+    struct_alloc_idx: Option<u32>,
+
+    /// The return type of the function being compiled.
+    return_type: StrongValType,
 }
 
 impl NativeContext {
@@ -376,6 +402,9 @@ impl NativeContext {
             block_scope: Vec::new(),
             namespace: Namespace::new("".to_string(), "_".to_string()),
             imported_modules: vec![],
+            structs: vec![],
+            struct_alloc_idx: None,
+            return_type: StrongValType::None,
         }
     }
 
@@ -405,7 +434,7 @@ impl NativeContext {
         self.is_pub = is_pub;
         match stmt {
             Statement::VariableStatement(_, name, val_type, value, _) => {
-                self.compile_variable_stmt(name, value, true);
+                self.compile_variable_stmt(name, val_type, value, true);
             }
             // Statement::ConstVariableStatement(_, name, val_type, value, _) => {
             //     self.compile_variable_stmt(name, value, false);
@@ -418,7 +447,9 @@ impl NativeContext {
                 }
             }
             Statement::ReturnStatement(token, expr) => {
-                let mut instructions = self.compile_expression(expr);
+                let instructions = self.compile_expression(expr);
+                let return_type = self.return_type.clone();
+                let mut instructions = self.convert_instructions(token, instructions, &return_type);
                 if let Some(current_fn_ins) = self.instructions.get_mut(&self.next_fn_idx) {
                     current_fn_ins.append(&mut instructions);
                     current_fn_ins.push(Instruction::Return);
@@ -430,6 +461,25 @@ impl NativeContext {
                     self.compile_statement(stmt, is_pub);
                 }
             }
+            Statement::ForStatement(token, condition, body) => {
+                self.compile_for_stmt(token, condition, body, None);
+            }
+            Statement::LabeledStatement(_, label, stmt) => match stmt.as_ref() {
+                Statement::ForStatement(token, condition, body) => {
+                    self.compile_for_stmt(token, condition, body, Some(label.clone()));
+                }
+                _ => self.compile_statement(stmt, is_pub),
+            },
+            Statement::StructStatement(token, name, fields, _, _, variables, methods, _) => {
+                let fields = fields.as_deref().map(Vec::as_slice).unwrap_or_default();
+                self.compile_struct_stmt(token, name, fields, variables, methods);
+            }
+            Statement::BreakStatement(token, label) => {
+                self.compile_jump(token, label, EasyNativeBlockKind::Break);
+            }
+            Statement::ContinueStatement(token, label) => {
+                self.compile_jump(token, label, EasyNativeBlockKind::Continue);
+            }
             _ => {
                 // This stmt is not supported in native blocks (yet)
                 self.errors
@@ -442,10 +492,21 @@ impl NativeContext {
     /// Compile a native variable.
     ///
     /// It is important to note whether or not the variable is in the global scope.
-    fn compile_variable_stmt(&mut self, name: &Expression, value: &Expression, is_mut: bool) {
+    ///
+    /// Without a type the variable has the type of its value.
+    fn compile_variable_stmt(
+        &mut self,
+        name: &Expression,
+        val_type: &Option<Box<Expression>>,
+        value: &Expression,
+        is_mut: bool,
+    ) {
         // get variable name as raw expression
         let var_name = self.compile_raw_expression(name);
-        let strong_val_type = self.get_val_type_from_expression(&value);
+        let strong_val_type = match val_type {
+            Some(val_type) => self.get_val_type_from_expression(val_type),
+            None => self.get_val_type_from_expression(value),
+        };
 
         // check if variable already exists in scope
         let mut easy_native_var: Option<&EasyNativeVar> = None;
@@ -468,7 +529,9 @@ impl NativeContext {
             let easy_native_var = easy_native_var.clone();
             // We should simply get and set the variable
             // First let's compiile the value
-            let mut instructions = self.compile_expression(value);
+            let instructions = self.compile_expression(value);
+            let mut instructions =
+                self.convert_instructions(name.get_token(), instructions, &easy_native_var.val_type);
 
             // Let's check if we have a function context.
             if let Some(current_fn_ins) = self.instructions.get_mut(&self.next_fn_idx) {
@@ -489,11 +552,12 @@ impl NativeContext {
         }
 
         if self.is_currently_global {
-            let parsed = self.compile_global_variable_stmt(value);
-            // add to variable
+            let parsed = self.compile_global_variable_stmt(value, &strong_val_type);
+            // add to variable, globals are counted apart from the locals of functions.
+            let global_idx = self.variable_scope[0].len() as u32;
             self.variable_scope[0].push(EasyNativeVar {
                 name: self.namespace.get_obj_name(&var_name.clone()),
-                idx: self.next_var_idx,
+                idx: global_idx,
                 is_global: true,
                 value: parsed,
                 val_type: strong_val_type.clone(),
@@ -505,7 +569,13 @@ impl NativeContext {
                 is_mut: is_mut,
                 val_type: strong_val_type.clone(),
             });
+            return;
         } else {
+            // Instance the instructions first, the value can add locals of its own.
+            let value_instructions = self.compile_expression(value);
+            let mut value_instructions =
+                self.convert_instructions(name.get_token(), value_instructions, &strong_val_type);
+
             // add to variable scope
             self.variable_scope.last_mut().unwrap().push(EasyNativeVar {
                 name: var_name,
@@ -516,9 +586,6 @@ impl NativeContext {
                 is_mut,
             });
 
-            // Instance the instructions
-            let mut value_instructions = self.compile_expression(value);
-
             // add to instructions
             if let Some(current_fn_ins) = self.instructions.get_mut(&self.next_fn_idx) {
                 current_fn_ins.append(&mut value_instructions);
@@ -537,16 +604,585 @@ impl NativeContext {
         self.next_var_idx += 1;
     }
 
+    /// Compile a `for` loop.
+    ///
+    /// All loops are compiled to the same shape:
+    ///
+    /// ```wat
+    /// block         ;; break jumps to the end of this block.
+    ///   loop
+    ///     condition
+    ///     i32.eqz
+    ///     br_if 1
+    ///     next item ;; only for arrays.
+    ///     block     ;; continue jumps to the end of this block.
+    ///       body
+    ///     end
+    ///     step      ;; only for ranges and arrays.
+    ///     br 0
+    ///   end
+    /// end
+    /// ```
+    fn compile_for_stmt(
+        &mut self,
+        token: &Token,
+        condition: &Expression,
+        body: &Statement,
+        label: Option<String>,
+    ) {
+        if !self.instructions.contains_key(&self.next_fn_idx) || self.block_scope.is_empty() {
+            self.errors.push(native_loop_must_go_within_functions(token));
+            return;
+        }
+
+        let (mut check, next, mut step) = match condition {
+            Expression::InExpression(_, left, right) | Expression::OfExpression(_, left, right) => {
+                match right.as_ref() {
+                    Expression::RangeExpression(tk, start, end, step) => {
+                        self.compile_range_loop(left, tk, start, end, step)
+                    }
+                    _ => self.compile_array_loop(left, right),
+                }
+            }
+            // anything else loops while it is true.
+            _ => (self.compile_expression(condition), vec![], vec![]),
+        };
+
+        self.add_instructions(vec![Instruction::Block(BlockType::Empty)]);
+        self.push_block(EasyNativeBlockKind::Break, label.clone());
+        self.add_instructions(vec![Instruction::Loop(BlockType::Empty)]);
+        self.push_block(EasyNativeBlockKind::Loop, label.clone());
+
+        check.append(&mut vec![Instruction::I32Eqz, Instruction::BrIf(1)]);
+        self.add_instructions(check);
+        self.add_instructions(next);
+
+        self.add_instructions(vec![Instruction::Block(BlockType::Empty)]);
+        self.push_block(EasyNativeBlockKind::Continue, label);
+        self.compile_statement(body, self.is_pub);
+        self.add_instructions(vec![Instruction::End]);
+        self.pop_block();
+
+        step.push(Instruction::Br(0));
+        self.add_instructions(step);
+        // end loop and block
+        self.add_instructions(vec![Instruction::End, Instruction::End]);
+        self.pop_block();
+        self.pop_block();
+    }
+
+    /// Compile the loop variable of `for i in start..end step n`.
+    ///
+    /// Like in JS a range of literals counts down when start is bigger than end, any other range
+    /// counts up unless the step is negative.
+    ///
+    /// Returns the (check, next, step) instructions of the loop.
+    fn compile_range_loop(
+        &mut self,
+        ident: &Expression,
+        token: &Token,
+        start: &Expression,
+        end: &Expression,
+        step: &Expression,
+    ) -> (EasyInstructions, EasyInstructions, EasyInstructions) {
+        let name = self.compile_raw_expression(ident);
+        self.compile_variable_stmt(ident, &None, start, true);
+        let current = self.compile_expression(ident);
+        if current.is_empty() {
+            return (vec![], vec![], vec![]);
+        }
+        let (is_global, idx) = get_left_side_idx!(&current);
+        let (get, set) = get_set_instructions(is_global, idx as u32);
+
+        let end_idx = self.add_local(format!("__{}_end", name), StrongValType::Int);
+        let step_idx = self.add_local(format!("__{}_step", name), StrongValType::Int);
+
+        // Does a range of literals count down?
+        let literal_descending = match (start.get_int_literal(), end.get_int_literal()) {
+            (Some(start), Some(end)) => Some(start > end),
+            _ => None,
+        };
+        let step_size = if step.is_empty() { Some(1) } else { step.get_int_literal() };
+        // None when the sign of the step is only known at runtime.
+        let descending = step_size.map(|size| literal_descending.unwrap_or(size < 0));
+
+        let mut setup = self.compile_expression(end);
+        setup.push(Instruction::LocalSet(end_idx));
+        match (step_size, descending) {
+            (Some(size), Some(true)) => setup.push(Instruction::I32Const(-(size.abs() as i32))),
+            (Some(size), _) => setup.push(Instruction::I32Const(size.abs() as i32)),
+            // 10..0 step n moves down by n
+            (None, _) if literal_descending == Some(true) => {
+                setup.push(Instruction::I32Const(0));
+                setup.append(&mut self.compile_expression(step));
+                setup.push(Instruction::I32Sub);
+            }
+            (None, _) => setup.append(&mut self.compile_expression(step)),
+        }
+        setup.push(Instruction::LocalSet(step_idx));
+        self.add_instructions(setup);
+
+        let (up, down) = if token.typ == token::DOTDOT_EQUALS {
+            (Instruction::I32LeS, Instruction::I32GeS)
+        } else {
+            (Instruction::I32LtS, Instruction::I32GtS)
+        };
+        let check = match descending {
+            Some(true) => vec![get.clone(), Instruction::LocalGet(end_idx), down],
+            Some(false) => vec![get.clone(), Instruction::LocalGet(end_idx), up],
+            // step > 0 ? i < end : step < 0 && i > end, a step of 0 never runs.
+            None => vec![
+                get.clone(),
+                Instruction::LocalGet(end_idx),
+                up,
+                get.clone(),
+                Instruction::LocalGet(end_idx),
+                down,
+                Instruction::LocalGet(step_idx),
+                Instruction::I32Const(0),
+                Instruction::I32LtS,
+                Instruction::I32And,
+                Instruction::LocalGet(step_idx),
+                Instruction::I32Const(0),
+                Instruction::I32GtS,
+                Instruction::Select,
+            ],
+        };
+        let step = vec![get, Instruction::LocalGet(step_idx), Instruction::I32Add, set];
+
+        (check, vec![], step)
+    }
+
+    /// Compile the item of `for item in array` using `__arr_get_len` and `__arr_get_item`.
+    ///
+    /// Items are read as ints.
+    ///
+    /// Returns the (check, next, step) instructions of the loop.
+    fn compile_array_loop(
+        &mut self,
+        ident: &Expression,
+        array: &Expression,
+    ) -> (EasyInstructions, EasyInstructions, EasyInstructions) {
+        let name = self.compile_raw_expression(ident);
+
+        let array_idx = self.add_local(format!("__{}_array", name), StrongValType::Array);
+        let index_idx = self.add_local(format!("__{}_idx", name), StrongValType::Int);
+        let item_idx = match self.variable_scope[1].iter().find(|v| v.name == name) {
+            Some(var) => var.idx,
+            None => self.add_local(name, StrongValType::Int),
+        };
+
+        let mut setup = self.compile_expression(array);
+        setup.append(&mut vec![
+            Instruction::LocalSet(array_idx),
+            Instruction::I32Const(0),
+            Instruction::LocalSet(index_idx),
+        ]);
+        self.add_instructions(setup);
+
+        let check = vec![
+            Instruction::LocalGet(index_idx),
+            Instruction::LocalGet(array_idx),
+            Instruction::Call(ARR_GET_LEN_IDX),
+            Instruction::I32LtS,
+        ];
+
+        // __arr_get_item returns [type, int, float, string, array], keep the int.
+        let next = vec![
+            Instruction::LocalGet(array_idx),
+            Instruction::LocalGet(index_idx),
+            Instruction::Call(ARR_GET_ITEM_IDX),
+            Instruction::Drop,
+            Instruction::Drop,
+            Instruction::Drop,
+            Instruction::LocalSet(item_idx),
+            Instruction::Drop,
+        ];
+
+        let step = vec![
+            Instruction::LocalGet(index_idx),
+            Instruction::I32Const(1),
+            Instruction::I32Add,
+            Instruction::LocalSet(index_idx),
+        ];
+
+        (check, next, step)
+    }
+
+    /// Compile a `break` or `continue`.
+    ///
+    /// The depth of the `br` is the amount of blocks between the jump and the loop it belongs to.
+    fn compile_jump(&mut self, token: &Token, label: &Option<String>, kind: EasyNativeBlockKind) {
+        let depth = self.block_scope.last().and_then(|blocks| {
+            blocks
+                .iter()
+                .rev()
+                .position(|block| block.kind == kind && (label.is_none() || block.label == *label))
+        });
+
+        match (depth, label) {
+            (Some(depth), _) => self.add_instructions(vec![Instruction::Br(depth as u32)]),
+            (None, Some(label)) => self.errors.push(native_unknown_loop_label(token, label)),
+            (None, None) => self.errors.push(native_jump_outside_of_loop(token)),
+        }
+    }
+
+    /// Add a block to the current block scope.
+    fn push_block(&mut self, kind: EasyNativeBlockKind, label: Option<String>) {
+        if let Some(blocks) = self.block_scope.last_mut() {
+            blocks.push(EasyNativeBlock {
+                idx: blocks.len() as u32,
+                block_type: None,
+                strong_block_type: StrongValType::None,
+                kind,
+                label,
+            });
+        }
+    }
+
+    /// Pop the last block of the current block scope.
+    fn pop_block(&mut self) {
+        if let Some(blocks) = self.block_scope.last_mut() {
+            blocks.pop();
+        }
+    }
+
+    /// Add a local variable to the current function and return its idx.
+    fn add_local(&mut self, name: String, val_type: StrongValType) -> u32 {
+        let idx = self.next_var_idx;
+        self.next_var_idx += 1;
+        self.variable_scope.get_mut(1).unwrap().push(EasyNativeVar {
+            name,
+            idx,
+            is_global: false,
+            value: ConstExpr::empty(),
+            val_type,
+            is_mut: true,
+        });
+        idx
+    }
+
+    /// Add instructions to the current function.
+    fn add_instructions(&mut self, mut instructions: EasyInstructions) {
+        if let Some(current_fn_ins) = self.instructions.get_mut(&self.next_fn_idx) {
+            current_fn_ins.append(&mut instructions);
+        }
+    }
+
+    /// Compile a native struct.
+    ///
+    /// The fields are laid out in memory and the methods become functions named `Struct_method`.
+    fn compile_struct_stmt(
+        &mut self,
+        token: &Token,
+        name: &Expression,
+        fields: &[Expression],
+        variables: &[Statement],
+        methods: &[Expression],
+    ) {
+        let raw_name = self.compile_raw_expression(name);
+        let struct_name = self.namespace.get_obj_name(&raw_name);
+
+        let mut typed_fields = vec![];
+        for field in fields {
+            match field {
+                // A struct can point to itself, i.e. `next: Node`
+                Expression::IdentifierWithType(_, field_name, val_type)
+                    if matches!(val_type.as_ref(), Expression::Type(_, t) if *t == raw_name) =>
+                {
+                    typed_fields
+                        .push((field_name.to_owned(), StrongValType::Custom(struct_name.clone())));
+                }
+                Expression::IdentifierWithType(_, field_name, _) => {
+                    let val_type = self.get_val_type_from_expression(field);
+                    typed_fields.push((field_name.to_owned(), val_type));
+                }
+                _ => {
+                    let field_name = self.compile_raw_expression(field);
+                    self.errors
+                        .push(native_struct_field_needs_type(field.get_token(), &field_name));
+                }
+            }
+        }
+
+        // static variables are not supported (yet)
+        for variable in variables {
+            self.errors
+                .push(native_unsupported_statement(variable.get_token()));
+        }
+
+        // Structs need to be allocated
+        if self.struct_alloc_idx.is_none() {
+            self.struct_alloc_idx = Some(self.next_fn_idx);
+            self.functions.push(native_struct_alloc(self.next_fn_idx));
+            self.next_fn_idx += 1;
+        }
+
+        self.structs
+            .push(create_struct_layout(&struct_name, &typed_fields));
+
+        for method in methods {
+            if let Some(Expression::FunctionLiteral(_, name, params, val_type, body, _, _)) =
+                struct_method_to_function(&struct_name, method)
+            {
+                self.compile_function_literal(&name, &params, &val_type, &body);
+            }
+        }
+    }
+
+    /// Compile `Struct(fields...)`.
+    ///
+    /// Allocates the struct, stores the fields and leaves the pointer on the stack.
+    fn compile_struct_constructor(
+        &mut self,
+        token: &Token,
+        native_struct: &EasyNativeStruct,
+        arguments: &[Expression],
+    ) -> EasyInstructions {
+        if arguments.len() != native_struct.fields.len() {
+            self.errors.push(native_struct_wrong_number_of_fields(
+                token,
+                &native_struct.name,
+                native_struct.fields.len(),
+                arguments.len(),
+            ));
+            return vec![];
+        }
+
+        let ptr_idx = self.add_local(
+            format!("__{}_ptr", native_struct.name),
+            StrongValType::Custom(native_struct.name.clone()),
+        );
+        let mut instructions = vec![
+            Instruction::I32Const(native_struct.size as i32),
+            Instruction::Call(self.struct_alloc_idx.unwrap()),
+            Instruction::LocalSet(ptr_idx),
+        ];
+        for (field, argument) in native_struct.fields.iter().zip(arguments.iter()) {
+            instructions.push(Instruction::LocalGet(ptr_idx));
+            instructions.append(&mut self.compile_field_value(field, argument));
+            instructions.push(store_field(field));
+        }
+        instructions.push(Instruction::LocalGet(ptr_idx));
+
+        instructions
+    }
+
+    /// Compile the value for a field, numbers are converted to the type of the field.
+    fn compile_field_value(&mut self, field: &EasyNativeField, value: &Expression) -> EasyInstructions {
+        let instructions = self.compile_expression(value);
+        self.convert_instructions(value.get_token(), instructions, &field.val_type)
+    }
+
+    /// Convert compiled instructions to `to`.
+    ///
+    /// Numbers only go to a higher rank without `as` (i32 types convert to each other), other values are left as is.
+    fn convert_instructions(
+        &mut self,
+        token: &Token,
+        mut instructions: EasyInstructions,
+        to: &StrongValType,
+    ) -> EasyInstructions {
+        let Some(last) = instructions.last() else {
+            return instructions;
+        };
+        let from = self.get_val_type_from_instruction(last);
+        if !is_number_type(&from) || !is_number_type(to) {
+            return instructions;
+        }
+        if !is_implicit_conversion(&from, to) {
+            self.errors.push(native_conversion_needs_cast(
+                token,
+                &get_string_rep_of_type(&from),
+                &get_string_rep_of_type(to),
+            ));
+            return instructions;
+        }
+
+        instructions.append(&mut convert_number(&from, to));
+        instructions
+    }
+
+    /// Compile the arguments of a call, each one is converted to the type of its paramater.
+    ///
+    /// `first_param` skips paramaters that are already on the stack, i.e. `self`.
+    fn compile_arguments(
+        &mut self,
+        fun_idx: u32,
+        arguments: &[Expression],
+        first_param: usize,
+    ) -> EasyInstructions {
+        let param_types = self
+            .functions
+            .iter()
+            .find(|function| function.idx == fun_idx)
+            .map(|function| function.signature.param_strong.clone())
+            .unwrap_or_default();
+
+        let mut instructions = vec![];
+        for (i, argument) in arguments.iter().enumerate() {
+            let argument_instructions = self.compile_expression(argument);
+            let mut argument_instructions = match param_types.get(first_param + i) {
+                Some(param_type) => {
+                    self.convert_instructions(argument.get_token(), argument_instructions, param_type)
+                }
+                None => argument_instructions,
+            };
+            instructions.append(&mut argument_instructions);
+        }
+
+        instructions
+    }
+
+    /// Compile a DotExpression on a struct. i.e. `v.x`, `v.length()` or `Vec.new()`
+    ///
+    /// Returns None if the left side is not a struct.
+    fn compile_struct_dot_expression(
+        &mut self,
+        left: &Expression,
+        right: &Expression,
+    ) -> Option<EasyInstructions> {
+        // Static methods
+        if let (Expression::Identifier(_, name), Expression::CallExpression(tk, method, arguments)) =
+            (left, right)
+            && let Some(native_struct) = self.get_struct(name)
+        {
+            return Some(self.compile_method_call(tk, &native_struct, method, None, arguments));
+        }
+
+        match right {
+            Expression::Identifier(_, _) => {
+                let field = self.get_struct_field(left, right)?;
+                let mut instructions = self.compile_expression(left);
+                instructions.push(load_field(&field));
+                Some(instructions)
+            }
+            Expression::CallExpression(tk, method, arguments) => {
+                let native_struct = self.get_struct_of_expression(left)?;
+                Some(self.compile_method_call(tk, &native_struct, method, Some(left), arguments))
+            }
+            _ => None,
+        }
+    }
+
+    /// Compile a call to a struct method, `instance` is passed as `self`.
+    fn compile_method_call(
+        &mut self,
+        token: &Token,
+        native_struct: &EasyNativeStruct,
+        method: &Expression,
+        instance: Option<&Expression>,
+        arguments: &[Expression],
+    ) -> EasyInstructions {
+        let method_name = self.compile_raw_expression(method);
+        let Some(fun_idx) =
+            self.get_fun_idx_from_name(&format!("{}_{}", native_struct.name, method_name))
+        else {
+            self.errors.push(native_could_not_parse_function(
+                token,
+                &format!("{}.{}", native_struct.name, method_name),
+            ));
+            return vec![];
+        };
+
+        let mut instructions = vec![];
+        if let Some(instance) = instance {
+            instructions.append(&mut self.compile_expression(instance));
+        }
+        let first_param = if instance.is_some() { 1 } else { 0 };
+        instructions.append(&mut self.compile_arguments(fun_idx, arguments, first_param));
+        instructions.push(Instruction::Call(fun_idx));
+
+        instructions
+    }
+
+    /// Get a struct by name.
+    fn get_struct(&self, name: &str) -> Option<EasyNativeStruct> {
+        let mangled_name = self.namespace.get_obj_name(&name.to_string());
+        self.structs
+            .iter()
+            .find(|s| s.name == name || s.name == mangled_name)
+            .cloned()
+    }
+
+    /// Get the struct an expression evaluates to.
+    fn get_struct_of_expression(&mut self, expr: &Expression) -> Option<EasyNativeStruct> {
+        match self.get_val_type_from_expression(expr) {
+            StrongValType::Custom(name) => self.get_struct(&name),
+            _ => None,
+        }
+    }
+
+    /// Get the field of `struct_expression.field`.
+    ///
+    /// Returns None if struct_expression is not a struct or does not have the field.
+    fn get_struct_field(
+        &mut self,
+        struct_expression: &Expression,
+        field: &Expression,
+    ) -> Option<EasyNativeField> {
+        let native_struct = self.get_struct_of_expression(struct_expression)?;
+        let Expression::Identifier(tk, field_name) = field else {
+            return None;
+        };
+
+        let field = native_struct.fields.iter().find(|f| f.name == *field_name);
+        if field.is_none() {
+            self.errors
+                .push(native_struct_has_no_field(tk, &native_struct.name, field_name));
+        }
+        field.cloned()
+    }
+
     /// Compile a global variable.
     ///
     /// This is used to get a ConstExpr.
     /// It is only used for global variables in the global scope. Global variables in the local scope act as normal.
-    fn compile_global_variable_stmt(&mut self, value: &Expression) -> ConstExpr {
+    ///
+    /// Number literals are stored as `val_type`.
+    fn compile_global_variable_stmt(&mut self, value: &Expression, val_type: &StrongValType) -> ConstExpr {
         // Let's parse and get the value type
         match value {
-            Expression::IntegerLiteral(_, val) => ConstExpr::i32_const(*val as i32),
+            Expression::IntegerLiteral(_, val) if *val_type == StrongValType::I64 => {
+                ConstExpr::i64_const(*val)
+            }
+            Expression::IntegerLiteral(_, val) if *val_type == StrongValType::Float => {
+                ConstExpr::f32_const(*val as f32)
+            }
+            Expression::IntegerLiteral(_, val) if *val_type == StrongValType::F64 => {
+                ConstExpr::f64_const(*val as f64)
+            }
+            Expression::IntegerLiteral(_, val) if *val_type == StrongValType::U8 => {
+                ConstExpr::i32_const(*val as u8 as i32)
+            }
+            Expression::IntegerLiteral(tk, val) => {
+                if i32::try_from(*val).is_err() && u32::try_from(*val).is_err() {
+                    self.errors.push(native_integer_out_of_range(tk));
+                }
+                ConstExpr::i32_const(*val as i32)
+            }
+            Expression::BigIntLiteral(tk, val) => match parse_i64_literal(val) {
+                Some(val) => ConstExpr::i64_const(val),
+                None => {
+                    self.errors.push(native_integer_out_of_range(tk));
+                    ConstExpr::empty()
+                }
+            },
+            Expression::FloatLiteral(_, val) if *val_type == StrongValType::F64 => {
+                ConstExpr::f64_const(*val)
+            }
             Expression::FloatLiteral(_, val) => ConstExpr::f32_const(*val as f32),
             Expression::Boolean(_, val) => ConstExpr::i32_const(*val as i32),
+            Expression::PrefixExpression(_, prefix, right) => match negate_literal(prefix, right) {
+                Some(literal) => self.compile_global_variable_stmt(&literal, val_type),
+                None => {
+                    self.errors
+                        .push(native_unsupported_expression_as_value_for_global_variable(
+                            value.get_token(),
+                        ));
+                    ConstExpr::empty()
+                }
+            },
             Expression::Identifier(_, name) => {
                 // check if null, Globals if not a basic type (int, float, bool) need to be set to null and instanced in a main() function
                 if name != "null" {
@@ -571,6 +1207,83 @@ impl NativeContext {
     /// Compile a native expression (to be used only within NativeContext logic.)
     ///
     /// This returns a list of Instructions that are then used for compilation.
+    /// Compile a ++ or -- on a number variable.
+    fn compile_step(&mut self, tk: &Token, op: &str, variable: &Expression) -> EasyInstructions {
+        let mut instructions = self.compile_expression(variable);
+        if instructions.is_empty() {
+            return vec![];
+        }
+
+        let (is_global, idx) = get_left_side_idx!(&instructions);
+        let val_type = self.get_val_type_from_instruction(instructions.last().unwrap());
+        let operator = if op == token::INCREMENT { token::PLUS } else { token::MINUS };
+        let operation = number_operation(operator, &val_type);
+        if idx < 0 || !is_number_type(&val_type) || operation.is_none() {
+            self.errors.push(native_unsupported_operator(tk, op));
+            return vec![];
+        }
+
+        instructions.push(number_one(&val_type));
+        instructions.push(operation.unwrap());
+        if val_type == StrongValType::U8 {
+            // u8 wraps
+            instructions.append(&mut convert_number(&StrongValType::Int, &val_type));
+        }
+        if is_global {
+            instructions.push(Instruction::GlobalSet(idx as u32));
+        } else {
+            instructions.push(Instruction::LocalSet(idx as u32));
+        }
+
+        instructions
+    }
+
+    /// Compile `left op right` on numbers, both sides are converted to `val_type` first.
+    ///
+    /// `x += y` stores the result back into `x` as the type of `x`.
+    fn compile_number_operation(
+        &mut self,
+        tk: &Token,
+        op: &str,
+        left: EasyInstructions,
+        right: EasyInstructions,
+        val_type: &StrongValType,
+    ) -> EasyInstructions {
+        let left_type = &self.get_val_type_from_instruction(left.last().unwrap());
+        let right_type = &self.get_val_type_from_instruction(right.last().unwrap());
+        let operator = get_assign_operator(op).unwrap_or(op);
+        let Some(operation) = number_operation(operator, val_type) else {
+            self.errors.push(native_unsupported_operator(tk, op));
+            return vec![];
+        };
+
+        let mut instructions = left.clone();
+        instructions.append(&mut convert_number(left_type, val_type));
+        instructions.extend(right);
+        instructions.append(&mut convert_number(right_type, val_type));
+        instructions.push(operation);
+        if is_comparison_operator(operator) {
+            return instructions;
+        }
+        if *val_type == StrongValType::U8 {
+            // u8 wraps
+            instructions.append(&mut convert_number(&StrongValType::Int, val_type));
+        }
+
+        if operator != op {
+            let (is_global, idx) = get_left_side_idx!(&left);
+            if idx < 0 {
+                self.errors.push(native_unsupported_operator(tk, op));
+                return vec![];
+            }
+            let mut instructions = self.convert_instructions(tk, instructions, left_type);
+            instructions.push(get_set_instructions(is_global, idx as u32).1);
+            return instructions;
+        }
+
+        instructions
+    }
+
     fn compile_expression(&mut self, expr: &Expression) -> EasyInstructions {
         match expr {
             Expression::Identifier(_, name) => {
@@ -599,12 +1312,23 @@ impl NativeContext {
                     .push(native_error_compiling_identifier(expr.get_token(), name));
                 vec![]
             }
-            Expression::FunctionLiteral(_, name, params, val_type, body) => {
+            Expression::FunctionLiteral(_, name, params, val_type, body, _, _) => {
                 self.compile_function_literal(name, params, val_type, body);
                 vec![]
                 // self.instructions.iter().last().unwrap().1.clone()
             }
-            Expression::IntegerLiteral(_, val) => vec![Instruction::I32Const(*val as i32)],
+            Expression::IntegerLiteral(tk, val) => match i32::try_from(*val) {
+                Ok(val) => vec![Instruction::I32Const(val)],
+                // too big for a int, so it is a i64
+                Err(_) => vec![Instruction::I64Const(*val)],
+            },
+            Expression::BigIntLiteral(tk, val) => match parse_i64_literal(val) {
+                Some(val) => vec![Instruction::I64Const(val)],
+                None => {
+                    self.errors.push(native_integer_out_of_range(tk));
+                    vec![]
+                }
+            },
             Expression::FloatLiteral(_, val) => vec![Instruction::F32Const(*val as f32)],
             Expression::InfixExpression(_, left, op, right) => {
                 let left = self.compile_expression(left.as_ref());
@@ -613,17 +1337,20 @@ impl NativeContext {
                 let left_type = self.get_val_type_from_instruction(left.last().unwrap());
                 let right_type = self.get_val_type_from_instruction(right.last().unwrap());
 
-                // if left is int and right is float: use f32Add
+                // Numbers are converted to the same type first.
                 // hierarchy: f64 > f32 > i64 > i32
+                if let Some(val_type) = promote_number_types(&left_type, &right_type) {
+                    return self.compile_number_operation(
+                        expr.get_token(),
+                        op,
+                        left,
+                        right,
+                        &val_type,
+                    );
+                }
+
                 let instruction_type = match (&left_type, &right_type) {
-                    (StrongValType::Int, StrongValType::Int) => "int",
-                    (StrongValType::Int, StrongValType::Float) => "int",
-                    (StrongValType::Float, StrongValType::Float) => "float",
-                    (StrongValType::Float, StrongValType::Int) => "float",
                     (StrongValType::String, StrongValType::String) => "string",
-                    (StrongValType::Bool, StrongValType::Bool) => "int",
-                    (StrongValType::Int, StrongValType::Bool) => "int",
-                    (StrongValType::Bool, StrongValType::Int) => "int",
                     (StrongValType::Array, StrongValType::Int) => "array-int",
                     (StrongValType::Array, StrongValType::Float) => "array-float",
                     (StrongValType::Array, StrongValType::String) => "array-string",
@@ -646,111 +1373,22 @@ impl NativeContext {
 
                 match op.as_str() {
                     token::PLUS => match instruction_type {
-                        "int" => instructions.push(Instruction::I32Add),
-                        "float" => instructions.push(Instruction::F32Add),
                         "string" => instructions.push(Instruction::Call(STR_CONCAT_IDX)),
                         _ => {}
                     },
-                    token::MINUS => match instruction_type {
-                        "int" => instructions.push(Instruction::I32Sub),
-                        "float" => instructions.push(Instruction::F32Sub),
-                        _ => {}
-                    },
-                    token::ASTERISK => match instruction_type {
-                        "int" => instructions.push(Instruction::I32Mul),
-                        "float" => instructions.push(Instruction::F32Mul),
-                        _ => {}
-                    },
-                    token::SLASH => match instruction_type {
-                        "int" => instructions.push(Instruction::I32DivS),
-                        "float" => instructions.push(Instruction::F32Div),
-                        _ => {}
-                    },
-                    token::MODULUS => match instruction_type {
-                        "int" => instructions.push(Instruction::I32RemS),
-                        "float" => instructions.push(Instruction::I32RemS),
-                        _ => {}
-                    },
-                    token::EQ => match instruction_type {
-                        "int" => instructions.push(Instruction::I32Eq),
-                        "float" => instructions.push(Instruction::F32Eq),
-                        _ => {}
-                    },
-                    token::LT => match instruction_type {
-                        "int" => instructions.push(Instruction::I32LtS),
-                        "float" => instructions.push(Instruction::F32Lt),
-                        _ => {}
-                    },
-                    token::LT_OR_EQ => match instruction_type {
-                        "int" => instructions.push(Instruction::I32LeS),
-                        "float" => instructions.push(Instruction::F32Le),
-                        _ => {}
-                    },
-                    token::GT => match instruction_type {
-                        "int" => instructions.push(Instruction::I32GtS),
-                        "float" => instructions.push(Instruction::F32Gt),
-                        _ => {}
-                    },
-                    token::GT_OR_EQ => match instruction_type {
-                        "int" => instructions.push(Instruction::I32GeS),
-                        "float" => instructions.push(Instruction::F32Ge),
-                        _ => {}
-                    },
-                    token::PLUS_EQUALS => match instruction_type {
-                        "int" => {
-                            let mut n_assign =
-                                generate_n_assign_instructions!(left, Instruction::I32Add);
-                            instructions.append(&mut n_assign);
-                        }
-                        "float" => {
-                            let mut n_assign =
-                                generate_n_assign_instructions!(left, Instruction::F32Add);
-                            instructions.append(&mut n_assign);
-                        }
-                        "string" => {
-                            let mut n_assign = generate_n_assign_instructions!(
-                                left,
-                                Instruction::Call(STR_CONCAT_IDX)
-                            );
-                            instructions.append(&mut n_assign);
-                        }
-                        "array-int" => {
-                            let mut n_assign = generate_n_assign_instructions!(
-                                left,
-                                Instruction::Call(ARR_PUSH_INT_IDX)
-                            );
-                            instructions.append(&mut n_assign);
-                        }
-                        "array-float" => {
-                            let mut n_assign = generate_n_assign_instructions!(
-                                left,
-                                Instruction::Call(ARR_PUSH_FLOAT_IDX)
-                            );
-                            instructions.append(&mut n_assign);
-                        }
-                        "array-string" => {
-                            let mut n_assign = generate_n_assign_instructions!(
-                                left,
-                                Instruction::Call(ARR_PUSH_STRING_IDX)
-                            );
-                            instructions.append(&mut n_assign);
-                        }
-                        "array-bool" => {
-                            let mut n_assign = generate_n_assign_instructions!(
-                                left,
-                                Instruction::Call(ARR_PUSH_INT_IDX)
-                            );
-                            instructions.append(&mut n_assign);
-                        }
-                        "array" => {
-                            let mut n_assign = generate_n_assign_instructions!(
-                                left,
-                                Instruction::Call(ARR_PUSH_ARRAY_IDX)
-                            );
-                            instructions.append(&mut n_assign);
-                        }
-                        _ => {}
-                    },
+                    token::PLUS_EQUALS => {
+                        let call = match instruction_type {
+                            "string" => STR_CONCAT_IDX,
+                            "array-float" => ARR_PUSH_FLOAT_IDX,
+                            "array-string" => ARR_PUSH_STRING_IDX,
+                            "array" => ARR_PUSH_ARRAY_IDX,
+                            // bools are just ints.
+                            _ => ARR_PUSH_INT_IDX,
+                        };
+                        let mut n_assign =
+                            generate_n_assign_instructions!(left, Instruction::Call(call));
+                        instructions.append(&mut n_assign);
+                    }
                     _ => {
                         self.errors
                             .push(native_unsupported_operator(expr.get_token(), op));
@@ -764,20 +1402,49 @@ impl NativeContext {
 
                 instructions
             }
-            Expression::CallExpression(_, name, arguments) => {
+            Expression::CastExpression(tk, value, val_type) => {
+                let to = self.get_val_type_from_expression(val_type);
+                let mut instructions = self.compile_expression(value);
+                let Some(last) = instructions.last() else {
+                    return vec![];
+                };
+                let from = self.get_val_type_from_instruction(last);
+                if !is_number_type(&from) || !is_number_type(&to) {
+                    self.errors.push(native_unsupported_cast(
+                        tk,
+                        &get_string_rep_of_type(&from),
+                        &get_string_rep_of_type(&to),
+                    ));
+                    return vec![];
+                }
+
+                instructions.append(&mut convert_number(&from, &to));
+                instructions
+            }
+            Expression::GroupedExpression(_, inner) => self.compile_expression(inner),
+            Expression::CallExpression(tk, name, arguments) => {
                 let name = self.compile_raw_expression(name);
+                if let Some(native_struct) = self.get_struct(&name) {
+                    return self.compile_struct_constructor(tk, &native_struct, arguments);
+                }
                 // get namespace defined name.
                 let mangled_name = self.namespace.get_obj_name(&name);
                 
-                // Parse arguments first...
-                let mut parsed_arguments = vec![];
+                let fun_idx = self.get_fun_idx_from_name(&mangled_name);
 
-                for arg in arguments.as_ref() {
-                    let mut instructions = self.compile_expression(arg);
-                    parsed_arguments.append(instructions.as_mut());
-                }
+                // Parse arguments first...
+                let mut parsed_arguments = match fun_idx {
+                    Some(fun_idx) => self.compile_arguments(fun_idx, arguments, 0),
+                    None => {
+                        let mut parsed_arguments = vec![];
+                        for arg in arguments.as_ref() {
+                            let mut instructions = self.compile_expression(arg);
+                            parsed_arguments.append(instructions.as_mut());
+                        }
+                        parsed_arguments
+                    }
+                };
 
-                let fun_idx = self.get_fun_idx_from_name(&mangled_name);
                 if fun_idx.is_none() {
                     // Check if this is a wasm core function
                     if !is_wasm_core(name.as_str()) {
@@ -802,10 +1469,18 @@ impl NativeContext {
                 result
             }
             Expression::DotExpression(tk, left, right) => {
+                // `a.b + 1` is parsed as `a.(b + 1)`
+                let expression = rebalance_dot_expression(tk, left, right);
+                let Expression::DotExpression(tk, left, right) = &expression else {
+                    return self.compile_expression(&expression);
+                };
                 let mut instructions = vec![];
 
                 // Check if we have a namespace to the left
-                let left_side_as_string = self.compile_raw_expression(left.as_ref());
+                let left_side_as_string = match left.as_ref() {
+                    Expression::Identifier(_, name) => name.to_owned(),
+                    _ => String::new(),
+                };
                 // clone namespaces to appease borrow checker
                 let cloned_namespaces = self.imported_modules.clone();
                 // Check if this is a namespace.
@@ -822,6 +1497,11 @@ impl NativeContext {
                 }
 
                 if instructions.len() == 0 {
+                    if let Some(struct_instructions) =
+                        self.compile_struct_dot_expression(left, right)
+                    {
+                        return struct_instructions;
+                    }
                     // compile left side
                     instructions.append(&mut self.compile_expression(left.as_ref()));
                     // compile right side.
@@ -829,6 +1509,19 @@ impl NativeContext {
                 }
                 instructions
             }
+            Expression::AssignExpression(tk, left, value) => {
+                // Only struct fields can be assigned to, variables are VariableStatements.
+                if let Expression::DotExpression(_, struct_expression, field) = left.as_ref()
+                    && let Some(field) = self.get_struct_field(struct_expression, field)
+                {
+                    let mut instructions = self.compile_expression(struct_expression);
+                    instructions.append(&mut self.compile_field_value(&field, value));
+                    instructions.push(store_field(&field));
+                    return instructions;
+                }
+                self.errors.push(native_unsupported_expression(expr));
+                vec![]
+            }
             Expression::StringLiteral(_, literal) => {
                 // add to the variable scope
                 let string_var_idx = self.next_var_idx;
@@ -842,7 +1535,7 @@ impl NativeContext {
                     val_type: StrongValType::String,
                     is_mut: true,
                 });
-                set_local_string(string_var_idx, literal.to_owned())
+                set_local_string(string_var_idx, unescape_string(literal))
             }
             Expression::IndexExpression(tk, left, index) => {
                 let mut instructions = vec![];
@@ -889,6 +1582,9 @@ impl NativeContext {
                 instructions
             }
             Expression::PrefixExpression(tk, prefix, right) => {
+                if let Some(literal) = negate_literal(prefix, right) {
+                    return self.compile_expression(&literal);
+                }
                 // TODO: this and in get_val_type
                 let mut instructions = vec![];
 
@@ -898,8 +1594,31 @@ impl NativeContext {
                     token::MINUS => {
                         // Multiple the expression by -1 to convert the number into a negative.
                         instructions.append(&mut self.compile_expression(&right));
-                        instructions
-                            .append(&mut vec![Instruction::I32Const(-1), Instruction::I32Mul]);
+                        let val_type = match instructions.last() {
+                            Some(last) => self.get_val_type_from_instruction(last),
+                            None => StrongValType::Int,
+                        };
+                        instructions.append(&mut match val_type {
+                            StrongValType::Float => vec![Instruction::F32Neg],
+                            StrongValType::F64 => vec![Instruction::F64Neg],
+                            StrongValType::I64 => vec![Instruction::I64Const(-1), Instruction::I64Mul],
+                            _ => vec![Instruction::I32Const(-1), Instruction::I32Mul],
+                        });
+                    }
+                    token::BITWISE_NOT => {
+                        // ~x is x ^ -1
+                        instructions.append(&mut self.compile_expression(right));
+                        let val_type = match instructions.last() {
+                            Some(last) => self.get_val_type_from_instruction(last),
+                            None => StrongValType::Int,
+                        };
+                        instructions.append(&mut match val_type {
+                            StrongValType::I64 => vec![Instruction::I64Const(-1), Instruction::I64Xor],
+                            _ => vec![Instruction::I32Const(-1), Instruction::I32Xor],
+                        });
+                    }
+                    token::INCREMENT | token::DECREMENT => {
+                        instructions.append(&mut self.compile_step(tk, prefix, right));
                     }
                     _ => self
                         .errors
@@ -908,6 +1627,9 @@ impl NativeContext {
 
                 instructions
             }
+            Expression::PostfixExpression(tk, left, postfix) => {
+                self.compile_step(tk, postfix, left)
+            }
             Expression::Boolean(tk, value) => {
                 if *value {
                     vec![Instruction::I32Const(1)]
@@ -926,6 +1648,7 @@ impl NativeContext {
                         instructions.append(&mut compiled_condition);
                         current_instruction_index += instructions.len();
                         instructions.push(Instruction::If(BlockType::Empty));
+                        self.push_block(EasyNativeBlockKind::If, None);
                     } else {
                         self.errors
                             .push(native_if_expression_must_go_within_functions(token));
@@ -959,6 +1682,7 @@ impl NativeContext {
                 if let Some(instructions) = self.instructions.get_mut(&self.next_fn_idx) {
                     instructions.push(Instruction::End);
                 }
+                self.pop_block();
 
                 // TODO: implement iife
                 // if self.block_scope.len() > 0 {
@@ -1111,6 +1835,7 @@ impl NativeContext {
             .collect::<Vec<ValType>>();
         // lets get the type too
         let return_type = self.get_val_type_from_expression(val_type);
+        self.return_type = return_type.clone();
         // For namespace
         let mut variables = vec![];
         for (param_n, param_t) in param_names.iter().zip(param_types.iter()) {
@@ -1125,6 +1850,7 @@ impl NativeContext {
             name: name.clone(),
             params: variables,
             return_type: return_type.clone(),
+            type_params: vec![],
         });
 
         // Get the block type
@@ -1145,6 +1871,7 @@ impl NativeContext {
 
         // add a new scope
         self.add_var_scope();
+        self.add_block_scope();
 
         // add params to scope
         for i in 0..params.len() {
@@ -1219,6 +1946,7 @@ impl NativeContext {
 
         // pop current variable scope
         self.pop_var_scope();
+        self.poop_block_scope();
     }
 
     /// Compile the raw expression into a String
@@ -1236,7 +1964,7 @@ impl NativeContext {
             Expression::Identifier(_, name) => name.clone(),
             Expression::IdentifierWithType(_, name, _) => name.clone(),
             Expression::StringLiteral(_, lit) => lit.clone(),
-            Expression::FunctionLiteral(_, name, _, _, _) => {
+            Expression::FunctionLiteral(_, name, _, _, _, _, _) => {
                 self.compile_raw_expression(name.as_ref())
             }
             Expression::StringLiteral(_, literal) => literal.to_owned(),
@@ -1287,11 +2015,17 @@ impl NativeContext {
                     res
                 }
             }
-            Expression::Type(tk, val_type) => get_param_type_by_string(val_type),
+            Expression::Type(tk, val_type) => match get_param_type_by_string(val_type) {
+                StrongValType::NotSupported => match self.get_struct(val_type) {
+                    Some(native_struct) => StrongValType::Custom(native_struct.name),
+                    None => StrongValType::NotSupported,
+                },
+                strong => strong,
+            },
             Expression::IdentifierWithType(tk, name, val_type) => {
                 self.get_val_type_from_expression(val_type.as_ref())
             }
-            Expression::FunctionLiteral(tk, _, _, val_type, _) => {
+            Expression::FunctionLiteral(tk, _, _, val_type, _, _, _) => {
                 // There is no way to infer the return type of a function (not yet)
                 // TODO: infer return type of function. also in JS
                 self.get_val_type_from_expression(val_type)
@@ -1304,8 +2038,18 @@ impl NativeContext {
                 // }
             }
             Expression::StringLiteral(tk, literal) => StrongValType::String,
+            Expression::IntegerLiteral(tk, literal) if i32::try_from(*literal).is_err() => {
+                StrongValType::I64
+            }
             Expression::IntegerLiteral(tk, literal) => StrongValType::Int,
-            Expression::PrefixExpression(tk, prefix, expression) => {
+            Expression::BigIntLiteral(_, _) => StrongValType::I64,
+            Expression::CastExpression(_, _, val_type) => self.get_val_type_from_expression(val_type),
+            Expression::GroupedExpression(_, inner) => self.get_val_type_from_expression(inner),
+            Expression::PrefixExpression(tk, prefix, expression) => match negate_literal(prefix, expression) {
+                Some(literal) => self.get_val_type_from_expression(&literal),
+                None => self.get_val_type_from_expression(expression),
+            },
+            Expression::PostfixExpression(tk, expression, postfix) => {
                 self.get_val_type_from_expression(expression)
             }
             Expression::IndexExpression(tk, source, index) => {
@@ -1313,10 +2057,68 @@ impl NativeContext {
             }
             Expression::Boolean(_, _) => StrongValType::Bool,
             Expression::InfixExpression(tk, left, infix, right) => {
-                self.get_val_type_from_expression(left)
+                if is_comparison_operator(infix) {
+                    return StrongValType::Bool;
+                }
+                let left_type = self.get_val_type_from_expression(left);
+                if get_assign_operator(infix).is_some() {
+                    return left_type;
+                }
+                let right_type = self.get_val_type_from_expression(right);
+                promote_number_types(&left_type, &right_type).unwrap_or(left_type)
             }
             Expression::FloatLiteral(tk, literal) => StrongValType::Float,
             Expression::ArrayLiteral(_, _) => StrongValType::Array,
+            Expression::CallExpression(tk, name, _) => {
+                let name = self.compile_raw_expression(name);
+                if let Some(native_struct) = self.get_struct(&name) {
+                    return StrongValType::Custom(native_struct.name);
+                }
+                let mangled_name = self.namespace.get_obj_name(&name);
+                for function in self.functions.iter() {
+                    if function.name == mangled_name {
+                        return function.signature.results_strong[0].clone();
+                    }
+                }
+                self.errors
+                    .push(native_can_not_get_value_from_expression(tk));
+                StrongValType::NotSupported
+            }
+            Expression::DotExpression(tk, left, right) => {
+                let expression = rebalance_dot_expression(tk, left, right);
+                let Expression::DotExpression(tk, left, right) = &expression else {
+                    return self.get_val_type_from_expression(&expression);
+                };
+                match right.as_ref() {
+                    Expression::Identifier(_, _) => match self.get_struct_field(left, right) {
+                        Some(field) => field.val_type,
+                        None => StrongValType::NotSupported,
+                    },
+                    Expression::CallExpression(_, method, _) => {
+                        // Struct.method() or instance.method()
+                        let native_struct = match left.as_ref() {
+                            Expression::Identifier(_, name) if self.get_struct(name).is_some() => {
+                                self.get_struct(name)
+                            }
+                            _ => self.get_struct_of_expression(left),
+                        };
+                        let method_name = self.compile_raw_expression(method);
+                        native_struct
+                            .and_then(|native_struct| {
+                                self.functions.iter().find(|function| {
+                                    function.name == format!("{}_{}", native_struct.name, method_name)
+                                })
+                            })
+                            .map(|function| function.signature.results_strong[0].clone())
+                            .unwrap_or(StrongValType::NotSupported)
+                    }
+                    _ => {
+                        self.errors
+                            .push(native_can_not_get_value_from_expression(tk));
+                        StrongValType::NotSupported
+                    }
+                }
+            }
             _ => {
                 // add error
                 self.errors
@@ -1357,9 +2159,96 @@ impl NativeContext {
                 }
                 StrongValType::NotSupported
             }
-            // Instruction::I64Const(_) => StrongValType::Long,
+            // the result of a nested operation, i.e. the `a << 2` in `a << 2 >> 1`
+            Instruction::I32DivU
+            | Instruction::I32RemU
+            | Instruction::I32WrapI64
+            | Instruction::I32TruncSatF32S
+            | Instruction::I32TruncSatF64S
+            | Instruction::I32Add
+            | Instruction::I32Sub
+            | Instruction::I32Mul
+            | Instruction::I32DivS
+            | Instruction::I32RemS
+            | Instruction::I32And
+            | Instruction::I32Or
+            | Instruction::I32Xor
+            | Instruction::I32Shl
+            | Instruction::I32ShrS
+            | Instruction::I32ShrU => StrongValType::Int,
+            Instruction::I32TruncSatF32U | Instruction::I32TruncSatF64U => StrongValType::U32,
+            Instruction::F32Add
+            | Instruction::F32Sub
+            | Instruction::F32Mul
+            | Instruction::F32Div
+            | Instruction::F32Neg
+            | Instruction::F32ConvertI32S
+            | Instruction::F32ConvertI32U
+            | Instruction::F32ConvertI64S
+            | Instruction::F32DemoteF64 => StrongValType::Float,
+            Instruction::I64Add
+            | Instruction::I64Sub
+            | Instruction::I64Mul
+            | Instruction::I64DivS
+            | Instruction::I64RemS
+            | Instruction::I64And
+            | Instruction::I64Or
+            | Instruction::I64Xor
+            | Instruction::I64Shl
+            | Instruction::I64ShrS
+            | Instruction::I64ShrU
+            | Instruction::I64ExtendI32S
+            | Instruction::I64ExtendI32U
+            | Instruction::I64TruncSatF32S
+            | Instruction::I64TruncSatF64S => StrongValType::I64,
+            Instruction::F64Add
+            | Instruction::F64Sub
+            | Instruction::F64Mul
+            | Instruction::F64Div
+            | Instruction::F64Neg
+            | Instruction::F64ConvertI32S
+            | Instruction::F64ConvertI32U
+            | Instruction::F64ConvertI64S
+            | Instruction::F64PromoteF32 => StrongValType::F64,
+            // comparisons
+            Instruction::I32Eq
+            | Instruction::I32Ne
+            | Instruction::I32Eqz
+            | Instruction::I32LtS
+            | Instruction::I32LtU
+            | Instruction::I32LeS
+            | Instruction::I32LeU
+            | Instruction::I32GtS
+            | Instruction::I32GtU
+            | Instruction::I32GeS
+            | Instruction::I32GeU
+            | Instruction::I64Eq
+            | Instruction::I64Ne
+            | Instruction::I64LtS
+            | Instruction::I64LeS
+            | Instruction::I64GtS
+            | Instruction::I64GeS
+            | Instruction::F32Eq
+            | Instruction::F32Ne
+            | Instruction::F32Lt
+            | Instruction::F32Le
+            | Instruction::F32Gt
+            | Instruction::F32Ge
+            | Instruction::F64Eq
+            | Instruction::F64Ne
+            | Instruction::F64Lt
+            | Instruction::F64Le
+            | Instruction::F64Gt
+            | Instruction::F64Ge => StrongValType::Bool,
+            // a struct field
+            Instruction::I32Load(_) => StrongValType::Int,
+            Instruction::I32Load8U(_) => StrongValType::U8,
+            Instruction::F32Load(_) => StrongValType::Float,
+            Instruction::I64Load(_) => StrongValType::I64,
+            Instruction::F64Load(_) => StrongValType::F64,
+            Instruction::I64Const(_) => StrongValType::I64,
             Instruction::F32Const(_) => StrongValType::Float,
-            // Instruction::F64Const(_) => StrongValType::Double,
+            Instruction::F64Const(_) => StrongValType::F64,
             _ => StrongValType::NotSupported,
         }
     }
@@ -1476,6 +2365,58 @@ impl NativeContext {
 
 }
 
+/// `a.b + c` is parsed as `a.(b + c)`, move the dot into the left most operand: `(a.b) + c`.
+///
+/// Field access and method calls stay a DotExpression.
+fn rebalance_dot_expression(token: &Token, left: &Expression, right: &Expression) -> Expression {
+    let dot = |inner: &Expression| Box::new(rebalance_dot_expression(token, left, inner));
+    match right {
+        Expression::InfixExpression(tk, l, op, r) => {
+            Expression::InfixExpression(tk.to_owned(), dot(l), op.to_owned(), r.to_owned())
+        }
+        Expression::AssignExpression(tk, l, r) => {
+            Expression::AssignExpression(tk.to_owned(), dot(l), r.to_owned())
+        }
+        Expression::IndexExpression(tk, l, index) => {
+            Expression::IndexExpression(tk.to_owned(), dot(l), index.to_owned())
+        }
+        Expression::PostfixExpression(tk, l, op) => {
+            Expression::PostfixExpression(tk.to_owned(), dot(l), op.to_owned())
+        }
+        Expression::CastExpression(tk, l, val_type) => {
+            Expression::CastExpression(tk.to_owned(), dot(l), val_type.to_owned())
+        }
+        Expression::DotExpression(tk, l, r) => rebalance_dot_expression(tk, &dot(l), r),
+        _ => Expression::DotExpression(
+            token.to_owned(),
+            Box::new(left.to_owned()),
+            Box::new(right.to_owned()),
+        ),
+    }
+}
+
+/// Fold `-` into a number literal, so the range of `-2147483648` is checked after the negation.
+fn negate_literal(prefix: &str, value: &Expression) -> Option<Expression> {
+    match value {
+        Expression::IntegerLiteral(tk, val) if prefix == token::MINUS => {
+            Some(Expression::IntegerLiteral(tk.to_owned(), -val))
+        }
+        Expression::FloatLiteral(tk, val) if prefix == token::MINUS => {
+            Some(Expression::FloatLiteral(tk.to_owned(), -val))
+        }
+        _ => None,
+    }
+}
+
+/// The get and set instructions of a variable.
+fn get_set_instructions(is_global: bool, idx: u32) -> (Instruction<'static>, Instruction<'static>) {
+    if is_global {
+        (Instruction::GlobalGet(idx), Instruction::GlobalSet(idx))
+    } else {
+        (Instruction::LocalGet(idx), Instruction::LocalSet(idx))
+    }
+}
+
 fn call_wasm_core_function(function_name: String) -> Instruction<'static> {
     let mem_arg = MemArg {
         offset: 0,
@@ -4,24 +4,57 @@ use std::iter::Map;
 use easyjs_utils::utils::sanatize;
 use regex::Regex;
 
-use super::macros::Macro;
+use super::cfg::eval_cfg;
+use super::macros::{Expansion, Macro};
 use super::native::compile_native;
 use crate::builtins;
-use crate::compiler::namespaces::{Function, NAMESPACE_PREFIX, Namespace, Struct, Variable};
-use crate::compiler::runes::RuneParser;
+use crate::errors::{
+    attribute_on_private_method, call_duplicate_argument, call_positional_after_named, call_unknown_named_argument,
+    comptime_evaluation_failed, deprecated_call, macro_is_private, macro_not_found, proc_macro_failed, proc_macro_invalid_ast,
+    type_unsupported_is_check,
+};
+use crate::compiler::namespaces::{Function, NAMESPACE_PREFIX, Namespace, NativeStruct, Struct, Variable};
 // use crate::interpreter::{interpret_js, is_javascript_var_defined};
-use crate::lexer::lex::{self, ALLOWED_IN_IDENT};
+use crate::lexer::lex;
 use crate::lexer::token;
 use crate::parser::ast::{Expression, Statement};
 use crate::parser::{ast, par};
+use crate::compiler::dts::emit_dts;
+use crate::emitter::structs::{create_struct_layout, struct_method_to_function};
+use crate::typechecker::checker::check_types;
 use crate::typechecker::{
-    StrongValType, get_param_type_by_string, get_param_type_by_string_ej, get_string_rep_of_type,
+    StrongValType, bind_type_params, get_param_type_by_expression_ej, get_param_type_by_string,
+    get_param_type_by_string_ej, get_string_rep_of_type, is_array_type, is_nullable, remove_null, typeof_name,
 };
-use easyjs_utils::utils::{h::hash_string, js_helpers::is_javascript_keyword};
-use easyjsr::{EJR, JSArg, JSArgResult, OpaqueObject, jsarg_as_string, jsarg_exception, jsarg_string};
+use easyjs_utils::utils::{h::hash_string, js_helpers::is_javascript_keyword, version};
+use easyjsr::{EJR, JSArg, JSArgResult, OpaqueObject, jsarg_as_string, jsarg_exception, jsarg_null, jsarg_string};
 
 use super::import::import_file;
 
+/// Attributes that are only metadata, not decorators.
+pub const METADATA_ATTRIBUTES: [&str; 2] = ["deprecated", "test"];
+
+/// The parts of a struct statement, see `Transpiler::transpile_struct_stmt`.
+struct StructParts {
+    name: ast::Expression,
+    constructor_vars: Option<Box<Vec<Expression>>>,
+    mixins: Option<Box<Vec<ast::Expression>>>,
+    derives: Vec<ast::Expression>,
+    variables: Vec<ast::Statement>,
+    methods: Vec<ast::Expression>,
+    attributes: Vec<ast::Expression>,
+}
+
+/// A expanded macro call, see `Transpiler::annotate_macros`.
+pub struct MacroExpansion {
+    /// The name of the macro as it was called.
+    pub name: String,
+    /// Where the macro was called, `file:line:col`.
+    pub location: String,
+    /// The JS the call expanded to.
+    pub js: String,
+}
+
 pub struct Transpiler {
     /// Stmt by Stmt
     scripts: Vec<String>,
@@ -47,7 +80,52 @@ pub struct Transpiler {
     custom_libs: HashMap<String, String>,
 
     /// A EJR reference
-    ejr: EJR
+    ejr: EJR,
+
+    /// Run the type checker after transpiling? (--strict-types)
+    pub strict_types: bool,
+
+    /// Type checker diagnostics. Only filled when `strict_types` is on.
+    pub type_errors: Vec<String>,
+
+    /// Should we emit TypeScript declarations?
+    pub emit_dts: bool,
+
+    /// The TypeScript declarations. Only filled when `emit_dts` is on.
+    pub dts: String,
+
+    /// Compile errors. i.e. a named argument the function does not have.
+    pub errors: Vec<String>,
+
+    /// The next id for names generated by macro expansion.
+    macro_ids: usize,
+
+    /// Are the comptime helpers registered in the EJR?
+    comptime_ready: bool,
+
+    /// The target we are compiling for (--target). One of `cfg::TARGETS`.
+    pub target: String,
+
+    /// The enabled features (--feature).
+    pub features: Vec<String>,
+
+    /// Mark each macro expansion with its call site and keep it in `macro_expansions`? (easyjs expand)
+    pub annotate_macros: bool,
+
+    /// The macro expansions. Only filled when `annotate_macros` is on.
+    pub macro_expansions: Vec<MacroExpansion>,
+
+    /// Compile warnings. i.e. a call to a `@deprecated` fn.
+    pub warnings: Vec<String>,
+
+    /// The `@deprecated` fns and structs. (name, message)
+    deprecated: HashMap<String, String>,
+
+    /// The `@test` fns, for tooling.
+    pub tests: Vec<String>,
+
+    /// The modules of the macros being expanded, innermost last.
+    macro_modules: Vec<String>,
 }
 /// Non Wasm specific (if running in non wasm enviroment, optionally save the wasm binary)
 #[cfg(not(target_arch = "wasm32"))]
@@ -62,6 +140,30 @@ fn save_wasm_bin(wasm_bin: &Vec<u8>) {
     // Empty body
 }
 
+/// comptime `read_file(path)`
+fn comptime_read_file(args: Vec<JSArg>, _: &OpaqueObject) -> JSArgResult {
+    let Some(path) = args.first().and_then(|arg| jsarg_as_string(*arg)) else {
+        return Some(jsarg_exception("read_file expects a path", "TypeError"));
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Some(jsarg_string(&contents)),
+        Err(err) => Some(jsarg_exception(format!("{}: {}", path, err).as_str(), "IOError")),
+    }
+}
+
+/// comptime `env(name)`, null if the variable is not set.
+fn comptime_env(args: Vec<JSArg>, _: &OpaqueObject) -> JSArgResult {
+    let Some(name) = args.first().and_then(|arg| jsarg_as_string(*arg)) else {
+        return Some(jsarg_exception("env expects a name", "TypeError"));
+    };
+
+    match std::env::var(name) {
+        Ok(value) => Some(jsarg_string(&value)),
+        Err(_) => Some(jsarg_null()),
+    }
+}
+
 impl Transpiler {
     pub fn new() -> Self {
         let mut t = Transpiler {
@@ -73,7 +175,22 @@ impl Transpiler {
             debug_mode: false,
             is_module: false,
             custom_libs: HashMap::new(),
-            ejr: EJR::new()
+            ejr: EJR::new(),
+            strict_types: false,
+            type_errors: vec![],
+            emit_dts: false,
+            dts: String::new(),
+            errors: vec![],
+            macro_ids: 0,
+            comptime_ready: false,
+            target: "easyjsr".to_string(),
+            features: vec![],
+            annotate_macros: false,
+            macro_expansions: vec![],
+            warnings: vec![],
+            deprecated: HashMap::new(),
+            tests: vec![],
+            macro_modules: vec![],
         };
 
         // Check the EASYJS_DEBUG variable
@@ -139,7 +256,7 @@ impl Transpiler {
     /// Call this to get the correct Expression.
     fn apply_namespace_mangling_to_native_expr(&self, expression: &Expression) -> Expression {
         match expression {
-            Expression::FunctionLiteral(tk, name, params, return_type, body) => {
+            Expression::FunctionLiteral(tk, name, params, return_type, body, type_params, attributes) => {
                 let name_transpiled = match name.as_ref() {
                     Expression::Identifier(tk, identifier) => (tk, identifier),
                     _ => {
@@ -156,6 +273,8 @@ impl Transpiler {
                     params.to_owned(),
                     return_type.to_owned(),
                     body.to_owned(),
+                    type_params.to_owned(),
+                    attributes.to_owned(),
                 )
             }
             _ => expression.to_owned(),
@@ -182,12 +301,77 @@ impl Transpiler {
 
                 self.add_expr_to_native_ctx(expr.as_ref());
             }
+            Statement::StructStatement(_, name, fields, _, _, _, methods, _) => {
+                let fields = fields.as_deref().map(Vec::as_slice).unwrap_or_default();
+                self.add_struct_to_native_ctx(name, fields, methods, is_export);
+            }
             _ => {
                 return;
             }
         }
     }
 
+    /// Add a native struct to the native context.
+    ///
+    /// Structs are always added so JS objects can be passed to and returned from native functions.
+    /// Their methods are only added when the struct is `pub`.
+    fn add_struct_to_native_ctx(
+        &mut self,
+        name: &Expression,
+        fields: &[Expression],
+        methods: &[Expression],
+        is_export: bool,
+    ) {
+        let Expression::Identifier(_, raw_name) = name else {
+            return;
+        };
+
+        let mut native_fields = vec![];
+        for field in fields {
+            if let Expression::IdentifierWithType(_, field_name, val_type) = field
+                && let Expression::Type(_, t) = val_type.as_ref()
+            {
+                native_fields.push(Variable {
+                    name: field_name.to_owned(),
+                    is_mut: true,
+                    val_type: self.get_native_param_type(t),
+                });
+            }
+        }
+
+        let struct_name = self.namespace.get_obj_name(raw_name);
+        let mut method_names = vec![];
+        if is_export {
+            for method in methods {
+                let (Some(function), Expression::FunctionLiteral(_, method_name, _, _, _, _, _)) =
+                    (struct_method_to_function(&struct_name, method), method)
+                else {
+                    continue;
+                };
+                self.add_expr_to_native_ctx(&function);
+                if let Expression::Identifier(_, method_name) = method_name.as_ref() {
+                    method_names.push(method_name.to_owned());
+                }
+            }
+        }
+
+        self.namespace.native_ctx.structs.push(NativeStruct {
+            name: struct_name,
+            fields: native_fields,
+            methods: method_names,
+        });
+    }
+
+    /// Get the type of a native paramater, anything that is not a builtin type is a struct.
+    fn get_native_param_type(&self, val_type: &str) -> StrongValType {
+        match get_param_type_by_string(val_type) {
+            StrongValType::NotSupported => {
+                StrongValType::Custom(self.namespace.get_obj_name(&val_type.to_string()))
+            }
+            strong => strong,
+        }
+    }
+
     /// Add a expression to the native context.
     ///
     /// Currently only used for functions. Could potentially be used for global varaibles too, but not ideal atm.
@@ -197,7 +381,7 @@ impl Transpiler {
     /// Or __easyjs_native_instance directly.
     fn add_expr_to_native_ctx(&mut self, expr: &Expression) {
         match expr {
-            Expression::FunctionLiteral(_, name, params, result, _) => {
+            Expression::FunctionLiteral(_, name, params, result, _, _, _) => {
                 // find out param types (as string...)
                 let param_types = {
                     let mut param_types = vec![];
@@ -237,17 +421,20 @@ impl Transpiler {
                 };
 
                 // add to native ctx
+                let params = param_types
+                    .iter()
+                    .map(|v| Variable {
+                        name: String::from(""),
+                        is_mut: true,
+                        val_type: self.get_native_param_type(v),
+                    })
+                    .collect::<Vec<Variable>>();
+                let return_type = self.get_native_param_type(&return_types);
                 self.namespace.native_ctx.functions.push(Function {
                     name: fn_name,
-                    params: param_types
-                        .iter()
-                        .map(|v| Variable {
-                            name: String::from(""),
-                            is_mut: true,
-                            val_type: get_param_type_by_string(v),
-                        })
-                        .collect::<Vec<Variable>>(),
-                    return_type: get_param_type_by_string(&return_types),
+                    params,
+                    return_type,
+                    type_params: vec![],
                 })
             }
             _ => {
@@ -264,6 +451,9 @@ impl Transpiler {
     pub fn transpile_module(&mut self, file_name: &str, alias: &str, p: ast::Program) -> String {
         let mut t = Transpiler::new();
         t.is_module = true;
+        t.target = self.target.clone();
+        t.features = self.features.clone();
+        t.annotate_macros = self.annotate_macros;
         // Clean the filename
         t.namespace.id = file_name.to_string();
         t.namespace.alias = alias.to_string();
@@ -273,6 +463,8 @@ impl Transpiler {
 
         // Add the namespace to our modules
         self.modules.push(t.namespace.clone());
+        self.errors.extend(t.errors);
+        self.macro_expansions.extend(t.macro_expansions);
 
         // Check if this namespace goes into global scope
         if alias.len() == 0 {
@@ -284,7 +476,13 @@ impl Transpiler {
 
             self.namespace.functions.extend(t.namespace.functions);
             self.namespace.structs.extend(t.namespace.structs);
-            self.namespace.macros.extend(t.namespace.macros);
+            // private macros stay in the module.
+            self.namespace
+                .macros
+                .extend(t.namespace.macros.into_iter().filter(|(_, ej_macro)| ej_macro.is_pub));
+            self.namespace.type_aliases.extend(t.namespace.type_aliases);
+            self.namespace.classes.extend(t.namespace.classes);
+            self.namespace.enums.extend(t.namespace.enums);
         }
 
         if t.native_stmts.len() > 0 {
@@ -303,6 +501,10 @@ impl Transpiler {
                 .native_ctx
                 .variables
                 .extend(t.namespace.native_ctx.variables);
+            self.namespace
+                .native_ctx
+                .structs
+                .extend(t.namespace.native_ctx.structs);
         }
 
         // return JS code
@@ -341,13 +543,28 @@ impl Transpiler {
         let l = lex::Lex::new(p);
         let mut p = par::Parser::new(l);
         let program = p.parse_program();
+        let program = ast::Program { statements: self.resolve_cfg(program.statements) };
 
         self.transpile_from(program)
     }
 
     /// Transpile easyjs code into JS from a ast program.
     pub fn transpile(&mut self, p: ast::Program) -> String {
+        // @if/@cfg are resolved first so that hoisting and the type checker only see our target.
+        let p = ast::Program { statements: self.resolve_cfg(p.statements) };
+        let statements = if self.strict_types || self.emit_dts {
+            p.statements.clone()
+        } else {
+            vec![]
+        };
         let code = self.transpile_from(p);
+
+        if self.strict_types {
+            self.type_errors = check_types(&statements, &self.namespace, &self.modules);
+        }
+        if self.emit_dts {
+            self.dts = emit_dts(&statements);
+        }
         code
     }
 
@@ -390,6 +607,15 @@ impl Transpiler {
             }
         }
 
+        // types are hoisted, so they can be used before they are declared.
+        for stmt in statements.iter() {
+            self.declare_types(stmt);
+        }
+        // and so are functions and structs, after the types their paramaters use.
+        for stmt in statements.iter() {
+            self.declare_function(stmt);
+        }
+
         // transpile JS statements..
         for stmt in statements {
             if stmt.is_empty() {
@@ -441,15 +667,19 @@ impl Transpiler {
                 name,
                 constructor_vars,
                 mixins,
+                derives,
                 vars,
                 methods,
-            ) => Some(self.transpile_struct_stmt(
-                name.as_ref().to_owned(),
+                attributes,
+            ) => Some(self.transpile_struct_stmt(StructParts {
+                name: name.as_ref().to_owned(),
                 constructor_vars,
                 mixins,
-                vars.as_ref().to_owned(),
-                methods.as_ref().to_owned(),
-            )),
+                derives: derives.map(|derives| *derives).unwrap_or_default(),
+                variables: vars.as_ref().to_owned(),
+                methods: methods.as_ref().to_owned(),
+                attributes: attributes.as_ref().to_owned(),
+            })),
             Statement::ExportStatement(token, stmt) => {
                 Some(self.transpile_export_stmt(token, stmt.as_ref().to_owned()))
             }
@@ -464,18 +694,53 @@ impl Transpiler {
             Statement::EnumStatement(tk, name, options) => {
                 Some(self.transpile_enum_stmt(&name, options.as_ref()))
             }
-            Statement::BreakStatement(tk) => Some("break".to_string()),
-            Statement::ContinueStatement(tk) => Some("continue".to_string()),
-            Statement::MacroStatement(_, name, paramaters, body, is_hygenic) => {
+            Statement::BreakStatement(tk, label) => Some(match label {
+                Some(label) => format!("break {};\n", label),
+                None => "break;\n".to_string(),
+            }),
+            Statement::ContinueStatement(tk, label) => Some(match label {
+                Some(label) => format!("continue {};\n", label),
+                None => "continue;\n".to_string(),
+            }),
+            Statement::DoWhileStatement(tk, body, condition) => {
+                let mut res = String::from("do {\n");
+                if let Some(stmt) = self.transpile_stmt(body.as_ref().to_owned()) {
+                    res.push_str(&stmt);
+                }
+                res.push_str(&format!(
+                    "}} while ({});\n",
+                    self.transpile_expression(condition.as_ref().to_owned())
+                ));
+                Some(res)
+            }
+            Statement::LabeledStatement(tk, label, stmt) => self
+                .transpile_stmt(stmt.as_ref().to_owned())
+                .map(|stmt| format!("{}: {}", label, stmt)),
+            Statement::MacroStatement(_, name, paramaters, body, is_hygenic, is_procedural) => {
                 let macro_name: String = self.transpile_expression(name.as_ref().to_owned());
                 let macro_params = paramaters.as_ref().to_owned();
                 let macro_body = body.as_ref().to_owned();
 
-                self.add_macro_function(macro_name, macro_params, macro_body, is_hygenic);
+                self.add_macro_function(macro_name, macro_params, macro_body, is_hygenic, is_procedural, false);
                 Some(String::from(""))
             }
-            Statement::ClassStatement(tk, name, extends, stmts) => {
-                Some(self.transpile_class_stmt(&tk, name.as_ref(), extends.as_ref(), &stmts))
+            Statement::ClassStatement(tk, name, extends, stmts, attributes) => {
+                Some(self.transpile_class_stmt(&tk, name.as_ref(), extends.as_ref(), &stmts, &attributes))
+            }
+            Statement::TypeAliasStatement(_, name, alias_type) => {
+                // types only exist at compile time.
+                self.add_type_alias(&name, &alias_type);
+                None
+            }
+            Statement::CfgStatement(_, condition, consequence, alternative) => {
+                // The branch goes in the current scope, just like it was written here.
+                let mut res = String::new();
+                for stmt in self.select_cfg_branch(&condition, *consequence, *alternative) {
+                    if let Some(stmt) = self.transpile_stmt(stmt) {
+                        res.push_str(&stmt);
+                    }
+                }
+                Some(res)
             }
             _ => None,
         }
@@ -502,7 +767,7 @@ impl Transpiler {
             Statement::ExpressionStatement(tk, expr) => {
                 let mut result = String::new();
                 match expr.as_ref() {
-                    Expression::FunctionLiteral(tk, fn_name, params, type_, block) => {
+                    Expression::FunctionLiteral(tk, fn_name, params, type_, block, type_params, attributes) => {
                         // Check name of function
                         let mut fn_name_parsed =
                             self.transpile_expression(fn_name.as_ref().to_owned());
@@ -583,6 +848,8 @@ impl Transpiler {
                             Box::new(cleaned_params),
                             type_.to_owned(),
                             final_block.to_owned(),
+                            type_params.to_owned(),
+                            Box::default(),
                         );
                         // Transpile the function but just removed the `function` keyword from the beginning
                         let tf = self.transpile_expression(function);
@@ -594,6 +861,20 @@ impl Transpiler {
                         // FUNCTION = 8 len
                         result.push_str(&tf.trim()[8..]);
                         result.push('\n');
+
+                        // Decorators replace the method once the class is made.
+                        if !attributes.is_empty() {
+                            if !fn_is_pub {
+                                self.errors.push(attribute_on_private_method(tk, &fn_name_parsed));
+                                return result;
+                            }
+                            let owner = if is_static { "this" } else { "this.prototype" };
+                            let method = format!("{}.{}", owner, fn_name_parsed);
+                            let decorated = self.apply_attributes(&fn_name_parsed, &method, attributes, None);
+                            if decorated != method {
+                                result.push_str(&format!("static {{\n{} = {};\n}}\n", method, decorated));
+                            }
+                        }
                         result
                     }
                     Expression::AsyncExpression(tk, expr) => {
@@ -631,6 +912,7 @@ impl Transpiler {
         name: &Expression,
         extends: &Vec<Expression>,
         stmts: &Vec<Statement>,
+        attributes: &[Expression],
     ) -> String {
         let mut result = String::new();
 
@@ -691,6 +973,15 @@ impl Transpiler {
             result.push(')');
         }
         result.push_str("{}");
+
+        let mut macro_calls = String::new();
+        let decorated = self.apply_attributes(&base_name, &base_name, attributes, Some(&mut macro_calls));
+        if decorated != base_name {
+            result.push_str(&format!("\n{} = {};", base_name, decorated));
+        }
+        if !macro_calls.is_empty() {
+            result.push_str(&format!("\n{}", macro_calls));
+        }
         result
     }
 
@@ -778,8 +1069,51 @@ impl Transpiler {
             res.push_str(",");
         }
         res.push_str("]);\n");
+
+        // The memory layout of native structs, used to convert them to and from JS objects.
+        res.push_str("const __easyjs_native_structs = {");
+        for native_struct in self.namespace.native_ctx.structs.iter() {
+            let fields = native_struct
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), field.val_type.clone()))
+                .collect();
+            let layout = create_struct_layout(&native_struct.name, &fields);
+            res.push_str(&format!(
+                "'{}': {{size: {}, fields: [{}]}},",
+                layout.name,
+                layout.size,
+                layout
+                    .fields
+                    .iter()
+                    .map(|field| format!(
+                        "['{}', '{}', {}]",
+                        field.name,
+                        get_string_rep_of_type(&field.val_type),
+                        field.offset
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        res.push_str("};\n");
         res.push_str(include_str!("../native_runner.js"));
 
+        // pub structs can be used from JS i.e. `Vec3.sum(v)`
+        for native_struct in self.namespace.native_ctx.structs.iter() {
+            if native_struct.methods.is_empty() {
+                continue;
+            }
+            res.push_str(&format!("const {} = {{\n", native_struct.name));
+            for method in native_struct.methods.iter() {
+                let fn_name = format!("{}_{}", native_struct.name, method);
+                let mut call = self.transpile_native_function_with_args(&self.namespace, &fn_name, vec![]);
+                call.push_str(", ...args)");
+                res.push_str(&format!("{}: (...args) => {},\n", method, call));
+            }
+            res.push_str("};\n");
+        }
+
         res
     }
 
@@ -854,7 +1188,94 @@ impl Transpiler {
     }
 
     fn transpile_export_stmt(&mut self, token: token::Token, stmt: ast::Statement) -> String {
-        format!("export {};\n", self.transpile_stmt(stmt).unwrap())
+        // `pub macro` only exists at compile time.
+        if let Statement::MacroStatement(_, name, paramaters, body, is_hygenic, is_procedural) = stmt {
+            let macro_name = self.transpile_expression(*name);
+            self.add_macro_function(macro_name, *paramaters, *body, is_hygenic, is_procedural, true);
+            return String::new();
+        }
+
+        match self.transpile_stmt(stmt) {
+            Some(js) => format!("export {};\n", js),
+            // i.e. `pub type UserId = int`
+            None => String::new(),
+        }
+    }
+
+    /// Declare a top level function or struct, so named arguments work before it is defined.
+    fn declare_function(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::ExportStatement(_, stmt) => self.declare_function(stmt),
+            Statement::StructStatement(_, name, constructor_vars, _, _, _, _, _) => {
+                let struct_name = self.transpile_expression(name.as_ref().to_owned());
+                let mut params = vec![];
+                for var in constructor_vars.iter().flat_map(|vars| vars.iter()) {
+                    let (name, val_type, _) = self.transpile_function_paramater(var);
+                    params.push(Variable { name, is_mut: true, val_type });
+                }
+                self.add_namespace_struct(Struct {
+                    name: self.namespace.get_obj_name(&struct_name),
+                    params,
+                    variables: vec![],
+                    methods: vec![],
+                    static_methods: vec![],
+                    mixins: vec![],
+                });
+            }
+            Statement::ExpressionStatement(_, expr) => {
+                if let Expression::FunctionLiteral(_, name, params, return_type, _, type_params, attributes) =
+                    expr.as_ref()
+                {
+                    let fn_name = self.transpile_expression(name.as_ref().to_owned());
+                    self.add_attribute_metadata(&fn_name, attributes);
+                    let function = self.create_namespace_function(
+                        &fn_name,
+                        params.clone(),
+                        return_type.clone(),
+                        type_params,
+                    );
+                    self.add_namespace_function(function);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Declare the types of a top level statement. i.e. type aliases, enums and classes.
+    fn declare_types(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::ExportStatement(_, stmt) => self.declare_types(stmt),
+            Statement::TypeAliasStatement(_, name, alias_type) => {
+                self.add_type_alias(name, alias_type)
+            }
+            Statement::EnumStatement(_, name, _) => {
+                let name = self.namespace.get_obj_name(name);
+                if !self.namespace.enums.contains(&name) {
+                    self.namespace.enums.push(name);
+                }
+            }
+            Statement::ClassStatement(_, name, _, _, _) => {
+                if let Expression::Identifier(_, name) = name.as_ref() {
+                    let name = self.namespace.get_obj_name(name);
+                    if !self.namespace.classes.contains(&name) {
+                        self.namespace.classes.push(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_type_alias(&mut self, name: &String, alias_type: &Expression) {
+        let alias_name = self.namespace.get_obj_name(name);
+        let val_type = get_param_type_by_expression_ej(alias_type);
+        self.namespace.type_aliases.insert(alias_name, val_type);
+    }
+
+    /// Get the type of a type expression, resolved through the namespace.
+    fn get_type(&self, expr: &Expression) -> StrongValType {
+        self.namespace
+            .resolve_type(&get_param_type_by_expression_ej(expr), &self.modules)
     }
 
     fn transpile_var_stmt(
@@ -885,9 +1306,7 @@ impl Transpiler {
             // check for type
             let mut val_type: StrongValType = StrongValType::None;
             if let Some(ej_type) = ej_type {
-                // transpile
-                let type_name = self.transpile_expression(ej_type.to_owned());
-                val_type = get_param_type_by_string_ej(&type_name);
+                val_type = self.get_type(ej_type);
             }
 
             // Add to scope
@@ -992,20 +1411,20 @@ impl Transpiler {
     ) -> String {
         let mut res = String::new();
         match condition {
-            ast::Expression::Boolean(token, value) => {
-                res.push_str(format!("while({})", value).as_str());
-            }
-            ast::Expression::InfixExpression(token, left, operator, right) => {
-                res.push_str(
-                    format!(
-                        "while({} {} {}) ",
-                        self.transpile_expression(left.as_ref().to_owned()),
-                        operator,
-                        self.transpile_expression(right.as_ref().to_owned())
-                    )
-                    .as_str(),
-                );
-            }
+            // for await item of stream
+            ast::Expression::AwaitExpression(token, iterable) => match iterable.as_ref() {
+                Expression::OfExpression(_, left, right) | Expression::InExpression(_, left, right) => {
+                    res.push_str(
+                        format!(
+                            "for await (let {} of {}) ",
+                            self.transpile_for_item(left.as_ref()),
+                            self.transpile_expression(right.as_ref().to_owned())
+                        )
+                        .as_str(),
+                    );
+                }
+                _ => {}
+            },
             ast::Expression::OfExpression(token, left, right) => {
                 res.push_str(
                     format!(
@@ -1017,34 +1436,23 @@ impl Transpiler {
                 );
             }
             Expression::InExpression(token, left, right) => match right.as_ref().to_owned() {
-                Expression::RangeExpression(token, start, end) => {
-                    // TODO: error for if end is empty
-
+                Expression::RangeExpression(token, start, end, step) => {
                     let ident: String = self.transpile_expression(left.as_ref().to_owned());
-                    res.push_str("for (let ");
-                    res.push_str(&ident);
-
-                    res.push_str(" = ");
-                    res.push_str(&self.transpile_expression(start.as_ref().to_owned()));
-                    res.push_str(";");
-                    res.push_str(&ident);
-                    res.push_str(" < ");
-                    res.push_str(&self.transpile_expression(end.as_ref().to_owned()));
-                    res.push_str(";");
-                    res.push_str(&ident);
-                    res.push_str("++");
-                    res.push_str(") ");
+                    res.push_str(&self.transpile_range_loop(&ident, &token, &start, &end, &step));
                 }
                 _ => res.push_str(
                     format!(
                         "for (let {} of {}) ",
-                        self.transpile_expression(left.as_ref().to_owned()),
-                        self.transpile_expression(right.as_ref().to_owned())
+                        self.transpile_for_item(left.as_ref()),
+                        self.transpile_for_collection(left.as_ref(), right.as_ref())
                     )
                     .as_str(),
                 ),
             },
-            _ => panic!("Condition must be boolean"),
+            // anything else loops while it is true.
+            _ => res.push_str(
+                format!("while({}) ", self.transpile_expression(condition)).as_str(),
+            ),
         }
 
         res.push_str("{\n");
@@ -1060,32 +1468,128 @@ impl Transpiler {
         res
     }
 
+    /// The item of a foreach loop, `key, value` loops get the entries of the collection.
+    fn transpile_for_item(&mut self, left: &Expression) -> String {
+        match left {
+            Expression::ArrayLiteral(_, pair) => format!(
+                "[{}]",
+                self.join_expressions(pair.as_ref().to_owned())
+            ),
+            _ => self.transpile_expression(left.to_owned()),
+        }
+    }
+
+    /// The collection of a foreach loop. `key, value` loops go over the entries of a array, Map or object.
+    fn transpile_for_collection(&mut self, left: &Expression, collection: &Expression) -> String {
+        let collection_js = self.transpile_expression(collection.to_owned());
+        if !matches!(left, Expression::ArrayLiteral(_, _)) {
+            return collection_js;
+        }
+
+        match collection {
+            Expression::ArrayLiteral(_, _) => format!("{}.entries()", collection_js),
+            Expression::ObjectLiteral(_, _) => format!("Object.entries({})", collection_js),
+            // only known when the loop runs.
+            _ => format!(
+                "((v) => Array.isArray(v) || v instanceof Map ? v.entries() : Object.entries(v))({})",
+                collection_js
+            ),
+        }
+    }
+
+    /// Lower `for i in start..end step n` into a JS for loop.
+    ///
+    /// A range of literals counts down when start is bigger than end, any other range counts
+    /// up unless the step is negative. A step that is not a literal picks the direction when the loop starts.
+    fn transpile_range_loop(
+        &mut self,
+        ident: &str,
+        token: &token::Token,
+        start: &Expression,
+        end: &Expression,
+        step: &Expression,
+    ) -> String {
+        let inclusive = token.typ == token::DOTDOT_EQUALS;
+        let start_js = self.transpile_expression(start.to_owned());
+        let end_js = self.transpile_expression(end.to_owned());
+        let step_js = if step.is_empty() {
+            None
+        } else {
+            Some(self.transpile_expression(step.to_owned()))
+        };
+
+        let (up, down) = if inclusive { ("<=", ">=") } else { ("<", ">") };
+        // Does a range of literals count down?
+        let literal_descending = match (start.get_int_literal(), end.get_int_literal()) {
+            (Some(start), Some(end)) => Some(start > end),
+            _ => None,
+        };
+
+        let step_size = if step.is_empty() { Some(1) } else { step.get_int_literal() };
+        let Some(step_size) = step_size else {
+            // the sign of the step is only known at runtime, a step of 0 never runs.
+            let end_var = format!("__{}_end", ident);
+            let step_var = format!("__{}_step", ident);
+            let step = step_js.unwrap_or_default();
+            return format!(
+                "for (let {i} = {start}, {end_var} = {end}, {step_var} = {step};{step_var} > 0 ? {i} {up} {end_var} : {step_var} < 0 && {i} {down} {end_var};{i} += {step_var}) ",
+                i = ident,
+                start = start_js,
+                end = end_js,
+                // 10..0 step n moves down by n
+                step = if literal_descending == Some(true) { format!("-({})", step) } else { step },
+            );
+        };
+
+        // `0..xs.length - 1` with a empty xs never runs.
+        let descending = literal_descending.unwrap_or(step_size < 0);
+        let (check, op) = if descending { (down, "-") } else { (up, "+") };
+        let update = match step_size.abs() {
+            1 => format!("{}{}{}", ident, op, op),
+            size => format!("{} {}= {}", ident, op, size),
+        };
+
+        format!(
+            "for (let {} = {};{} {} {};{}) ",
+            ident, start_js, ident, check, end_js, update
+        )
+    }
+
+    /// The end of a slice, a inclusive range includes the end.
+    fn transpile_slice_end(&mut self, token: &token::Token, end: &Expression) -> String {
+        let end = self.transpile_expression(end.to_owned());
+        if token.typ == token::DOTDOT_EQUALS {
+            format!("{} + 1", end)
+        } else {
+            end
+        }
+    }
+
     fn transpile_expression_stmt(
         &mut self,
         token: token::Token,
         expression: ast::Expression,
     ) -> String {
         let has_semicolon = match expression {
-            Expression::FunctionLiteral(_, _, _, _, _) => false,
+            Expression::FunctionLiteral(_, _, _, _, _, _, _) => false,
             Expression::DocCommentExpression(_, _) => false,
             Expression::MacroExpression(_, _, _) => false,
             _ => true,
         };
+        if let Expression::MacroExpression(_, name, arguments) = &expression {
+            return self.transpile_macro_call(name, arguments.as_ref().to_owned(), true);
+        }
         let res = self.transpile_expression(expression);
         let semi = if has_semicolon { ";\n" } else { "" };
         format!("{}{}", res, semi)
     }
 
-    fn transpile_struct_stmt(
-        &mut self,
-        name: ast::Expression,
-        constructor_vars: Option<Box<Vec<Expression>>>,
-        mixins: Option<Box<Vec<ast::Expression>>>,
-        variables: Vec<ast::Statement>,
-        methods: Vec<ast::Expression>,
-    ) -> String {
+    fn transpile_struct_stmt(&mut self, parts: StructParts) -> String {
+        let StructParts { name, constructor_vars, mixins, derives, variables, methods, attributes } = parts;
         let mut res = String::new();
         let mut parsed_mixins = vec![];
+        // (name, type) of every field. Used by derives.
+        let mut struct_fields: Vec<(String, StrongValType)> = vec![];
         // Struct namespace holders.
         let mut struct_params: Vec<Variable> = vec![];
         let mut struct_variables: Vec<Variable> = vec![];
@@ -1115,6 +1619,7 @@ impl Transpiler {
             for i in 0..vars.len() {
                 let var = &vars[i];
                 let (var_name, val_type, default_value) = self.transpile_function_paramater(var);
+                struct_fields.push((var_name.clone(), val_type.clone()));
 
                 // Add to transpilation process. name: value
                 struct_vars.push((var_name.clone(), None));
@@ -1146,16 +1651,16 @@ impl Transpiler {
 
                 //     res.push_str(format!("{}.{} = {};\n", struct_name, name, value).as_str());
                 // }
-                ast::Statement::VariableStatement(_, name, _, value, _) => {
+                ast::Statement::VariableStatement(_, name, var_type, value, _) => {
                     let mut val_type = StrongValType::None;
 
-                    match name.as_ref() {
-                        Expression::IdentifierWithType(_, _, var_type) => {
-                            val_type = get_param_type_by_string_ej(
-                                &self.transpile_expression(var_type.as_ref().to_owned()),
-                            );
+                    match var_type.as_deref() {
+                        Some(var_type) => {
+                            val_type = self.get_type(var_type);
+                        }
+                        None => {
+                            val_type = self.get_type(name.as_ref());
                         }
-                        _ => {}
                     }
 
                     // TODO: infer type automatically
@@ -1164,6 +1669,7 @@ impl Transpiler {
                     let value = self.transpile_expression(value.as_ref().to_owned());
 
                     struct_vars.push((name.clone(), Some(value)));
+                    struct_fields.push((name.clone(), val_type.clone()));
 
                     // Also add to struct_variable
                     struct_variables.push(Variable {
@@ -1187,7 +1693,7 @@ impl Transpiler {
                 Expression::DocCommentExpression(tk, comments) => {
                     result = self.transpile_doc_comment_expr(tk, comments);
                 }
-                Expression::FunctionLiteral(_, name, params, return_val_type, body) => {
+                Expression::FunctionLiteral(_, name, params, return_val_type, body, type_params, _) => {
                     result = (self.transpile_struct_method(
                         &struct_name,
                         cleaned_method_is_static.0,
@@ -1197,7 +1703,14 @@ impl Transpiler {
 
                     let fn_name = &self.transpile_expression(name.as_ref().to_owned());
                     // Add to struct methods
-                    let function = self.create_namespace_function(fn_name, params, return_val_type);
+                    let mut function = self.create_namespace_function(
+                        fn_name,
+                        params,
+                        return_val_type,
+                        &type_params,
+                    );
+                    // methods belong to the struct, not the namespace.
+                    function.name = fn_name.to_owned();
 
                     if cleaned_method_is_static.1 {
                         struct_static_methods.push(function);
@@ -1215,13 +1728,15 @@ impl Transpiler {
 
                     // Add to struct methods
                     match function.as_ref() {
-                        Expression::FunctionLiteral(_, name, params, return_type, body) => {
+                        Expression::FunctionLiteral(_, name, params, return_type, body, type_params, _) => {
                             let fn_name = &self.transpile_expression(name.as_ref().to_owned());
-                            let namespace_function = self.create_namespace_function(
+                            let mut namespace_function = self.create_namespace_function(
                                 fn_name,
                                 params.to_owned(),
                                 return_type.to_owned(),
+                                type_params,
                             );
+                            namespace_function.name = fn_name.to_owned();
                             if cleaned_method_is_static.1 {
                                 struct_static_methods.push(namespace_function);
                             } else {
@@ -1243,6 +1758,76 @@ impl Transpiler {
             }
         }
 
+        // add derived methods.
+        let mut derived_static_methods = String::new();
+        let ctor_field_count = struct_params.len();
+        for derive in derives {
+            let derive = self.transpile_expression(derive);
+            match derive.as_str() {
+                "eq" => {
+                    cleaned_methods.push(self.derive_struct_equals(&struct_fields));
+                    struct_methods.push(Function {
+                        name: "equals".to_string(),
+                        params: vec![Variable {
+                            name: "other".to_string(),
+                            is_mut: true,
+                            val_type: StrongValType::None,
+                        }],
+                        return_type: StrongValType::Bool,
+                        type_params: vec![],
+                    });
+                }
+                "clone" => {
+                    cleaned_methods.push(self.derive_struct_clone(
+                        &struct_name,
+                        &struct_fields,
+                        ctor_field_count,
+                    ));
+                    struct_methods.push(Function {
+                        name: "clone".to_string(),
+                        params: vec![],
+                        return_type: StrongValType::None,
+                        type_params: vec![],
+                    });
+                }
+                "json" => {
+                    cleaned_methods.push(self.derive_struct_to_json(&struct_fields));
+                    derived_static_methods.push_str(&self.derive_struct_from_json(
+                        &struct_name,
+                        &struct_fields,
+                        ctor_field_count,
+                    ));
+                    struct_methods.push(Function {
+                        name: "toJSON".to_string(),
+                        params: vec![],
+                        return_type: StrongValType::None,
+                        type_params: vec![],
+                    });
+                    struct_static_methods.push(Function {
+                        name: "fromJSON".to_string(),
+                        params: vec![Variable {
+                            name: "json".to_string(),
+                            is_mut: true,
+                            val_type: StrongValType::None,
+                        }],
+                        return_type: StrongValType::None,
+                        type_params: vec![],
+                    });
+                }
+                "debug" => {
+                    cleaned_methods
+                        .push(self.derive_struct_to_string(&name_transpiled, &struct_fields));
+                    struct_methods.push(Function {
+                        name: "toString".to_string(),
+                        params: vec![],
+                        return_type: StrongValType::String,
+                        type_params: vec![],
+                    });
+                }
+                _ => {}
+            }
+        }
+
         // begin returning object.
         res.push_str("return ");
         if parsed_mixins.len() > 0 {
@@ -1267,6 +1852,7 @@ impl Transpiler {
         // close struct
         res.push_str("}\n");
 
+        let struct_mixins = parsed_mixins.clone();
         if parsed_mixins.len() > 0 {
             res.push_str(", ");
             for mixin in parsed_mixins {
@@ -1280,13 +1866,24 @@ impl Transpiler {
         res.push_str("}\n");
         // }\n
 
+        // derived static methods must exist before the first instance.
+        res.push_str(&derived_static_methods);
+
+        let mut macro_calls = String::new();
+        let decorated = self.apply_attributes(&name_transpiled, &struct_name, &attributes, Some(&mut macro_calls));
+        if decorated != struct_name {
+            res.push_str(&format!("{} = {};\n", struct_name, decorated));
+        }
+        res.push_str(&macro_calls);
+
         // add struct to namespace
-        self.namespace.structs.push(Struct {
+        self.add_namespace_struct(Struct {
             name: struct_name,
             params: struct_params,
             variables: struct_variables,
             methods: struct_methods,
             static_methods: struct_static_methods,
+            mixins: struct_mixins,
         });
 
         res
@@ -1294,7 +1891,14 @@ impl Transpiler {
 
     fn transpile_expression(&mut self, expression: ast::Expression) -> String {
         match expression {
-            ast::Expression::IntegerLiteral(token, value) => value.to_string(),
+            // numbers are emitted as written, i.e. 0xff or 1_000
+            ast::Expression::IntegerLiteral(token, value) => {
+                if token.typ == token::INT {
+                    token.literal
+                } else {
+                    value.to_string()
+                }
+            }
             Expression::StringLiteral(token, value) => {
                 let quote_type =
                     if (&value.contains("$")).to_owned() || (&value.contains("\n")).to_owned() {
@@ -1305,51 +1909,69 @@ impl Transpiler {
                         "\'"
                     };
 
-                // supporting string $ interpolation.
-                // 1. check if string contains $
-                // 2. get the positions of all $
-                // 3. if any of the positions is followed by a {, then ignore it because this should be interpreted by itself.
-                // 4. for all other positions, get the start and end position of the identifier using lex::ALLOWED_IN_IDENT.contains(char)
-                // 5. once we have the start and end position of the identifier, add ${} around the identifier
-                let mut str_value = string_interpolation(&value);
-
-                if quote_type == "`" {
-                    // extract expressions
-                    let rp = RuneParser::new(str_value.clone());
-                    let expressions = rp.expressions;
-
-                    for expression in expressions {
-                        let mut internal_t = Transpiler::new();
-                        internal_t.namespace = self.namespace.clone();
-                        internal_t.modules = self.modules.clone();
-
-                        let mut response = internal_t.transpile_from_string(expression.clone());
-                        response = response.trim().to_string();
-                        if response.ends_with(";") {
-                            response = response.strip_suffix(';').unwrap().to_string();
-                        }
-                        str_value = str_value.replace(
-                            format!("${{{}}}", expression).as_str(),
-                            format!("${{{}}}", response).as_str(),
-                        );
+                format!(
+                    "{}{}{}",
+                    quote_type,
+                    escape_quote(&value, quote_type),
+                    quote_type
+                )
+            }
+            Expression::TemplateLiteral(token, strings, expressions) => {
+                let mut result = String::from("`");
+                for (i, string) in strings.iter().enumerate() {
+                    result.push_str(&escape_quote(string, "`"));
+                    if let Some(expression) = expressions.get(i) {
+                        result.push_str("${");
+                        result.push_str(&self.transpile_expression(expression.to_owned()));
+                        result.push('}');
                     }
                 }
-
-                format!("{}{}{}", quote_type, str_value, quote_type)
+                result.push('`');
+                result
             }
             Expression::PrefixExpression(token, op, value) => {
+                let starts_with_exponent = starts_with_exponent(value.as_ref());
+                let mut value = self.transpile_expression(value.as_ref().to_owned());
+                // -2 ** 2 is -(2 ** 2), JS does not allow a unary expression on the left of **
+                if starts_with_exponent {
+                    value = format!("({})", value);
+                }
+                // - -x must not become --x
+                let separator = if (op == "-" || op == "+") && value.starts_with(op.as_str()) {
+                    " "
+                } else {
+                    ""
+                };
                 format!(
                     // "({}{})",
+                    "{}{}{}",
+                    op, separator, value
+                )
+            }
+            Expression::PostfixExpression(token, value, op) => {
+                format!(
                     "{}{}",
-                    op,
-                    self.transpile_expression(value.as_ref().to_owned())
+                    self.transpile_expression(value.as_ref().to_owned()),
+                    op
                 )
             }
             Expression::InfixExpression(token, left, operator, right) => {
+                let mut left_str = self.transpile_expression(left.as_ref().to_owned());
+                // JS does not allow a unary expression on the left of **, -2 ** 2 is (-2) ** 2
+                if operator == token::EXPONENT
+                    && matches!(
+                        left.as_ref(),
+                        Expression::PrefixExpression(_, _, _)
+                            | Expression::NotExpression(_, _)
+                            | Expression::AwaitExpression(_, _)
+                    )
+                {
+                    left_str = format!("({})", left_str);
+                }
                 format!(
                     // "({} {} {})",
                     "{} {} {}",
-                    self.transpile_expression(left.as_ref().to_owned()),
+                    left_str,
                     operator,
                     self.transpile_expression(right.as_ref().to_owned())
                 )
@@ -1390,19 +2012,28 @@ impl Transpiler {
 
                 res
             }
-            Expression::FunctionLiteral(token, name, paramters, return_type, body) => {
+            Expression::FunctionLiteral(token, name, paramters, return_type, body, type_params, attributes) => {
                 let mut res = String::new();
 
                 // add to namespace
                 let fn_name = self.transpile_expression(name.as_ref().to_owned());
                 {
-                    let namespace_function =
-                        self.create_namespace_function(&fn_name, paramters.clone(), return_type);
-                    self.namespace.functions.push(namespace_function);
+                    let namespace_function = self.create_namespace_function(
+                        &fn_name,
+                        paramters.clone(),
+                        return_type,
+                        &type_params,
+                    );
+                    self.add_namespace_function(namespace_function);
                 }
 
+                let keyword = if token.typ == token::GENERATOR {
+                    "function*"
+                } else {
+                    "function"
+                };
                 res.push_str(
-                    format!("function {}(", self.namespace.get_obj_name(&fn_name)).as_str(),
+                    format!("{} {}(", keyword, self.namespace.get_obj_name(&fn_name)).as_str(),
                 );
 
                 let ps = paramters.as_ref().to_owned();
@@ -1421,12 +2052,23 @@ impl Transpiler {
                 }
                 res.push_str("}\n");
 
+                let js_name = self.namespace.get_obj_name(&fn_name);
+                let mut macro_calls = String::new();
+                let decorated = self.apply_attributes(&fn_name, &js_name, &attributes, Some(&mut macro_calls));
+                if decorated != js_name {
+                    res.push_str(&format!("{} = {};\n", js_name, decorated));
+                }
+                res.push_str(&macro_calls);
+
                 res
             }
             Expression::CallExpression(token, name, arguments) => {
                 let mut res = String::new();
 
                 let name_exp = self.transpile_expression(name.as_ref().to_owned());
+                if let Some(message) = self.deprecated.get(&name_exp) {
+                    self.warnings.push(deprecated_call(&token, &name_exp, message));
+                }
 
                 // check if name_exp exists in native_ctx
                 let native_fn = self
@@ -1448,7 +2090,7 @@ impl Transpiler {
 
                 // parse the args
                 let parsed_args = self
-                    .transpile_call_arguments(arguments.as_ref().to_owned())
+                    .transpile_call_arguments(&token, &name_exp, arguments.as_ref().to_owned())
                     .join(",");
                 res.push_str(&parsed_args);
                 res.push_str(")");
@@ -1506,10 +2148,54 @@ impl Transpiler {
                 }
                 res
             }
+            Expression::OptionalDotExpression(token, left, right) => {
+                let left_side = self.transpile_expression(left.as_ref().to_owned());
+
+                // A namespace is never null, so `alias?.x` is the same as `alias.x`
+                let cloned_modules = self.modules.clone();
+                if let Some(namespace) = cloned_modules.iter().find(|n| n.has_name(&left_side)) {
+                    let new_right = self.convert_namespaced_dot_expression(namespace, &right);
+                    return self.transpile_expression(new_right);
+                }
+
+                let mut right_side = self.transpile_expression(right.as_ref().to_owned());
+                if right_side.starts_with("(") {
+                    right_side = right_side[1..right_side.len() - 1].to_string();
+                }
+                format!("{}?.{}", left_side, right_side)
+            }
+            Expression::OptionalIndexExpression(token, left, index) => {
+                let left_side = self.transpile_expression(left.as_ref().to_owned());
+                match index.as_ref() {
+                    Expression::RangeExpression(tk, start, end, _) => {
+                        let start = self.transpile_expression(start.as_ref().to_owned());
+                        if end.is_empty() {
+                            format!("{}?.slice({})", left_side, start)
+                        } else {
+                            let end = self.transpile_slice_end(tk, end);
+                            format!("{}?.slice({},{})", left_side, start, end)
+                        }
+                    }
+                    _ => format!(
+                        "{}?.[{}]",
+                        left_side,
+                        self.transpile_expression(index.as_ref().to_owned())
+                    ),
+                }
+            }
+            Expression::OptionalCallExpression(token, name, arguments) => {
+                let name_exp = self.transpile_expression(name.as_ref().to_owned());
+                let parsed_args = self
+                    .transpile_call_arguments(&token, &name_exp, arguments.as_ref().to_owned())
+                    .join(",");
+                format!("{}?.({})", name_exp, parsed_args)
+            }
             Expression::LambdaLiteral(token, paramters, body) => {
                 let mut res = String::new();
+                // arrow functions can not be generators.
+                let is_generator = token.typ == token::GENERATOR;
 
-                res.push_str("(");
+                res.push_str(if is_generator { "function*(" } else { "(" });
                 let params = paramters.as_ref().to_owned();
                 let joined_params = params
                     .iter()
@@ -1517,7 +2203,7 @@ impl Transpiler {
                     .collect::<Vec<_>>()
                     .join(",");
                 res.push_str(&joined_params);
-                res.push_str(") => {\n");
+                res.push_str(if is_generator { ") {\n" } else { ") => {\n" });
                 res.push_str(
                     match body.as_ref() {
                         Statement::EmptyStatement => String::from(" return undefined; "),
@@ -1556,7 +2242,7 @@ impl Transpiler {
 
                 res.push_str(&self.transpile_expression(left.as_ref().to_owned()));
                 match index.as_ref() {
-                    Expression::RangeExpression(tk, start, end) => {
+                    Expression::RangeExpression(tk, start, end, _) => {
                         res.push_str(
                             format!(
                                 ".slice({},",
@@ -1576,7 +2262,7 @@ impl Transpiler {
                                 );
                             }
                             _ => {
-                                res.push_str(&self.transpile_expression(end.as_ref().to_owned()));
+                                res.push_str(&self.transpile_slice_end(tk, end));
                                 res.push_str(")");
                             }
                         }
@@ -1629,6 +2315,18 @@ impl Transpiler {
                     self.transpile_expression(exp.as_ref().to_owned())
                 )
             }
+            Expression::YieldExpression(token, exp, is_delegate) => {
+                let keyword = if is_delegate { "yield*" } else { "yield" };
+                if exp.is_empty() {
+                    keyword.to_string()
+                } else {
+                    format!(
+                        "{} {}",
+                        keyword,
+                        self.transpile_expression(exp.as_ref().to_owned())
+                    )
+                }
+            }
             Expression::InExpression(token, left, right) => {
                 let mut res = String::new();
 
@@ -1671,6 +2369,7 @@ impl Transpiler {
                     self.transpile_stmt(block.as_ref().to_owned()).unwrap()
                 )
             }
+            Expression::ComptimeExpression(token, block) => self.transpile_comptime(&token, block.as_ref().to_owned()),
             Expression::AndExpression(token, left, right) => {
                 format!(
                     "{} && {}",
@@ -1696,72 +2395,28 @@ impl Transpiler {
             Expression::NewClassExpression(token, exp) => {
                 format!("new {}", self.transpile_expression(exp.as_ref().to_owned()))
             }
-            Expression::FloatLiteral(token, value) => format!("{}", value),
-            Expression::IsExpression(_tk, left, right) => {
-                format!(
-                    "typeof({}) == {}",
-                    self.transpile_expression(left.as_ref().to_owned()),
-                    self.transpile_expression(right.as_ref().to_owned())
-                )
+            Expression::FloatLiteral(token, value) => {
+                if token.typ == token::FLOAT || token.typ == token::INT {
+                    token.literal
+                } else {
+                    format!("{}", value)
+                }
             }
-            Expression::MacroExpression(_, name, arguments) => {
-                // Check if this has a namespace
-                let mut macro_object: Option<Macro> = None;
-
-                // Check macro namespace is correct, or udapet it
-                let full_macro_name = self.transpile_expression(name.as_ref().to_owned());
-
-                // The macro might already be namespaced, so we need to go through all namespaces
-                // and see if we can catch it.
-                // Starting with the current one
-
-                if let Some(found_macro) = self.namespace.macros.get(&full_macro_name) {
-                    macro_object = Some(found_macro.clone());
+            Expression::BigIntLiteral(token, value) => {
+                if token.typ == token::BIGINT {
+                    token.literal
                 } else {
-                    if !full_macro_name.starts_with(NAMESPACE_PREFIX) {
-                        return "".to_string();
-                    }
-
-                    for namespace in self.modules.iter() {
-                        if let Some(found_macro) = namespace.macros.get(&full_macro_name) {
-                            macro_object = Some(found_macro.clone());
-                            break;
-                        }
-                    }
-                }
-                if macro_object.is_none() {
-                    return "".to_string();
-                }
-                let macro_object = macro_object.unwrap();
-                let macro_arguments = arguments.as_ref().to_owned();
-
-                // parse the body first.
-                let transpiled_body = match &macro_object.body {
-                    Statement::BlockStatement(tk, stmts) => {
-                        let mut body = self.transpile_macro_block_stmt(stmts.as_ref().to_owned());
-                        body
-                    }
-                    Statement::ExpressionStatement(tk, macro_expro) => {
-                        self.transpile_expression(macro_expro.as_ref().to_owned())
-                    }
-                    _ => "".to_string(),
-                };
-
-                let macro_arguments = self.transpile_call_arguments(macro_arguments);
-
-                let macro_arguments =
-                    self.lineup_macro_args(macro_arguments, macro_object.paramaters.clone());
-
-                let result = macro_object.compile(macro_arguments, transpiled_body, &mut self.ejr);
-                
-                // Compile hygenic macros...
-                if macro_object.is_hygenic {
-                    let mut t = Transpiler::new();
-                    t.transpile_from_string(result)
-                } else {
-                    result
+                    format!("{}n", value)
                 }
             }
+            Expression::IsExpression(tk, left, right) => {
+                self.transpile_is_expression(&tk, left.as_ref(), right.as_ref())
+            }
+            // casts only change the value in native code.
+            Expression::CastExpression(_tk, left, _) => self.transpile_expression(left.as_ref().to_owned()),
+            Expression::MacroExpression(token, name, arguments) => {
+                self.transpile_macro_call(&name, arguments.as_ref().to_owned(), false)
+            }
             Expression::SpreadExpression(tk, expression) => {
                 format!(
                     "...{}",
@@ -1776,6 +2431,32 @@ impl Transpiler {
         }
     }
 
+    /// `x is "string"` is a typeof check. Types can be checked too, i.e. `x is int` or `x is SomeClass`.
+    fn transpile_is_expression(&mut self, tk: &token::Token, left: &Expression, right: &Expression) -> String {
+        let left = self.transpile_expression(left.to_owned());
+        let check = match right {
+            Expression::Identifier(_, name) => self.get_type(&Expression::Type(tk.to_owned(), name.to_owned())),
+            _ => StrongValType::None,
+        };
+
+        match &check {
+            StrongValType::Custom(name) if self.namespace.classes.contains(name) => {
+                format!("{} instanceof {}", left, self.transpile_expression(right.to_owned()))
+            }
+            StrongValType::Custom(name)
+                if self.namespace.structs.iter().any(|s| s.name == *name) || self.namespace.enums.contains(name) =>
+            {
+                self.errors.push(type_unsupported_is_check(right.get_token(), &right.get_token().literal));
+                String::new()
+            }
+            StrongValType::Array => format!("Array.isArray({})", left),
+            _ => match typeof_name(&check) {
+                Some(kind) if kind != "object" => format!("typeof({}) == \"{}\"", left, kind),
+                _ => format!("typeof({}) == {}", left, self.transpile_expression(right.to_owned())),
+            },
+        }
+    }
+
     fn join_expressions(&mut self, expressions: Vec<Expression>) -> String {
         expressions
             .iter()
@@ -1784,17 +2465,416 @@ impl Transpiler {
             .join(",")
     }
 
-    /// Add a macro function to later be used when calling.
-    fn add_macro_function(&mut self, name: String, params: Vec<Expression>, body: Statement, is_hygenic: bool) {
-        let pms = self.join_expressions(params.to_owned());
-        let mut parsed_args = vec![];
+    /// Find a macro by its (namespaced) name.
+    /// Wrap `value` in the decorators of a fn, struct, class or method. i.e. `@memoize` is `memoize(value)`
+    /// and `@route("/x")` is `route("/x")(value)`.
+    ///
+    /// Metadata attributes are not decorators. With `macro_calls`, an attribute that names a macro is a
+    /// macro call and is expanded into it.
+    fn apply_attributes(
+        &mut self,
+        item_name: &str,
+        value: &str,
+        attributes: &[Expression],
+        mut macro_calls: Option<&mut String>,
+    ) -> String {
+        self.add_attribute_metadata(item_name, attributes);
 
-        let macro_name = self.namespace.get_obj_name(&name);
-        for a in pms.split(",") {
-            parsed_args.push(a.to_string());
+        let mut decorators = vec![];
+        for attribute in attributes {
+            let (name, arguments) = split_attribute(attribute);
+            let attribute_name = self.transpile_expression(name.to_owned());
+            if METADATA_ATTRIBUTES.contains(&attribute_name.as_str()) {
+                continue;
+            }
+            if let Some(macro_calls) = macro_calls.as_deref_mut()
+                && self.find_macro(&attribute_name).is_some()
+            {
+                let js = self.transpile_macro_call(name, arguments, true);
+                macro_calls.push_str(&js);
+                continue;
+            }
+            decorators.push(self.transpile_expression(attribute.to_owned()));
+        }
+
+        // the closest decorator is applied first.
+        decorators
+            .iter()
+            .rev()
+            .fold(value.to_string(), |value, decorator| format!("{}({})", decorator, value))
+    }
+
+    /// Keep the metadata attributes of a fn or struct. i.e. `@deprecated("use g instead")` and `@test`.
+    fn add_attribute_metadata(&mut self, name: &str, attributes: &[Expression]) {
+        for attribute in attributes {
+            let (attribute_name, arguments) = split_attribute(attribute);
+            match self.transpile_expression(attribute_name.to_owned()).as_str() {
+                "deprecated" => {
+                    let message = match arguments.first() {
+                        Some(Expression::StringLiteral(_, message)) => message.to_owned(),
+                        _ => String::new(),
+                    };
+                    self.deprecated.insert(name.to_string(), message);
+                }
+                "test" if !self.tests.iter().any(|test| test == name) => {
+                    self.tests.push(name.to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn find_macro(&self, full_macro_name: &str) -> Option<Macro> {
+        self.find_macro_with_private(full_macro_name)
+            .filter(|(found_macro, is_local)| *is_local || found_macro.is_pub)
+            .map(|(found_macro, _)| found_macro)
+    }
+
+    /// Find a macro, public or not. The bool is true when the macro can be used here even if it is private.
+    fn find_macro_with_private(&self, full_macro_name: &str) -> Option<(Macro, bool)> {
+        // The macro might already be namespaced, so we need to go through all namespaces
+        // and see if we can catch it.
+        // Starting with the current one
+        let local_name = self.namespace.get_obj_name(&full_macro_name.to_string());
+        if let Some(found_macro) = self
+            .namespace
+            .macros
+            .get(full_macro_name)
+            .or_else(|| self.namespace.macros.get(&local_name))
+        {
+            return Some((found_macro.clone(), true));
+        }
+        // `alias.name` is looked up in the module imported as alias.
+        if let Some((alias, name)) = full_macro_name.rsplit_once('.') {
+            return self
+                .modules
+                .iter()
+                .filter(|namespace| namespace.alias == alias)
+                .find_map(|namespace| namespace.macros.get(&namespace.get_obj_name(&name.to_string())))
+                .map(|found_macro| (found_macro.clone(), false));
+        }
+        // A macro body can use the private macros of its own module.
+        if let Some(module) = self.macro_modules.last() {
+            let found_macro = self
+                .modules
+                .iter()
+                .filter(|namespace| &namespace.id == module)
+                .find_map(|namespace| namespace.macros.get(&namespace.get_obj_name(&full_macro_name.to_string())));
+            if let Some(found_macro) = found_macro {
+                return Some((found_macro.clone(), true));
+            }
+        }
+        // `import 'std' as _` and `import 'std'` keep the names as they are.
+        let is_namespaced = full_macro_name.starts_with(NAMESPACE_PREFIX);
+        self.modules
+            .iter()
+            .filter(|namespace| is_namespaced || namespace.alias == "_" || namespace.alias.is_empty())
+            .find_map(|namespace| namespace.macros.get(full_macro_name))
+            .map(|found_macro| (found_macro.clone(), false))
+    }
+
+    /// Replace the `@if`/`@cfg` statements with the branch for our target and features.
+    fn resolve_cfg(&mut self, statements: Vec<Statement>) -> Vec<Statement> {
+        let mut resolved = vec![];
+        for stmt in statements {
+            match stmt {
+                Statement::CfgStatement(_, condition, consequence, alternative) => {
+                    let branch = self.select_cfg_branch(&condition, *consequence, *alternative);
+                    resolved.extend(self.resolve_cfg(branch));
+                }
+                stmt => resolved.push(stmt),
+            }
+        }
+        resolved
+    }
+
+    /// Get the statements of the `@if`/`@cfg` branch that is compiled.
+    fn select_cfg_branch(&mut self, condition: &Expression, consequence: Statement, alternative: Statement) -> Vec<Statement> {
+        let is_set = eval_cfg(condition, &self.target, &self.features).unwrap_or_else(|err| {
+            self.errors.push(err);
+            false
+        });
+        let branch = if is_set { consequence } else { alternative };
+        match branch {
+            Statement::BlockStatement(_, stmts) => *stmts,
+            Statement::EmptyStatement => vec![],
+            // else @if(...)
+            stmt => vec![stmt],
+        }
+    }
+
+    /// Register the comptime helpers (read_file, env, compiler_version) in the EJR.
+    fn init_comptime(&mut self) {
+        if self.comptime_ready {
+            return;
+        }
+        self.comptime_ready = true;
+
+        self.ejr.register_callback("___comptime_read_file", Box::new(comptime_read_file), None);
+        self.ejr.register_callback("___comptime_env", Box::new(comptime_env), None);
+
+        let helpers = format!(
+            "globalThis.read_file = ___comptime_read_file;\nglobalThis.env = ___comptime_env;\nglobalThis.compiler_version = () => '{}';",
+            version::VERSION_CODE
+        );
+        self.ejr.eval_script(&helpers, "<comptime>");
+    }
+
+    /// Run a comptime block in the EJR and get the result as a JS literal.
+    ///
+    /// The result goes through JSON.stringify so it has to be JSON serializable.
+    fn transpile_comptime(&mut self, token: &token::Token, block: Statement) -> String {
+        let body = self.transpile_stmt(block).unwrap_or_default();
+
+        match self.eval_to_json(&format!("(() => {{\n{}\n}})()", body), "<comptime>") {
+            Ok(literal) => literal,
+            Err(message) => {
+                self.errors.push(comptime_evaluation_failed(token, &message));
+                "undefined".to_string()
+            }
+        }
+    }
+
+    /// Evaluate a JS expression in the EJR and get the result back as JSON.
+    ///
+    /// The comptime helpers are available to the expression.
+    fn eval_to_json(&mut self, js: &str, script_name: &str) -> Result<String, String> {
+        self.init_comptime();
+
+        // Errors are caught so that they can be reported as compile errors.
+        let script = format!(
+            "(() => {{\ntry {{\nreturn 'ok:' + JSON.stringify({});\n}} catch (e) {{\nreturn 'error:' + e;\n}}\n}})()",
+            js
+        );
+
+        let val = self.ejr.eval_script(&script, script_name);
+        if val == -1 {
+            return Err("it is not valid JavaScript".to_string());
+        }
+        let result = self.ejr.val_to_string(val).unwrap_or_default();
+        self.ejr.free_jsvalue(val);
+
+        match result.strip_prefix("ok:") {
+            Some(json) => Ok(json.to_string()),
+            None => Err(result.strip_prefix("error:").unwrap_or(&result).to_string()),
+        }
+    }
+
+    /// Run a procedural macro in the EJR and transpile the AST it returns.
+    ///
+    /// The arguments are passed as AST JSON. The macro returns an `Expression`, a `Statement`,
+    /// a list of `Statement`s or nothing.
+    fn expand_proc_macro(
+        &mut self,
+        token: &token::Token,
+        name: &str,
+        macro_object: &Macro,
+        arguments: Vec<Expression>,
+        is_statement: bool,
+    ) -> String {
+        let arguments = serde_json::to_string(&arguments).unwrap_or("[]".to_string());
+        let lambda = self.transpile_expression(Expression::LambdaLiteral(
+            token.to_owned(),
+            Box::new(macro_object.params.clone()),
+            Box::new(macro_object.body.clone()),
+        ));
+
+        let json = match self.eval_to_json(&format!("({})(...{})", lambda, arguments), name) {
+            Ok(json) => json,
+            Err(message) => {
+                self.errors.push(proc_macro_failed(token, name, &message));
+                return "".to_string();
+            }
+        };
+        // JSON.stringify(undefined) is not JSON.
+        if json == "undefined" || json == "null" {
+            return "".to_string();
+        }
+
+        let value: serde_json::Value = match serde_json::from_str(&json) {
+            Ok(value) => value,
+            Err(err) => {
+                self.errors.push(proc_macro_invalid_ast(token, name, &err.to_string()));
+                return "".to_string();
+            }
+        };
+        // Statements are told apart from expressions by their variant name.
+        let is_stmt = match &value {
+            serde_json::Value::Object(map) => map.keys().any(|key| key.ends_with("Statement")),
+            serde_json::Value::String(variant) => variant.ends_with("Statement"),
+            _ => false,
+        };
+
+        let result = if value.is_array() {
+            serde_json::from_value::<Vec<Statement>>(value).map(|stmts| self.transpile_macro_block_stmt(stmts))
+        } else if is_stmt {
+            serde_json::from_value::<Statement>(value).map(|stmt| self.transpile_stmt(stmt).unwrap_or_default())
+        } else {
+            serde_json::from_value::<Expression>(value).map(|expr| {
+                if is_statement {
+                    self.transpile_expression_stmt(token.to_owned(), expr)
+                } else {
+                    self.transpile_expression(expr)
+                }
+            })
+        };
+
+        result.unwrap_or_else(|err| {
+            self.errors.push(proc_macro_invalid_ast(token, name, &err.to_string()));
+            "".to_string()
+        })
+    }
+
+    /// Transpile a macro call.
+    ///
+    /// The call is expanded into the macro body and that is transpiled. `is_statement` is true
+    /// when the call is a statement on its own.
+    fn transpile_macro_call(&mut self, name: &Expression, arguments: Vec<Expression>, is_statement: bool) -> String {
+        let js = self.expand_macro_call(name, arguments, is_statement);
+        if !self.annotate_macros {
+            return js;
+        }
+
+        let macro_name = match name {
+            Expression::Identifier(_, macro_name) => macro_name.to_owned(),
+            _ => self.transpile_expression(name.to_owned()),
+        };
+        let token = name.get_token();
+        let location = format!("{}:{}:{}", token.file_name, token.line_number, token.col_number);
+        let annotated = if is_statement {
+            format!("// {}! at {}\n{}", macro_name, location, js)
+        } else {
+            format!("/* {}! at {} */ {}", macro_name, location, js)
+        };
+
+        self.macro_expansions.push(MacroExpansion { name: macro_name, location, js });
+        annotated
+    }
+
+    /// Expand a macro call into JS.
+    fn expand_macro_call(&mut self, name: &Expression, arguments: Vec<Expression>, is_statement: bool) -> String {
+        let full_macro_name = self.transpile_expression(name.to_owned());
+        let Some(macro_object) = self.find_macro(&full_macro_name) else {
+            let error = match self.find_macro_with_private(&full_macro_name) {
+                Some(_) => macro_is_private(name.get_token(), &full_macro_name),
+                None => macro_not_found(name.get_token(), &full_macro_name),
+            };
+            self.errors.push(error);
+            return "".to_string();
+        };
+
+        // Macros used in the body are looked up in the module the macro is declared in too.
+        self.macro_modules.push(macro_object.module.clone());
+        let js = self.expand_macro(name, &full_macro_name, macro_object, arguments, is_statement);
+        self.macro_modules.pop();
+        js
+    }
+
+    fn expand_macro(
+        &mut self,
+        name: &Expression,
+        full_macro_name: &str,
+        macro_object: Macro,
+        arguments: Vec<Expression>,
+        is_statement: bool,
+    ) -> String {
+
+        if macro_object.is_procedural {
+            return self.expand_proc_macro(name.get_token(), full_macro_name, &macro_object, arguments, is_statement);
+        }
+
+        let first_id = self.macro_ids;
+        let expansion = macro_object.expand(arguments, first_id, &mut |arg| {
+            self.transpile_expression(arg.to_owned())
+        });
+        self.macro_ids = expansion.next_id;
+
+        // Compile time macros run the expanded body and use the string it builds as the code.
+        if macro_object.is_hygenic {
+            let script = self.transpile_expansion(expansion, true);
+            let result = macro_object.compile(script, &mut self.ejr);
+            let mut t = Transpiler::new();
+            t.target = self.target.clone();
+            t.features = self.features.clone();
+            t.annotate_macros = self.annotate_macros;
+            let js = t.transpile_from_string(result);
+            self.macro_expansions.extend(t.macro_expansions);
+            return js;
+        }
+
+        self.transpile_expansion(expansion, is_statement)
+    }
+
+    /// Transpile an expanded macro body.
+    fn transpile_expansion(&mut self, expansion: Expansion, is_statement: bool) -> String {
+        let body = match expansion.body {
+            Statement::BlockStatement(_, stmts) if stmts.len() == 1 => stmts[0].to_owned(),
+            body => body,
+        };
+
+        match body {
+            Statement::ExpressionStatement(tk, expr) => {
+                let expr = if expansion.temps.is_empty() {
+                    *expr
+                } else {
+                    // ((__a_1) => body)(arg) evaluates each argument once.
+                    let (params, args): (Vec<_>, Vec<_>) = expansion
+                        .temps
+                        .into_iter()
+                        .map(|(temp, arg)| (Expression::Identifier(tk.to_owned(), temp), arg))
+                        .unzip();
+                    let lambda = Expression::LambdaLiteral(
+                        tk.to_owned(),
+                        Box::new(params),
+                        Box::new(Statement::ExpressionStatement(tk.to_owned(), expr)),
+                    );
+                    Expression::CallExpression(
+                        tk.to_owned(),
+                        Box::new(Expression::GroupedExpression(tk.to_owned(), Box::new(lambda))),
+                        Box::new(args),
+                    )
+                };
+                if is_statement {
+                    self.transpile_expression_stmt(tk, expr)
+                } else {
+                    self.transpile_expression(expr)
+                }
+            }
+            Statement::BlockStatement(tk, stmts) => {
+                let mut body: Vec<Statement> = expansion
+                    .temps
+                    .into_iter()
+                    .map(|(temp, arg)| {
+                        Statement::VariableStatement(
+                            tk.to_owned(),
+                            Box::new(Expression::Identifier(tk.to_owned(), temp)),
+                            None,
+                            Box::new(arg),
+                            false,
+                        )
+                    })
+                    .collect();
+                body.extend(*stmts);
+                self.transpile_macro_block_stmt(body)
+            }
+            body => self.transpile_stmt(body).unwrap_or_default(),
         }
+    }
 
-        let ej_macro = Macro::new(macro_name.clone(), parsed_args, body, is_hygenic);
+    /// Add a macro function to later be used when calling.
+    fn add_macro_function(
+        &mut self,
+        name: String,
+        params: Vec<Expression>,
+        body: Statement,
+        is_hygenic: bool,
+        is_procedural: bool,
+        is_pub: bool,
+    ) {
+        let macro_name = self.namespace.get_obj_name(&name);
+        let mut ej_macro = Macro::new(macro_name.clone(), params, body, is_hygenic, is_procedural);
+        ej_macro.is_pub = is_pub;
+        ej_macro.module = self.namespace.id.clone();
         // add to namespace
         self.namespace.macros.insert(macro_name, ej_macro);
     }
@@ -1809,7 +2889,7 @@ impl Transpiler {
     ) -> String {
         let mut res = String::new();
         match method {
-            Expression::FunctionLiteral(_, name, params, _, body) => {
+            Expression::FunctionLiteral(token, name, params, _, body, _, attributes) => {
                 let name = self.transpile_expression(name.as_ref().to_owned());
                 let params = {
                     let params = params.as_ref().to_owned();
@@ -1833,16 +2913,23 @@ impl Transpiler {
                     res.push_str(format!("{}: ", &name).as_str());
                 }
 
+                let mut function = String::new();
                 if is_async {
-                    res.push_str("async ");
+                    function.push_str("async ");
                 }
-                res.push_str(format!("function({})", &params).as_str());
-                res.push_str("{");
+                if token.typ == token::GENERATOR {
+                    function.push_str(format!("function*({})", &params).as_str());
+                } else {
+                    function.push_str(format!("function({})", &params).as_str());
+                }
+                function.push('{');
 
                 if let Some(body) = body {
-                    res.push_str(&body);
+                    function.push_str(&body);
                 }
-                res.push_str("}");
+                function.push('}');
+                let method_name = format!("{}.{}", struct_name, name);
+                res.push_str(&self.apply_attributes(&method_name, &function, &attributes, None));
                 if is_static {
                     res.push_str(";\n");
                 } else {
@@ -1855,6 +2942,187 @@ impl Transpiler {
         res
     }
 
+    /// Derive `equals` for a struct.
+    ///
+    /// Arrays are compared item by item and other values use their own `equals` if they have one.
+    fn derive_struct_equals(&self, fields: &[(String, StrongValType)]) -> String {
+        let mut checks = vec!["other != null".to_string()];
+        for (name, val_type) in fields {
+            checks.push(match val_type {
+                StrongValType::Int
+                | StrongValType::Float
+                | StrongValType::Bool
+                | StrongValType::String => format!("this.{name} === other.{name}"),
+                _ if is_array_type(val_type) => format!(
+                    "(this.{name} === other.{name} || (Array.isArray(this.{name}) && Array.isArray(other.{name}) && this.{name}.length === other.{name}.length && this.{name}.every((v, i) => v === other.{name}[i])))"
+                ),
+                _ => format!(
+                    "(this.{name} === other.{name} || (Array.isArray(this.{name}) ? (Array.isArray(other.{name}) && this.{name}.length === other.{name}.length && this.{name}.every((v, i) => v === other.{name}[i])) : (this.{name} != null && typeof this.{name}.equals === 'function' && this.{name}.equals(other.{name}))))"
+                ),
+            });
+        }
+
+        format!("equals: function(other){{return {};}},\n", checks.join(" && "))
+    }
+
+    /// Derive `clone` for a struct.
+    ///
+    /// Arrays are copied and other values use their own `clone` if they have one.
+    fn derive_struct_clone(
+        &self,
+        struct_name: &str,
+        fields: &[(String, StrongValType)],
+        ctor_field_count: usize,
+    ) -> String {
+        let copies: Vec<String> = fields
+            .iter()
+            .map(|(name, val_type)| match val_type {
+                StrongValType::Int
+                | StrongValType::Float
+                | StrongValType::Bool
+                | StrongValType::String => format!("this.{name}"),
+                _ if is_array_type(val_type) => {
+                    format!("(Array.isArray(this.{name}) ? [...this.{name}] : this.{name})")
+                }
+                _ => format!(
+                    "(Array.isArray(this.{name}) ? [...this.{name}] : this.{name} != null && typeof this.{name}.clone === 'function' ? this.{name}.clone() : this.{name})"
+                ),
+            })
+            .collect();
+
+        let ctor_args = copies[..ctor_field_count].join(", ");
+        let rest: Vec<String> = fields[ctor_field_count..]
+            .iter()
+            .zip(copies[ctor_field_count..].iter())
+            .map(|((name, _), copy)| format!("{name}: {copy}"))
+            .collect();
+
+        format!(
+            "clone: function(){{return Object.assign({struct_name}({ctor_args}), {{{}}});}},\n",
+            rest.join(", ")
+        )
+    }
+
+    /// Derive `toJSON` for a struct.
+    fn derive_struct_to_json(&self, fields: &[(String, StrongValType)]) -> String {
+        let values: Vec<String> = fields
+            .iter()
+            .map(|(name, _)| format!("{name}: this.{name}"))
+            .collect();
+
+        format!("toJSON: function(){{return {{{}}};}},\n", values.join(", "))
+    }
+
+    /// Derive the static `fromJSON` for a struct.
+    ///
+    /// Values are converted to the declared field types. Fields typed as another struct use its `fromJSON`.
+    /// A missing key leaves the field `undefined`, or at its default for fields declared in the body.
+    fn derive_struct_from_json(
+        &self,
+        struct_name: &str,
+        fields: &[(String, StrongValType)],
+        ctor_field_count: usize,
+    ) -> String {
+        let conversions: Vec<String> = fields
+            .iter()
+            .map(|(name, val_type)| self.json_conversion(&format!("data.{name}"), val_type))
+            .collect();
+        let ctor_args: Vec<String> = fields[..ctor_field_count]
+            .iter()
+            .zip(conversions.iter())
+            .map(|((name, _), conversion)| format!("'{name}' in data ? {conversion} : undefined"))
+            .collect();
+
+        let mut res = String::new();
+        res.push_str(&format!("{struct_name}.fromJSON = function(json){{"));
+        res.push_str("const data = typeof json === 'string' ? JSON.parse(json) : json;");
+        res.push_str(&format!("const obj = {struct_name}({});", ctor_args.join(", ")));
+        for ((name, _), conversion) in fields[ctor_field_count..]
+            .iter()
+            .zip(conversions[ctor_field_count..].iter())
+        {
+            res.push_str(&format!("if ('{name}' in data) {{obj.{name} = {conversion};}}"));
+        }
+        res.push_str("return obj;};\n");
+
+        res
+    }
+
+    /// Convert a JSON value to `val_type`. Optional types keep `null` and `undefined` as they are.
+    fn json_conversion(&self, value: &str, val_type: &StrongValType) -> String {
+        if is_nullable(val_type) {
+            let conversion = self.json_conversion(value, &remove_null(val_type));
+            if conversion == value {
+                return conversion;
+            }
+            return format!("({value} == null ? {value} : {conversion})");
+        }
+
+        match val_type {
+            StrongValType::Int => format!("Math.trunc(Number({value}))"),
+            StrongValType::Float => format!("Number({value})"),
+            StrongValType::Bool => format!("Boolean({value})"),
+            StrongValType::String => format!("String({value})"),
+            StrongValType::Generic(_, type_args) if is_array_type(val_type) => {
+                match self.get_from_json_struct(type_args.first()) {
+                    Some(item_struct) => format!(
+                        "Array.from({value} ?? [], (v) => v != null && typeof {item_struct}.fromJSON === 'function' ? {item_struct}.fromJSON(v) : v)"
+                    ),
+                    None => format!("Array.from({value} ?? [])"),
+                }
+            }
+            StrongValType::Array => format!("Array.from({value} ?? [])"),
+            _ => match self.get_from_json_struct(Some(val_type)) {
+                Some(field_struct) => format!(
+                    "({value} != null && typeof {field_struct}.fromJSON === 'function' ? {field_struct}.fromJSON({value}) : {value})"
+                ),
+                None => value.to_string(),
+            },
+        }
+    }
+
+    /// Get the struct name of a `Custom` type if the struct exists in this namespace.
+    fn get_from_json_struct(&self, val_type: Option<&StrongValType>) -> Option<String> {
+        match val_type {
+            Some(StrongValType::Custom(type_name)) => {
+                let struct_name = self.namespace.get_obj_name(type_name);
+                if self.namespace.structs.iter().any(|s| s.name == struct_name) {
+                    Some(struct_name)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Derive `toString` for a struct. i.e. `Person { name: "Jordan", age: 23 }`
+    fn derive_struct_to_string(
+        &self,
+        struct_name: &str,
+        fields: &[(String, StrongValType)],
+    ) -> String {
+        let values: Vec<String> = fields
+            .iter()
+            .map(|(name, val_type)| match val_type {
+                StrongValType::String => format!("'{name}: ' + JSON.stringify(this.{name})"),
+                _ if is_array_type(val_type) => {
+                    format!("'{name}: [' + (this.{name} ?? []).join(', ') + ']'")
+                }
+                _ => format!("'{name}: ' + String(this.{name})"),
+            })
+            .collect();
+
+        if values.is_empty() {
+            return format!("toString: function(){{return '{struct_name} {{}}';}},\n");
+        }
+
+        format!(
+            "toString: function(){{return '{struct_name} {{ ' + {} + ' }}';}},\n",
+            values.join(" + ', ' + ")
+        )
+    }
+
     /// This gets the actual data for the struct method expression.
     /// Takes in `method` and returns the inner workings, and whether or not it is static..
     fn get_struct_method_function_exp(
@@ -1872,7 +3140,7 @@ impl Transpiler {
                     result.1,
                 );
             }
-            Expression::FunctionLiteral(fn_token, fn_name, params, var_type, body) => {
+            Expression::FunctionLiteral(fn_token, fn_name, params, var_type, body, type_params, attributes) => {
                 // // check if is a predescribed method like new => constructor
                 // if self.transpile_expression(fn_name.as_ref().to_owned()) == "new" {
                 //     return (
@@ -1904,12 +3172,22 @@ impl Transpiler {
                             Box::new(params.as_ref().to_owned()[1..].to_vec()),
                             var_type,
                             body,
+                            type_params,
+                            attributes,
                         ),
                         false,
                     );
                 } else {
                     return (
-                        Expression::FunctionLiteral(fn_token, fn_name, params, var_type, body),
+                        Expression::FunctionLiteral(
+                            fn_token,
+                            fn_name,
+                            params,
+                            var_type,
+                            body,
+                            type_params,
+                            attributes,
+                        ),
                         true,
                     );
                 }
@@ -1919,105 +3197,133 @@ impl Transpiler {
         (method, false)
     }
 
-    /// Transpile the arguments in a call.
-    ///
-    /// Works for CallExpression
-    fn transpile_call_arguments(&mut self, arguments: Vec<Expression>) -> Vec<String> {
-        arguments.iter().map(|v| self.transpile_expression(v.to_owned())).collect()
-        // let mut result = vec![];
-        // let mut has_named = false;
-        // let mut named_params = String::new();
-
-        // for i in 0..arguments.len() {
-        //     let argument = arguments[i].clone();
-        //     match argument {
-        //         // Expression::Identifier(tk, name) => {
-        //         //     if has_named {
-        //         //         // TODO: better errors
-        //         //         panic!("Can not have unnmaed after named");
-        //         //     }
-        //         //     result.push_str(&name);
-        //         //     if i < arguments.len() - 1 {
-        //         //         result.push_str(",");
-        //         //     }
-        //         // }
-        //         Expression::AssignExpression(tk, ident, value) => {
-        //             if !has_named {
-        //                 has_named = true;
-        //                 named_params.push_str("{");
-        //             }
-        //             let ident_parsed = self.transpile_expression(ident.as_ref().to_owned());
-        //             let value_parsed = self.transpile_expression(value.as_ref().to_owned());
-
-        //             named_params.push_str(format!("'{ident_parsed}': {value_parsed},").as_str());
-        //         }
-        //         _ => {
-        //             if has_named {
-        //                 // TODO: better errors
-        //                 panic!("Can not have unnamed after named");
-        //             }
-
-        //             result.push(self.transpile_expression(argument));
-        //         }
-        //     }
-        // }
-
-        // if has_named {
-        //     named_params.push_str("}");
-        //     result.push(named_params);
-        // }
-
-        // result
-    }
-
-    /// Lineup macro arguments to correctly pass in:
+    /// Transpile the arguments of a call to `fn_name`.
     ///
-    /// regular arguments
-    /// default paramaters
-    /// n number arguments.
-    fn lineup_macro_args(
-        &self,
-        macro_arguments: Vec<String>,
-        macro_params: Vec<String>,
+    /// Named arguments (`f(name="x")`) are lined up with the paramaters of `fn_name` when it is
+    /// a known function or struct. A `kwargs` paramater takes any named arguments that are not paramaters.
+    /// Otherwise the named arguments are passed as one object after the positional ones.
+    fn transpile_call_arguments(
+        &mut self,
+        tk: &token::Token,
+        fn_name: &str,
+        arguments: Vec<Expression>,
     ) -> Vec<String> {
-        let mut result = vec![];
-
-        for (i, param) in macro_params.iter().enumerate() {
-            if param.contains("=") {
-                // This is a default paramater
-                let value = param
-                    .split("=")
-                    .collect::<Vec<&str>>()
-                    .get(1)
-                    .unwrap()
-                    .trim()
-                    .to_string();
-
-                if let Some(macro_param) = macro_arguments.get(i) {
-                    result.push(macro_param.to_owned());
+        let mut positional = vec![];
+        let mut named: Vec<(token::Token, String, String)> = vec![];
+
+        for argument in arguments {
+            if let Expression::AssignExpression(_, left, value) = &argument
+                && let Expression::Identifier(name_tk, name) = left.as_ref()
+            {
+                if named.iter().any(|(_, n, _)| n == name) {
+                    self.errors.push(call_duplicate_argument(name_tk, fn_name, name));
                 } else {
-                    result.push(value);
-                }
-            } else if param.contains("...") {
-                // This is a n number of params
-                let mut n_args = String::new();
-                for j in i..macro_arguments.len() {
-                    n_args.push_str(&macro_arguments.get(j).unwrap().to_string());
-                    if j < macro_arguments.len() - 1 {
-                        n_args.push_str(",");
-                    }
+                    let value = self.transpile_expression(value.as_ref().to_owned());
+                    named.push((name_tk.to_owned(), name.to_owned(), value));
                 }
-                result.push(n_args);
-            } else {
-                if macro_arguments.len() <= i {
-                    result.push(String::from("undefined"));
-                } else {
-                    // Regular
-                    result.push(macro_arguments.get(i).unwrap().to_string());
+                continue;
+            }
+
+            if !named.is_empty() {
+                self.errors.push(call_positional_after_named(argument.get_token()));
+            }
+            positional.push(self.transpile_expression(argument));
+        }
+
+        if named.is_empty() {
+            return positional;
+        }
+
+        let params: Option<Vec<String>> = self
+            .find_function(fn_name)
+            .map(|function| &function.params)
+            .or_else(|| self.find_struct(fn_name).map(|s| &s.params))
+            .map(|params| params.iter().map(|p| p.name.to_owned()).collect());
+        let Some(params) = params else {
+            positional.push(named_arguments_object(&named));
+            return positional;
+        };
+
+        // Line up the arguments with the paramaters.
+        let mut slots: Vec<Option<String>> = vec![None; params.len().max(positional.len())];
+        for (i, arg) in positional.into_iter().enumerate() {
+            slots[i] = Some(arg);
+        }
+
+        let mut kwargs = vec![];
+        for (name_tk, name, value) in named {
+            match params.iter().position(|p| p == &name) {
+                Some(i) if slots[i].is_some() => {
+                    self.errors.push(call_duplicate_argument(&name_tk, fn_name, &name));
+                }
+                Some(i) => slots[i] = Some(value),
+                None if params.iter().any(|p| p == "kwargs") => kwargs.push((name_tk, name, value)),
+                None => {
+                    self.errors.push(call_unknown_named_argument(&name_tk, fn_name, &name));
                 }
             }
         }
-        result
+
+        if !kwargs.is_empty() {
+            let i = params.iter().position(|p| p == "kwargs").unwrap();
+            if slots[i].is_some() {
+                self.errors.push(call_duplicate_argument(tk, fn_name, "kwargs"));
+            } else {
+                slots[i] = Some(named_arguments_object(&kwargs));
+            }
+        }
+
+        // Skipped paramaters are undefined so their default value is used.
+        while let Some(None) = slots.last() {
+            slots.pop();
+        }
+        slots
+            .into_iter()
+            .map(|slot| slot.unwrap_or("undefined".to_string()))
+            .collect()
+    }
+
+    /// Find a easyjs function by the name it is called with.
+    fn find_function(&self, fn_name: &str) -> Option<&Function> {
+        let local_name = self.namespace.get_obj_name(&fn_name.to_string());
+        self.namespace
+            .functions
+            .iter()
+            .find(|f| f.name == local_name)
+            .or_else(|| {
+                self.modules
+                    .iter()
+                    .flat_map(|module| module.functions.iter())
+                    .find(|f| f.name == fn_name)
+            })
+    }
+
+    /// Find a easyjs struct by the name it is constructed with.
+    fn find_struct(&self, struct_name: &str) -> Option<&Struct> {
+        let local_name = self.namespace.get_obj_name(&struct_name.to_string());
+        self.namespace
+            .structs
+            .iter()
+            .find(|s| s.name == local_name)
+            .or_else(|| {
+                self.modules
+                    .iter()
+                    .flat_map(|module| module.structs.iter())
+                    .find(|s| s.name == struct_name)
+            })
+    }
+
+    /// Add a struct to the namespace, replacing the one declared before it was transpiled.
+    fn add_namespace_struct(&mut self, new_struct: Struct) {
+        self.namespace.structs.retain(|s| s.name != new_struct.name);
+        self.namespace.structs.push(new_struct);
+    }
+
+    /// Add a function to the namespace, unless it was already declared.
+    fn add_namespace_function(&mut self, function: Function) {
+        if !self.namespace.functions.iter().any(|f| f.name == function.name) {
+            self.namespace.functions.push(function);
+        }
     }
 
     /// Create a Namespace function from name, params, and return_type
@@ -2026,10 +3332,19 @@ impl Transpiler {
         name: &String,
         params: Box<Vec<Expression>>,
         return_type: Box<Expression>,
+        type_params: &[Expression],
     ) -> Function {
-        let function_type = get_param_type_by_string_ej(
-                &self.transpile_expression(return_type.as_ref().to_owned()),
-            );
+        let type_params: Vec<String> = type_params
+            .iter()
+            .filter_map(|type_param| match type_param {
+                Expression::Type(_, name) => Some(name.to_owned()),
+                _ => None,
+            })
+            .collect();
+        let function_type = bind_type_params(
+            &self.get_type(return_type.as_ref()),
+            &type_params,
+        );
 
         let fn_name: String;
         if !name.contains('.') {
@@ -2048,12 +3363,13 @@ impl Transpiler {
                     let result = self.transpile_function_paramater(v);
                     Variable {
                         is_mut: true,
-                        val_type: result.1,
+                        val_type: bind_type_params(&result.1, &type_params),
                         name: result.0    
                     }
                 })
                 .collect(),
             return_type: function_type,
+            type_params,
         }
     }
 
@@ -2078,9 +3394,7 @@ impl Transpiler {
             }
             Expression::IdentifierWithType(_, name, v_type) => {
                 ident = name.to_owned();
-                val_type = get_param_type_by_string_ej(
-                    &self.transpile_expression(v_type.as_ref().to_owned())
-                );
+                val_type = self.get_type(v_type.as_ref());
             }
             Expression::SpreadExpression(_, expr) => {
                 ident = self.transpile_expression(expr.as_ref().to_owned());
@@ -2188,6 +3502,18 @@ impl Transpiler {
                 let new_left = self.convert_namespaced_dot_expression(namespace, left);
                 Expression::DotExpression(token.to_owned(), Box::new(new_left), right.to_owned())
             }
+            Expression::OptionalDotExpression(token, left, right) => {
+                let new_left = self.convert_namespaced_dot_expression(namespace, left);
+                Expression::OptionalDotExpression(token.to_owned(), Box::new(new_left), right.to_owned())
+            }
+            Expression::OptionalIndexExpression(token, left, index) => {
+                let new_left = self.convert_namespaced_dot_expression(namespace, left);
+                Expression::OptionalIndexExpression(token.to_owned(), Box::new(new_left), index.to_owned())
+            }
+            Expression::OptionalCallExpression(token, name, args) => {
+                let new_name = self.convert_namespaced_dot_expression(namespace, name);
+                Expression::OptionalCallExpression(token.to_owned(), Box::new(new_name), args.to_owned())
+            }
             Expression::AssignExpression(tk, left, right) => {
                 let new_left = self.convert_namespaced_dot_expression(namespace, &left);
                 Expression::AssignExpression(tk.to_owned(), Box::new(new_left), right.to_owned())
@@ -2208,45 +3534,51 @@ impl Transpiler {
     }
 }
 
-/// Interpolate the string with $$$$$
-fn string_interpolation(input: &str) -> String {
-    let mut result = String::new();
-
-    let mut listen_for_ending = false;
-    let mut found_at = 0;
-
-    let chars: Vec<_> = input.chars().collect(); // Collect chars to access by index
+/// Escape the quotes in a string that would end it early.
+/// Split `@name(args)` into the name and arguments.
+fn split_attribute(attribute: &Expression) -> (&Expression, Vec<Expression>) {
+    match attribute {
+        Expression::CallExpression(_, name, arguments) => (name.as_ref(), arguments.as_ref().to_owned()),
+        _ => (attribute, vec![]),
+    }
+}
 
-    for i in 0..chars.len() {
-        let c = chars[i];
-        let next_char = chars.get(i + 1).copied().unwrap_or(' '); // Look ahead safely
-        let prev_char = if i > 0 { chars[i - 1] } else { ' ' }; // Look behind safely
+fn escape_quote(value: &str, quote: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
 
-        if c == '$' && prev_char != '\\' && next_char != '{' {
-            if listen_for_ending {
-                result.push('}');
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            result.push(c);
+            if let Some(next) = chars.next() {
+                result.push(next);
             }
-            listen_for_ending = true;
-            found_at = i;
-            result.push('$');
-            result.push('{');
             continue;
         }
 
-        if listen_for_ending && i > found_at {
-            if !c.is_alphabetic() && !ALLOWED_IN_IDENT.contains(c) {
-                listen_for_ending = false; // Stop listening if a non-alphabetic char is found
-                result.push('}');
-            }
+        if quote.starts_with(c) {
+            result.push('\\');
         }
-
         result.push(c);
-
-        // Close the interpolation block if at the end of input and still listening
-        if listen_for_ending && i == chars.len() - 1 {
-            result.push('}');
-        }
     }
 
     result
 }
+
+/// Does the JS of this expression start with a `**`? i.e. `2 ** 2 + 1`
+fn starts_with_exponent(expression: &Expression) -> bool {
+    match expression {
+        Expression::InfixExpression(_, left, op, _) => op == token::EXPONENT || starts_with_exponent(left),
+        _ => false,
+    }
+}
+
+/// Named arguments as a object. i.e. `{a:1,b:2}`
+fn named_arguments_object(named: &[(token::Token, String, String)]) -> String {
+    let entries = named
+        .iter()
+        .map(|(_, name, value)| format!("{}:{}", name, value))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", entries)
+}
@@ -149,6 +149,9 @@ pub const ARR_PUSH_ARRAY_IDX: u32 = 16;
 /// Returns the type, a integer value, a float value, a string value, and a array value.
 pub const ARR_GET_ITEM_IDX: u32 = 17;
 
+// =====      Structs      =====
+// __struct_alloc does not have a fixed IDX, it is only added when a native block has structs.
+
 // pub const RE_ALLOCATE_ARRAY_IDX: u32 = 8;
 // pub const ARR_GET_SIZE_IDX: u32 = 9;
 // pub const DELETE_FROM_ARR_IDX: u32 = 10;
@@ -176,4 +179,7 @@ pub const ARR_PUSH_INT_NAME: &str = "__arr_push_int";
 pub const ARR_PUSH_FLOAT_NAME: &str = "__arr_push_float";
 pub const ARR_PUSH_STRING_NAME: &str = "__arr_push_string";
 pub const ARR_PUSH_ARRAY_NAME: &str = "__arr_push_array";
-pub const ARR_GET_ITEM_NAME: &str = "__arr_get_item";
\ No newline at end of file
+pub const ARR_GET_ITEM_NAME: &str = "__arr_get_item";
+
+// =====        STRUCTS         =====
+pub const STRUCT_ALLOC_NAME: &str = "__struct_alloc";
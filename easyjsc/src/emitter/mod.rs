@@ -6,4 +6,6 @@ pub mod utils;
 pub mod strings;
 pub mod instruction_generator;
 pub mod builtins;
-pub mod arrays;
\ No newline at end of file
+pub mod arrays;
+pub mod structs;
+pub mod numbers;
@@ -0,0 +1,245 @@
+use wasm_encoder::Instruction;
+
+use crate::{lexer::token, typechecker::StrongValType};
+
+/// The rank of a number type. A number can go to a higher rank without `as`.
+///
+/// f64 > f32 > i64 > i32, bools, u8 and u32 are all i32.
+fn rank_of_type(val_type: &StrongValType) -> Option<u8> {
+    match val_type {
+        StrongValType::Int | StrongValType::Bool | StrongValType::U8 | StrongValType::U32 => Some(0),
+        StrongValType::I64 => Some(1),
+        StrongValType::Float => Some(2),
+        StrongValType::F64 => Some(3),
+        _ => None,
+    }
+}
+
+/// Is this a number type (bools included)?
+pub fn is_number_type(val_type: &StrongValType) -> bool {
+    rank_of_type(val_type).is_some()
+}
+
+/// Is the value of this number type unsigned?
+fn is_unsigned(val_type: &StrongValType) -> bool {
+    matches!(
+        val_type,
+        StrongValType::U8 | StrongValType::U32 | StrongValType::Bool
+    )
+}
+
+/// The type both sides of an operation are converted to.
+///
+/// The highest rank wins. For i32 types u32 wins over int (just like C) and u8 only stays u8 with another u8.
+pub fn promote_number_types(left: &StrongValType, right: &StrongValType) -> Option<StrongValType> {
+    let (left_rank, right_rank) = (rank_of_type(left)?, rank_of_type(right)?);
+    if left == right {
+        return Some(left.to_owned());
+    }
+
+    Some(if left_rank > right_rank {
+        left.to_owned()
+    } else if right_rank > left_rank {
+        right.to_owned()
+    } else if *left == StrongValType::U32 || *right == StrongValType::U32 {
+        StrongValType::U32
+    } else {
+        StrongValType::Int
+    })
+}
+
+/// Can a number be converted without `as`?
+///
+/// i32 types convert to each other (u8 wraps) and anything converts to a higher rank.
+pub fn is_implicit_conversion(from: &StrongValType, to: &StrongValType) -> bool {
+    match (rank_of_type(from), rank_of_type(to)) {
+        (Some(from_rank), Some(to_rank)) => {
+            from == to || from_rank == to_rank && from_rank == 0 || from_rank < to_rank
+        }
+        _ => false,
+    }
+}
+
+/// The instructions to convert a number from one type to another.
+///
+/// Floats are truncated (and saturated) when going to an int and u8 wraps.
+pub fn convert_number(from: &StrongValType, to: &StrongValType) -> Vec<Instruction<'static>> {
+    let wrap_u8 = [Instruction::I32Const(0xff), Instruction::I32And];
+    let unsigned = is_unsigned(from);
+
+    match (from, to) {
+        _ if from == to => vec![],
+        (StrongValType::I64, StrongValType::Bool) => vec![Instruction::I64Eqz, Instruction::I32Eqz],
+        (StrongValType::Float, StrongValType::Bool) => {
+            vec![Instruction::F32Const(0.0), Instruction::F32Ne]
+        }
+        (StrongValType::F64, StrongValType::Bool) => {
+            vec![Instruction::F64Const(0.0), Instruction::F64Ne]
+        }
+        (_, StrongValType::Bool) => vec![Instruction::I32Const(0), Instruction::I32Ne],
+        (StrongValType::I64, StrongValType::U8) => [vec![Instruction::I32WrapI64], wrap_u8.to_vec()].concat(),
+        (StrongValType::Float, StrongValType::U8) => {
+            [vec![Instruction::I32TruncSatF32U], wrap_u8.to_vec()].concat()
+        }
+        (StrongValType::F64, StrongValType::U8) => {
+            [vec![Instruction::I32TruncSatF64U], wrap_u8.to_vec()].concat()
+        }
+        (_, StrongValType::U8) => wrap_u8.to_vec(),
+        (StrongValType::I64, StrongValType::Int | StrongValType::U32) => vec![Instruction::I32WrapI64],
+        (StrongValType::Float, StrongValType::Int) => vec![Instruction::I32TruncSatF32S],
+        (StrongValType::Float, StrongValType::U32) => vec![Instruction::I32TruncSatF32U],
+        (StrongValType::F64, StrongValType::Int) => vec![Instruction::I32TruncSatF64S],
+        (StrongValType::F64, StrongValType::U32) => vec![Instruction::I32TruncSatF64U],
+        // i32 to i32
+        (_, StrongValType::Int | StrongValType::U32) => vec![],
+        (StrongValType::Float, StrongValType::I64) => vec![Instruction::I64TruncSatF32S],
+        (StrongValType::F64, StrongValType::I64) => vec![Instruction::I64TruncSatF64S],
+        (_, StrongValType::I64) if unsigned => vec![Instruction::I64ExtendI32U],
+        (_, StrongValType::I64) => vec![Instruction::I64ExtendI32S],
+        (StrongValType::F64, StrongValType::Float) => vec![Instruction::F32DemoteF64],
+        (StrongValType::I64, StrongValType::Float) => vec![Instruction::F32ConvertI64S],
+        (_, StrongValType::Float) if unsigned => vec![Instruction::F32ConvertI32U],
+        (_, StrongValType::Float) => vec![Instruction::F32ConvertI32S],
+        (StrongValType::Float, StrongValType::F64) => vec![Instruction::F64PromoteF32],
+        (StrongValType::I64, StrongValType::F64) => vec![Instruction::F64ConvertI64S],
+        (_, StrongValType::F64) if unsigned => vec![Instruction::F64ConvertI32U],
+        (_, StrongValType::F64) => vec![Instruction::F64ConvertI32S],
+        _ => vec![],
+    }
+}
+
+/// The operator of a assignment operator. i.e. `+` for `+=`
+pub fn get_assign_operator(op: &str) -> Option<&'static str> {
+    match op {
+        token::PLUS_EQUALS => Some(token::PLUS),
+        token::MINUS_EQUALS => Some(token::MINUS),
+        token::ASTERISK_EQUALS => Some(token::ASTERISK),
+        token::SLASH_EQUALS => Some(token::SLASH),
+        token::MODULUS_EQUALS => Some(token::MODULUS),
+        token::BITWISE_AND_EQUALS => Some(token::BITWISE_AND),
+        token::BITWISE_OR_EQUALS => Some(token::BITWISE_OR),
+        token::BITWISE_XOR_EQUALS => Some(token::BITWISE_XOR),
+        token::SHIFT_LEFT_EQUALS => Some(token::SHIFT_LEFT),
+        token::SHIFT_RIGHT_EQUALS => Some(token::SHIFT_RIGHT),
+        token::UNSIGNED_SHIFT_RIGHT_EQUALS => Some(token::UNSIGNED_SHIFT_RIGHT),
+        _ => None,
+    }
+}
+
+/// Is this a comparison operator?
+pub fn is_comparison_operator(op: &str) -> bool {
+    matches!(
+        op,
+        token::EQ | token::NOT_EQ | token::LT | token::LT_OR_EQ | token::GT | token::GT_OR_EQ
+    )
+}
+
+/// The instruction for `left op right` when both sides are `val_type`.
+///
+/// None if the operator does not work on that type, i.e. `%` on floats.
+pub fn number_operation(op: &str, val_type: &StrongValType) -> Option<Instruction<'static>> {
+    let instruction = match val_type {
+        StrongValType::I64 => match op {
+            token::PLUS => Instruction::I64Add,
+            token::MINUS => Instruction::I64Sub,
+            token::ASTERISK => Instruction::I64Mul,
+            token::SLASH => Instruction::I64DivS,
+            token::MODULUS => Instruction::I64RemS,
+            token::EQ => Instruction::I64Eq,
+            token::NOT_EQ => Instruction::I64Ne,
+            token::LT => Instruction::I64LtS,
+            token::LT_OR_EQ => Instruction::I64LeS,
+            token::GT => Instruction::I64GtS,
+            token::GT_OR_EQ => Instruction::I64GeS,
+            token::BITWISE_AND => Instruction::I64And,
+            token::BITWISE_OR => Instruction::I64Or,
+            token::BITWISE_XOR => Instruction::I64Xor,
+            token::SHIFT_LEFT => Instruction::I64Shl,
+            token::SHIFT_RIGHT => Instruction::I64ShrS,
+            token::UNSIGNED_SHIFT_RIGHT => Instruction::I64ShrU,
+            _ => return None,
+        },
+        StrongValType::Float => match op {
+            token::PLUS => Instruction::F32Add,
+            token::MINUS => Instruction::F32Sub,
+            token::ASTERISK => Instruction::F32Mul,
+            token::SLASH => Instruction::F32Div,
+            token::EQ => Instruction::F32Eq,
+            token::NOT_EQ => Instruction::F32Ne,
+            token::LT => Instruction::F32Lt,
+            token::LT_OR_EQ => Instruction::F32Le,
+            token::GT => Instruction::F32Gt,
+            token::GT_OR_EQ => Instruction::F32Ge,
+            _ => return None,
+        },
+        StrongValType::F64 => match op {
+            token::PLUS => Instruction::F64Add,
+            token::MINUS => Instruction::F64Sub,
+            token::ASTERISK => Instruction::F64Mul,
+            token::SLASH => Instruction::F64Div,
+            token::EQ => Instruction::F64Eq,
+            token::NOT_EQ => Instruction::F64Ne,
+            token::LT => Instruction::F64Lt,
+            token::LT_OR_EQ => Instruction::F64Le,
+            token::GT => Instruction::F64Gt,
+            token::GT_OR_EQ => Instruction::F64Ge,
+            _ => return None,
+        },
+        // int, bool, u8 and u32
+        _ => {
+            let unsigned = is_unsigned(val_type);
+            match op {
+                token::PLUS => Instruction::I32Add,
+                token::MINUS => Instruction::I32Sub,
+                token::ASTERISK => Instruction::I32Mul,
+                token::SLASH if unsigned => Instruction::I32DivU,
+                token::SLASH => Instruction::I32DivS,
+                token::MODULUS if unsigned => Instruction::I32RemU,
+                token::MODULUS => Instruction::I32RemS,
+                token::EQ => Instruction::I32Eq,
+                token::NOT_EQ => Instruction::I32Ne,
+                token::LT if unsigned => Instruction::I32LtU,
+                token::LT => Instruction::I32LtS,
+                token::LT_OR_EQ if unsigned => Instruction::I32LeU,
+                token::LT_OR_EQ => Instruction::I32LeS,
+                token::GT if unsigned => Instruction::I32GtU,
+                token::GT => Instruction::I32GtS,
+                token::GT_OR_EQ if unsigned => Instruction::I32GeU,
+                token::GT_OR_EQ => Instruction::I32GeS,
+                token::BITWISE_AND => Instruction::I32And,
+                token::BITWISE_OR => Instruction::I32Or,
+                token::BITWISE_XOR => Instruction::I32Xor,
+                token::SHIFT_LEFT => Instruction::I32Shl,
+                token::SHIFT_RIGHT if unsigned => Instruction::I32ShrU,
+                token::SHIFT_RIGHT => Instruction::I32ShrS,
+                token::UNSIGNED_SHIFT_RIGHT => Instruction::I32ShrU,
+                _ => return None,
+            }
+        }
+    };
+
+    Some(instruction)
+}
+
+/// The number 1 as a `val_type`, used by `++` and `--`.
+pub fn number_one(val_type: &StrongValType) -> Instruction<'static> {
+    match val_type {
+        StrongValType::I64 => Instruction::I64Const(1),
+        StrongValType::Float => Instruction::F32Const(1.0),
+        StrongValType::F64 => Instruction::F64Const(1.0),
+        _ => Instruction::I32Const(1),
+    }
+}
+
+/// Parse a integer literal (without the `n` of a BigInt) into a i64. i.e. `0xff`, `1_000`
+pub fn parse_i64_literal(literal: &str) -> Option<i64> {
+    let literal = literal.replace('_', "");
+    let (radix, digits) = match literal.get(..2) {
+        Some("0x") | Some("0X") => (16, &literal[2..]),
+        Some("0b") | Some("0B") => (2, &literal[2..]),
+        Some("0o") | Some("0O") => (8, &literal[2..]),
+        _ => (10, literal.as_str()),
+    };
+
+    i64::from_str_radix(digits, radix).ok()
+}
@@ -34,6 +34,32 @@ pub struct EasyNativeVar {
     pub is_mut: bool
 }
 
+/// Context for a native struct.
+///
+/// Structs live in linear memory, the fields are at `ptr + offset`.
+#[derive(Debug, Clone)]
+pub struct EasyNativeStruct {
+    /// Name of the struct (namespaced)
+    pub name: String,
+    /// The fields in the order of the constructor
+    pub fields: Vec<EasyNativeField>,
+    /// The size of the struct in bytes (padding included)
+    pub size: u32,
+    /// The alignment of the struct, the biggest alignment of its fields.
+    pub align: u32,
+}
+
+/// A field of a native struct.
+#[derive(Debug, Clone)]
+pub struct EasyNativeField {
+    /// Name of the field
+    pub name: String,
+    /// The type of the field
+    pub val_type: StrongValType,
+    /// The offset of the field from the struct pointer.
+    pub offset: u32,
+}
+
 /// Context for a Block.
 pub struct EasyNativeBlock {
     /// The blocks idx.
@@ -41,7 +67,24 @@ pub struct EasyNativeBlock {
     /// The block type as a `ValType`
     pub block_type: Option<ValType>,
     /// The `StrongValType` equivalent of block_type
-    pub strong_block_type: StrongValType
+    pub strong_block_type: StrongValType,
+    /// What the block is used for.
+    pub kind: EasyNativeBlockKind,
+    /// The label of the loop this block belongs to (if any)
+    pub label: Option<String>,
+}
+
+/// What a block is used for, `break` and `continue` use it to find their depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EasyNativeBlockKind {
+    /// A if/else block.
+    If,
+    /// The block around a loop, `break` jumps to the end of it.
+    Break,
+    /// The wasm `loop` itself.
+    Loop,
+    /// The block around the body of a loop, `continue` jumps to the end of it.
+    Continue,
 }
 
 /// A signature for a function.
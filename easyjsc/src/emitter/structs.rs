@@ -0,0 +1,205 @@
+use wasm_encoder::{BlockType, Function, Instruction, MemArg, ValType};
+
+use crate::{
+    emitter::{
+        builtins::STRUCT_ALLOC_NAME,
+        signatures::{EasyNativeFN, EasyNativeField, EasyNativeStruct, FunctionSignature},
+    },
+    new_function_with_instructions,
+    parser::ast::Expression,
+    typechecker::StrongValType,
+};
+
+/// Where the heap starts, the first 8 bytes of memory hold the heap pointer.
+const HEAP_START: i32 = 8;
+
+/// The size (and alignment) of a type in bytes.
+pub fn size_of_type(val_type: &StrongValType) -> u32 {
+    match val_type {
+        StrongValType::I64 | StrongValType::F64 => 8,
+        StrongValType::U8 => 1,
+        // int, float, bool, u32 and pointers to strings, arrays and structs are all 32 bits.
+        _ => 4,
+    }
+}
+
+/// Layout the fields of a struct in memory.
+///
+/// Each field is aligned to its own size and the struct size is padded to the biggest alignment,
+/// just like a C struct.
+pub fn create_struct_layout(name: &str, fields: &Vec<(String, StrongValType)>) -> EasyNativeStruct {
+    let mut layout_fields = vec![];
+    let mut offset = 0;
+    let mut align = 1;
+
+    for (field_name, val_type) in fields {
+        let size = size_of_type(val_type);
+        offset = align_to(offset, size);
+        layout_fields.push(EasyNativeField {
+            name: field_name.to_owned(),
+            val_type: val_type.to_owned(),
+            offset,
+        });
+        offset += size;
+        align = align.max(size);
+    }
+
+    EasyNativeStruct {
+        name: name.to_owned(),
+        fields: layout_fields,
+        size: align_to(offset, align),
+        align,
+    }
+}
+
+/// Round `value` up to a multiple of `align`.
+fn align_to(value: u32, align: u32) -> u32 {
+    value.div_ceil(align) * align
+}
+
+/// The MemArg of a field.
+fn field_mem_arg(field: &EasyNativeField) -> MemArg {
+    MemArg {
+        offset: field.offset as u64,
+        align: size_of_type(&field.val_type).trailing_zeros(),
+        memory_index: 0,
+    }
+}
+
+/// Load a field. The struct pointer must be on the stack.
+pub fn load_field(field: &EasyNativeField) -> Instruction<'static> {
+    match field.val_type {
+        StrongValType::Float => Instruction::F32Load(field_mem_arg(field)),
+        StrongValType::I64 => Instruction::I64Load(field_mem_arg(field)),
+        StrongValType::F64 => Instruction::F64Load(field_mem_arg(field)),
+        StrongValType::U8 => Instruction::I32Load8U(field_mem_arg(field)),
+        _ => Instruction::I32Load(field_mem_arg(field)),
+    }
+}
+
+/// Store a field. The struct pointer and the value must be on the stack.
+pub fn store_field(field: &EasyNativeField) -> Instruction<'static> {
+    match field.val_type {
+        StrongValType::Float => Instruction::F32Store(field_mem_arg(field)),
+        StrongValType::I64 => Instruction::I64Store(field_mem_arg(field)),
+        StrongValType::F64 => Instruction::F64Store(field_mem_arg(field)),
+        StrongValType::U8 => Instruction::I32Store8(field_mem_arg(field)),
+        _ => Instruction::I32Store(field_mem_arg(field)),
+    }
+}
+
+/// Native builtin function to allocate a struct.
+///
+/// `size(0): int` The size of the struct.
+///
+/// returns: `ptr: int` The pointer to the struct.
+///
+/// The heap pointer is kept at the start of memory, structs are 8 byte aligned.
+/// Memory grows when the struct does not fit.
+pub fn native_struct_alloc(idx: u32) -> EasyNativeFN {
+    // Locals are: ptr(1), end(2)
+    let locals = vec![(2, ValType::I32)];
+    let heap_mem_arg = MemArg {
+        offset: 0,
+        align: 2,
+        memory_index: 0,
+    };
+
+    let instructions = vec![
+        // ptr = heap pointer, or the start of the heap if nothing was allocated yet.
+        Instruction::I32Const(0),
+        Instruction::I32Load(heap_mem_arg),
+        Instruction::LocalTee(1),
+        Instruction::I32Eqz,
+        Instruction::If(BlockType::Empty),
+        Instruction::I32Const(HEAP_START),
+        Instruction::LocalSet(1),
+        Instruction::End,
+        // end = ptr + size (rounded up to 8)
+        Instruction::LocalGet(1),
+        Instruction::LocalGet(0),
+        Instruction::I32Const(7),
+        Instruction::I32Add,
+        Instruction::I32Const(-8),
+        Instruction::I32And,
+        Instruction::I32Add,
+        Instruction::LocalSet(2),
+        // grow memory if end is out of bounds.
+        Instruction::LocalGet(2),
+        Instruction::MemorySize(0),
+        Instruction::I32Const(16),
+        Instruction::I32Shl,
+        Instruction::I32GtU,
+        Instruction::If(BlockType::Empty),
+        Instruction::LocalGet(2),
+        Instruction::I32Const(16),
+        Instruction::I32ShrU,
+        Instruction::I32Const(1),
+        Instruction::I32Add,
+        Instruction::MemorySize(0),
+        Instruction::I32Sub,
+        Instruction::MemoryGrow(0),
+        Instruction::Drop,
+        Instruction::End,
+        // save the new heap pointer
+        Instruction::I32Const(0),
+        Instruction::LocalGet(2),
+        Instruction::I32Store(heap_mem_arg),
+        Instruction::LocalGet(1),
+        Instruction::End,
+    ];
+
+    EasyNativeFN {
+        signature: FunctionSignature {
+            params: vec![ValType::I32],
+            results: vec![ValType::I32],
+            param_strong: vec![StrongValType::Int],
+            results_strong: vec![StrongValType::Int],
+        },
+        function: new_function_with_instructions!(locals, instructions),
+        name: STRUCT_ALLOC_NAME.to_string(),
+        idx,
+        is_public: true,
+    }
+}
+
+/// Turn a struct method into a function named `Struct_method`.
+///
+/// `self` (which is parsed as `this`) becomes a paramater typed as the struct.
+pub fn struct_method_to_function(struct_name: &str, method: &Expression) -> Option<Expression> {
+    let Expression::FunctionLiteral(tk, name, params, return_type, body, type_params, attributes) =
+        method
+    else {
+        return None;
+    };
+    let Expression::Identifier(name_tk, method_name) = name.as_ref() else {
+        return None;
+    };
+
+    let params = params
+        .iter()
+        .map(|param| match param {
+            Expression::Identifier(param_tk, param_name) if param_name == "this" => {
+                Expression::IdentifierWithType(
+                    param_tk.to_owned(),
+                    param_name.to_owned(),
+                    Box::new(Expression::Type(param_tk.to_owned(), struct_name.to_owned())),
+                )
+            }
+            _ => param.to_owned(),
+        })
+        .collect::<Vec<Expression>>();
+
+    Some(Expression::FunctionLiteral(
+        tk.to_owned(),
+        Box::new(Expression::Identifier(
+            name_tk.to_owned(),
+            format!("{}_{}", struct_name, method_name),
+        )),
+        Box::new(params),
+        return_type.to_owned(),
+        body.to_owned(),
+        type_params.to_owned(),
+        attributes.to_owned(),
+    ))
+}
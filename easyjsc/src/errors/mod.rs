@@ -1,9 +1,13 @@
-use crate::{lexer::token::Token, parser::ast::Expression};
+use crate::{compiler::cfg::TARGETS, lexer::token::Token, parser::ast::Expression};
 
 fn make_error(token: &Token, error_msg: &str) -> String {
     format!("File: {} at line: {} and col: {}. ERROR {}. Token details: type: {}, literal: {}", token.file_name, token.line_number, token.col_number, error_msg, token.typ, token.literal)
 }
 
+fn make_warning(token: &Token, warning_msg: &str) -> String {
+    format!("File: {} at line: {} and col: {}. WARNING {}. Token details: type: {}, literal: {}", token.file_name, token.line_number, token.col_number, warning_msg, token.typ, token.literal)
+}
+
 pub fn make_native_error(token: &Token, error_msg: &str) -> String {
     make_error(token, format!("Native: {}", error_msg).as_str())
 }
@@ -64,6 +68,46 @@ pub fn native_if_expression_must_go_within_functions(token: &Token) -> String {
     make_native_error(token, "If expression must go within a function")
 }
 
+/// ERROR Native: loops must go within functions.
+pub fn native_loop_must_go_within_functions(token: &Token) -> String {
+    make_native_error(token, "Loops must go within a function")
+}
+
+/// ERROR Native: {break|continue} must go within a loop
+pub fn native_jump_outside_of_loop(token: &Token) -> String {
+    make_native_error(token, format!("{} must go within a loop", token.literal).as_str())
+}
+
+/// ERROR Native: There is no loop labeled {label}
+pub fn native_unknown_loop_label(token: &Token, label: &str) -> String {
+    make_native_error(token, format!("There is no loop labeled {}", label).as_str())
+}
+
+/// ERROR Native: Field {field} of a native struct needs a type
+pub fn native_struct_field_needs_type(token: &Token, field: &str) -> String {
+    make_native_error(token, format!("Field {} of a native struct needs a type", field).as_str())
+}
+
+/// ERROR Native: {name} has {expected} fields but got {got}
+pub fn native_struct_wrong_number_of_fields(token: &Token, name: &str, expected: usize, got: usize) -> String {
+    make_native_error(token, format!("{} has {} fields but got {}", name, expected, got).as_str())
+}
+
+/// ERROR Native: {name} has no field {field}
+pub fn native_struct_has_no_field(token: &Token, name: &str, field: &str) -> String {
+    make_native_error(token, format!("{} has no field {}", name, field).as_str())
+}
+
+/// ERROR Native: Can not convert {from} to {to} without `as`
+pub fn native_conversion_needs_cast(token: &Token, from: &str, to: &str) -> String {
+    make_native_error(token, format!("Can not convert {} to {} without `as`", from, to).as_str())
+}
+
+/// ERROR Native: Can not cast {from} to {to}
+pub fn native_unsupported_cast(token: &Token, from: &str, to: &str) -> String {
+    make_native_error(token, format!("Can not cast {} to {}", from, to).as_str())
+}
+
 /// ERROR Native: Unsupported expression: {expression}
 pub fn native_unsupported_expression(expression: &Expression) -> String {
     make_native_error(expression.get_token(), format!("Unsupported expression: {:#?}", expression).as_str())
@@ -79,7 +123,137 @@ pub fn native_return_value_does_not_match_function(token: &Token) -> String {
     make_native_error(token, "return value does not match function return type")
 }
 
+/// ERROR Native: Integer {literal} does not fit in a int
+pub fn native_integer_out_of_range(token: &Token) -> String {
+    make_native_error(token, format!("Integer {} does not fit in a int", token.literal).as_str())
+}
+
 /// ERROR Native: Unsupported expression in array
 pub fn native_unsupported_expression_in_array(expression: &Expression) -> String {
     make_native_error(expression.get_token(), format!("Unsupported expression in array: {:#?}", expression).as_str())
-}
\ No newline at end of file
+}
+pub fn make_call_error(token: &Token, error_msg: &str) -> String {
+    make_error(token, format!("Call: {}", error_msg).as_str())
+}
+
+/// ERROR Call: {fn_name} has no paramater named {name}
+pub fn call_unknown_named_argument(token: &Token, fn_name: &str, name: &str) -> String {
+    make_call_error(token, format!("{} has no paramater named {}", fn_name, name).as_str())
+}
+
+/// ERROR Call: {name} is passed to {fn_name} more than once
+pub fn call_duplicate_argument(token: &Token, fn_name: &str, name: &str) -> String {
+    make_call_error(token, format!("{} is passed to {} more than once", name, fn_name).as_str())
+}
+
+/// ERROR Call: Positional arguments can not come after named arguments
+pub fn call_positional_after_named(token: &Token) -> String {
+    make_call_error(token, "Positional arguments can not come after named arguments")
+}
+
+pub fn make_comptime_error(token: &Token, error_msg: &str) -> String {
+    make_error(token, format!("Comptime: {}", error_msg).as_str())
+}
+
+/// ERROR Comptime: Could not evaluate block: {message}
+pub fn comptime_evaluation_failed(token: &Token, message: &str) -> String {
+    make_comptime_error(token, format!("Could not evaluate block: {}", message).as_str())
+}
+
+pub fn make_macro_error(token: &Token, error_msg: &str) -> String {
+    make_error(token, format!("Macro: {}", error_msg).as_str())
+}
+
+/// ERROR Macro: {name} is not defined
+pub fn macro_not_found(token: &Token, name: &str) -> String {
+    make_macro_error(token, format!("{} is not defined", name).as_str())
+}
+
+/// ERROR Macro: {name} is private, declare it with `pub macro` to use it outside of its module
+pub fn macro_is_private(token: &Token, name: &str) -> String {
+    make_macro_error(
+        token,
+        format!("{} is private, declare it with `pub macro` to use it outside of its module", name).as_str(),
+    )
+}
+
+/// ERROR Macro: Proc macro {name} failed: {message}
+pub fn proc_macro_failed(token: &Token, name: &str, message: &str) -> String {
+    make_macro_error(token, format!("Proc macro {} failed: {}", name, message).as_str())
+}
+
+/// ERROR Macro: Proc macro {name} did not return a valid AST: {message}
+pub fn proc_macro_invalid_ast(token: &Token, name: &str, message: &str) -> String {
+    make_macro_error(token, format!("Proc macro {} did not return a valid AST: {}", name, message).as_str())
+}
+
+pub fn make_attribute_error(token: &Token, error_msg: &str) -> String {
+    make_error(token, format!("Attribute: {}", error_msg).as_str())
+}
+
+/// ERROR Attribute: Decorators can only be put on pub methods, {name} is private
+pub fn attribute_on_private_method(token: &Token, name: &str) -> String {
+    make_attribute_error(token, format!("Decorators can only be put on pub methods, {} is private", name).as_str())
+}
+
+/// WARNING Deprecated: {name} is deprecated. {message}
+pub fn deprecated_call(token: &Token, name: &str, message: &str) -> String {
+    if message.is_empty() {
+        make_warning(token, format!("Deprecated: {} is deprecated", name).as_str())
+    } else {
+        make_warning(token, format!("Deprecated: {} is deprecated. {}", name, message).as_str())
+    }
+}
+
+pub fn make_cfg_error(token: &Token, error_msg: &str) -> String {
+    make_error(token, format!("Cfg: {}", error_msg).as_str())
+}
+
+/// ERROR Cfg: Unsupported condition, expected target == "..." or feature = "..."
+pub fn cfg_unsupported_condition(token: &Token) -> String {
+    make_cfg_error(token, "Unsupported condition, expected target == \"...\" or feature = \"...\"")
+}
+
+/// ERROR Cfg: Unknown target {target}
+pub fn cfg_unknown_target(token: &Token, target: &str) -> String {
+    make_cfg_error(token, format!("Unknown target {}, expected one of {}", target, TARGETS.join(", ")).as_str())
+}
+
+pub fn make_type_error(token: &Token, error_msg: &str) -> String {
+    make_error(token, format!("Type: {}", error_msg).as_str())
+}
+
+/// ERROR Type: Expected {expected} but got {got}
+pub fn type_mismatch(token: &Token, expected: &str, got: &str) -> String {
+    make_type_error(token, format!("Expected {} but got {}", expected, got).as_str())
+}
+
+/// ERROR Type: Argument {position} of {fn_name} expected {expected} but got {got}
+pub fn type_argument_mismatch(token: &Token, fn_name: &str, position: usize, expected: &str, got: &str) -> String {
+    make_type_error(token, format!("Argument {} of {} expected {} but got {}", position, fn_name, expected, got).as_str())
+}
+
+/// ERROR Type: {fn_name} expected {expected} arguments but got {got}
+pub fn type_wrong_number_of_arguments(token: &Token, fn_name: &str, expected: &str, got: usize) -> String {
+    make_type_error(token, format!("{} expected {} arguments but got {}", fn_name, expected, got).as_str())
+}
+
+/// ERROR Type: Unsupported operation: {left} {operation} {right}
+pub fn type_unsupported_operation(token: &Token, left: &str, operation: &str, right: &str) -> String {
+    make_type_error(token, format!("Unsupported operation: {} {} {}", left, operation, right).as_str())
+}
+
+/// ERROR Type: Struct {struct_name} has no field or method {name}
+pub fn type_unknown_struct_member(token: &Token, struct_name: &str, name: &str) -> String {
+    make_type_error(token, format!("Struct {} has no field or method {}", struct_name, name).as_str())
+}
+
+/// ERROR Type: {name} may be null, check it before using it
+pub fn type_possibly_null(token: &Token, name: &str) -> String {
+    make_type_error(token, format!("{} may be null, check it before using it", name).as_str())
+}
+
+/// ERROR Type: Can not check for {name} with `is`, structs and enums are plain objects
+pub fn type_unsupported_is_check(token: &Token, name: &str) -> String {
+    make_type_error(token, format!("Can not check for {} with `is`, structs and enums are plain objects", name).as_str())
+}
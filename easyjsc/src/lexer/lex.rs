@@ -10,10 +10,16 @@ pub struct Lex {
     pub current_line: i32,
     /// The exact column number.
     pub current_col: i32,
+    /// The line the token being read starts on.
+    token_line: i32,
+    /// The column the token being read starts on.
+    token_col: i32,
     /// The file being parsed.
     pub current_file: String,
     /// A vector of chars to not .chars() every read_char,
     input_chars: Vec<char>,
+    /// Tokens that were already read, returned before any input. Stored in reverse.
+    queued: Vec<token::Token>,
 }
 
 /// Allowed chars in ident (other than letters of course)
@@ -28,9 +34,12 @@ impl Lex {
             read_position: 0,
             current_char: ' ', // Initialize with null character
             current_line: 1,
-            current_col: 1,
+            current_col: 0,
+            token_line: 1,
+            token_col: 1,
             current_file: String::new(),
             input_chars: input.chars().collect(),
+            queued: vec![],
         }
     }
 
@@ -42,6 +51,24 @@ impl Lex {
         l
     }
 
+    /// Create a lex instance that returns already read tokens, i.e. the expressions of a template string.
+    pub fn from_tokens(mut tokens: Vec<token::Token>, file: String) -> Self {
+        let mut l = Lex::new_with_file(String::new(), file);
+        if let Some(last) = tokens.last() {
+            l.current_line = last.line_number;
+            l.current_col = last.col_number;
+        }
+        tokens.reverse();
+        l.queued = tokens;
+
+        l
+    }
+
+    /// Put tokens that were read back, so `next_token` returns them again.
+    pub fn unread_tokens(&mut self, tokens: Vec<token::Token>) {
+        self.queued.extend(tokens.into_iter().rev());
+    }
+
     /// Create a String object with the current and peek char.
     fn cc_pp(&mut self) -> String {
         let s = format!("{}{}", self.current_char, self.peek_char());
@@ -73,6 +100,16 @@ impl Lex {
         self.read_position += 1; // Move to the next character
     }
 
+    /// Go back to the previous character. Used after reading past the end of a token.
+    ///
+    /// The previous character is always part of the token, so it is never a new line.
+    fn unread_char(&mut self) {
+        self.read_position = self.position;
+        self.position -= 1;
+        self.current_char = self.input_chars[self.position];
+        self.current_col -= 1;
+    }
+
     /// Skip the whitespace in input.
     fn skip_whitespace(&mut self) {
         while (self.current_char == ' '
@@ -94,79 +131,186 @@ impl Lex {
         }
     }
 
-    /// Read a runes contents seperately
-    fn read_rune(&mut self) -> String {
-        if self.current_char != '{' {
-            "".to_string()
+    /// peek `n` characters past the current one without changing position
+    fn peek_nth_char(&self, n: usize) -> char {
+        let position = self.position + n;
+        if position >= self.input_chars.len() {
+            '\0'
         } else {
-            let mut rune = String::new();
-            let mut brace_count = 1;
-            while brace_count > 0 && !self.is_eof() {
-                if self.current_char != '\\' && self.peek_char() == '{' {
-                    brace_count += 1;
-                }
+            self.input_chars[position]
+        }
+    }
 
-                if self.current_char == '\\' && self.peek_char() == '}' {
-                    rune.push(self.current_char); // consume \
-                    self.read_char(); // go to }
-                    rune.push(self.current_char); // conusme }
+    /// Read the longest operator in `operators` that starts at the current character.
+    /// `operators` must be ordered longest first, ending with the single character.
+    /// Operator tokens use their literal as the token type.
+    fn read_operator(&mut self, operators: &[&str]) -> token::Token {
+        for operator in operators {
+            let matches = operator
+                .chars()
+                .enumerate()
+                .all(|(i, c)| self.peek_nth_char(i) == c);
+
+            if matches {
+                for _ in 1..operator.len() {
                     self.read_char();
                 }
+                return self.create_new_token(operator, operator);
+            }
+        }
 
-                if self.current_char != '\\' && self.peek_char() == '}' {
-                    brace_count -= 1;
-                }
+        self.create_new_token(token::ILLEGAL, &self.current_char_str())
+    }
 
-                rune.push(self.current_char);
-                self.read_char();
-            }
+    /// Read the tokens of a $name or ${expression} within a string.
+    /// The lexer is left on the first character after the expression.
+    fn read_template_expression(&mut self) -> Vec<token::Token> {
+        self.read_char(); // the $
 
-            rune
+        // $name is just the identifier
+        if self.current_char != '{' {
+            let ident = self.next_token();
+            // next_token leaves us past the identifier
+            return vec![ident];
+        }
+
+        self.read_char(); // the {
+
+        let mut tokens = vec![];
+        let mut depth = 0;
+        loop {
+            let tk = self.next_token();
+            match tk.typ.as_str() {
+                token::EOF => break,
+                token::EOL => continue,
+                token::L_BRACE => depth += 1,
+                token::R_BRACE => {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            tokens.push(tk);
         }
+
+        tokens
     }
 
-    /// Read a string from '' to ""
-    fn read_string(&mut self, cs: char) -> String {
+    /// Read a string from '' to "" into its text and $ expressions.
+    ///
+    /// The lexer is left on the closing quote.
+    fn read_string(&mut self, cs: char) -> Vec<token::TemplatePart> {
+        // reading the expressions moves the token start.
+        let (token_line, token_col) = (self.token_line, self.token_col);
+
         // Go to next char to not be stuck in the "
         self.read_char();
 
-        let mut result = String::new();
-        let mut run = 0;
+        let mut parts = vec![];
+        let mut text = String::new();
 
         // go until end of statement.
         while self.current_char != cs && !self.is_eof() {
-            // Check if is a rune
-            if (self.current_char != '\\' && self.peek_char() == '$') || (run == 0 && self.current_char == '$') {
-                if run > 0 {
-                    result.push(self.current_char); // consume whatever this char is
-                    println!("{}", self.current_char);
+            // escapes are kept as is, i.e. \n \" \$ or \u{1F600}
+            if self.current_char == '\\' {
+                text.push(self.current_char);
+                self.read_char();
+                if !self.is_eof() {
+                    text.push(self.current_char);
                     self.read_char();
                 }
-                result.push(self.current_char); // consume $
-                self.read_char();
-
-                let rune = self.read_rune();
-                result.push_str(&rune);
                 continue;
             }
 
-            // check for an escaped quote.
-            if self.current_char == '\\' && (self.peek_char() == '"' || self.peek_char() == '\'') {
-                result.push(self.current_char);
-                // move pass the backslash
-                self.read_char();
+            let next = self.peek_char();
+            if self.current_char == '$'
+                && (next == '{' || next.is_alphabetic() || next == '_' || next == '#')
+            {
+                if !text.is_empty() {
+                    parts.push(token::TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                let tokens = self.read_template_expression();
+                parts.push(token::TemplatePart::Expression(tokens));
+                continue;
             }
 
             // add the current character to the result string
-            result.push(self.current_char);
+            text.push(self.current_char);
             self.read_char();
+        }
+
+        if !text.is_empty() || parts.is_empty() {
+            parts.push(token::TemplatePart::Text(text));
+        }
+
+        self.token_line = token_line;
+        self.token_col = token_col;
+
+        parts
+    }
+
+    /// Create a STRING token, or a TEMPLATE token if the string has any $ expressions.
+    fn create_string_token(&self, parts: Vec<token::TemplatePart>, start: usize) -> token::Token {
+        if let [token::TemplatePart::Text(text)] = parts.as_slice() {
+            return self.create_new_token(token::STRING, text);
+        }
+
+        let literal: String = self.input_chars[start + 1..self.position].iter().collect();
+        let mut tk = self.create_new_token(token::TEMPLATE, &literal);
+        tk.parts = parts;
+        tk
+    }
+
+    /// Read a raw string, i.e. r"C:\path\$file". Nothing inside a raw string is special.
+    ///
+    /// The result is escaped so it can be used like any other string.
+    fn read_raw_string(&mut self) -> String {
+        let cs = self.peek_char();
+        self.read_char(); // the r
+        self.read_char(); // the quote
 
-            run += 1;
+        let mut result = String::new();
+        while self.current_char != cs && !self.is_eof() {
+            match self.current_char {
+                '\\' | '$' | '"' | '\'' | '`' => {
+                    result.push('\\');
+                    result.push(self.current_char);
+                }
+                _ => result.push(self.current_char),
+            }
+            self.read_char();
         }
 
         result
     }
 
+    /// Read a block comment, /* ... */. Block comments can be nested.
+    fn read_block_comment(&mut self) -> String {
+        self.read_char(); // the /
+        self.read_char(); // the *
+
+        let mut comment = String::new();
+        let mut depth = 1;
+        while !self.is_eof() {
+            if self.current_char == '/' && self.peek_char() == '*' {
+                depth += 1;
+            } else if self.current_char == '*' && self.peek_char() == '/' {
+                depth -= 1;
+                if depth == 0 {
+                    self.read_char(); // end on the /
+                    break;
+                }
+            }
+
+            comment.push(self.current_char);
+            self.read_char();
+        }
+
+        comment
+    }
+
     /// read the identifier
     fn read_identifier(&mut self) -> String {
         let mut ident = String::new();
@@ -178,31 +322,87 @@ impl Lex {
             ident.push(self.current_char);
             self.read_char();
         }
-        self.read_position = self.position;
+        self.unread_char();
         ident
     }
 
-    /// Get the full number.
-    fn read_number(&mut self) -> String {
+    /// Get the full number and its token type.
+    ///
+    /// Supports `10`, `1.5`, `0xff`, `0b1010`, `0o17`, `1_000_000`, `1e-9` and `10n`.
+    /// Numbers like `0o19` or `1.5n` are returned whole as ILLEGAL.
+    fn read_number(&mut self) -> (String, &'static str) {
         let mut number = String::new();
 
-        while (self.current_char.is_numeric() || self.current_char == '.') && !self.is_eof() {
-            if self.current_char == '.' {
-                if self.peek_char().is_numeric() {
-                    number.push(self.current_char);
+        let radix = if self.current_char == '0' {
+            match self.peek_char() {
+                'x' | 'X' => 16,
+                'b' | 'B' => 2,
+                'o' | 'O' => 8,
+                _ => 10,
+            }
+        } else {
+            10
+        };
+        if radix != 10 {
+            // 0x 0b or 0o
+            number.push(self.current_char);
+            self.read_char();
+            number.push(self.current_char);
+            self.read_char();
+        }
+
+        let mut is_float = false;
+        let mut has_exponent = false;
+        let mut is_illegal = false;
+        while !self.is_eof() {
+            let c = self.current_char;
+            let next = self.peek_char();
+
+            // bad separators and exponents are kept in the number so the parser can report them.
+            if c.is_digit(radix) || c == '_' {
+                number.push(c);
+            } else if c.is_ascii_digit() {
+                // a digit out of range for the radix, i.e. the 9 in 0o19
+                is_illegal = true;
+                number.push(c);
+            } else if radix == 10 && c == '.' && !is_float && !has_exponent && next.is_ascii_digit()
+            {
+                is_float = true;
+                number.push(c);
+            } else if radix == 10 && (c == 'e' || c == 'E') && !has_exponent {
+                has_exponent = true;
+                number.push(c);
+                if next == '-' || next == '+' {
                     self.read_char();
-                } else {
-                    break;
+                    number.push(next);
                 }
+            } else {
+                break;
             }
+            self.read_char();
+        }
 
-            number.push(self.current_char);
+        let mut token_type = if is_float || has_exponent {
+            token::FLOAT
+        } else {
+            token::INT
+        };
+
+        if self.current_char == 'n' {
+            // a BigInt has to be a whole number, i.e. not 1.5n or 1e3n
+            is_illegal = is_illegal || token_type != token::INT;
+            number.push('n');
             self.read_char();
+            token_type = token::BIGINT;
         }
 
-        self.read_position = self.position;
+        if is_illegal {
+            token_type = token::ILLEGAL;
+        }
 
-        number
+        self.unread_char();
+
+        (number, token_type)
     }
 
     /// Read the javascript token.
@@ -241,8 +441,16 @@ impl Lex {
 
     /// Read the next token via the lexer.
     pub fn next_token(&mut self) -> token::Token {
+        if let Some(tk) = self.queued.pop() {
+            return tk;
+        }
+
         self.skip_whitespace();
 
+        // tokens are positioned where they start.
+        self.token_line = self.current_line;
+        self.token_col = self.current_col;
+
         if self.is_eof() {
             return self.create_new_token(token::EOF, &self.current_char_str());
         }
@@ -267,6 +475,10 @@ impl Lex {
                             token::SPREAD,
                             format!("{}{}", ccpp, self.current_char).as_str(),
                         )
+                    } else if self.peek_char() == '=' {
+                        // a inclusive range
+                        self.read_char();
+                        self.create_new_token(token::DOTDOT_EQUALS, token::DOTDOT_EQUALS)
                     } else {
                         self.create_new_token(token::DOTDOT, &ccpp)
                     }
@@ -274,30 +486,14 @@ impl Lex {
                     self.create_new_token(token::DOT, &self.current_char_str())
                 }
             }
-            '+' => {
-                if self.peek_char() == '=' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::PLUS_EQUALS, &ccpp)
-                } else {
-                    self.create_new_token(token::PLUS, &self.current_char_str())
-                }
-            }
-            '-' => {
-                if self.peek_char() == '=' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::MINUS_EQUALS, &ccpp)
-                } else {
-                    self.create_new_token(token::MINUS, &self.current_char_str())
-                }
-            }
-            '*' => {
-                if self.peek_char() == '=' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::ASTERISK_EQUALS, &ccpp)
-                } else {
-                    self.create_new_token(token::ASTERISK, &self.current_char_str())
-                }
-            }
+            '+' => self.read_operator(&[token::INCREMENT, token::PLUS_EQUALS, token::PLUS]),
+            '-' => self.read_operator(&[token::DECREMENT, token::MINUS_EQUALS, token::MINUS]),
+            '*' => self.read_operator(&[
+                token::EXPONENT_EQUALS,
+                token::EXPONENT,
+                token::ASTERISK_EQUALS,
+                token::ASTERISK,
+            ]),
             '{' => self.create_new_token(token::L_BRACE, &self.current_char_str()),
             '}' => self.create_new_token(token::R_BRACE, &self.current_char_str()),
             '(' => self.create_new_token(token::L_PAREN, &self.current_char_str()),
@@ -307,7 +503,9 @@ impl Lex {
             '\n' => self.create_new_token(token::EOL, &self.current_char_str()),
             '[' => self.create_new_token(token::L_BRACKET, &self.current_char_str()),
             ']' => self.create_new_token(token::R_BRACKET, &self.current_char_str()),
-            '%' => self.create_new_token(token::MODULUS, &self.current_char_str()),
+            '%' => self.read_operator(&[token::MODULUS_EQUALS, token::MODULUS]),
+            '^' => self.read_operator(&[token::BITWISE_XOR_EQUALS, token::BITWISE_XOR]),
+            '~' => self.create_new_token(token::BITWISE_NOT, &self.current_char_str()),
             '!' => {
                 if self.peek_char() == '=' {
                     let ccpp = self.cc_pp();
@@ -316,22 +514,20 @@ impl Lex {
                     self.create_new_token(token::BANG, &self.current_char_str())
                 }
             }
-            '>' => {
-                if self.peek_char() == '=' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::GT_OR_EQ, &ccpp)
-                } else {
-                    self.create_new_token(token::GT, &self.current_char_str())
-                }
-            }
-            '<' => {
-                if self.peek_char() == '=' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::LT_OR_EQ, &ccpp)
-                } else {
-                    self.create_new_token(token::LT, &self.current_char_str())
-                }
-            }
+            '>' => self.read_operator(&[
+                token::UNSIGNED_SHIFT_RIGHT_EQUALS,
+                token::UNSIGNED_SHIFT_RIGHT,
+                token::SHIFT_RIGHT_EQUALS,
+                token::SHIFT_RIGHT,
+                token::GT_OR_EQ,
+                token::GT,
+            ]),
+            '<' => self.read_operator(&[
+                token::SHIFT_LEFT_EQUALS,
+                token::SHIFT_LEFT,
+                token::LT_OR_EQ,
+                token::LT,
+            ]),
             ':' => {
                 let next_char = self.peek_char();
                 let token_type = token::lookup_colon_special(
@@ -372,6 +568,9 @@ impl Lex {
                     }
                     // }
                     self.create_new_token(token_type, &res)
+                } else if self.peek_char() == '*' {
+                    let comment = self.read_block_comment();
+                    self.create_new_token(token::COMMENT, &comment)
                 } else if self.peek_char() == '=' {
                     let ccpp = self.cc_pp();
                     self.create_new_token(token::SLASH_EQUALS, &ccpp)
@@ -379,43 +578,41 @@ impl Lex {
                     self.create_new_token(token::SLASH, &self.current_char_str())
                 }
             }
-            '\"' => {
-                let string = self.read_string('\"');
-                self.create_new_token(token::STRING, &string)
-            }
-            '\'' => {
-                let string = self.read_string('\'');
-                self.create_new_token(token::STRING, &string)
-            }
-            '|' => {
-                if self.peek_char() == '|' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::OR_SYMBOL, &ccpp)
-                } else {
-                    self.create_new_token(token::BITWISE_OR, &self.current_char_str())
-                }
-            }
-            '&' => {
-                if self.peek_char() == '&' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::AND_SYMBOL, &ccpp)
-                } else {
-                    self.create_new_token(token::BITWISE_AND, &self.current_char_str())
-                }
-            }
-            '?' => {
-                if self.peek_char() == '?' {
-                    let ccpp = self.cc_pp();
-                    self.create_new_token(token::DOUBLE_QUESTION_MARK, &ccpp)
-                } else {
-                    self.create_new_token(token::QUESTION_MARK, &self.current_char_str())
-                }
+            '\"' | '\'' => {
+                let start = self.position;
+                let parts = self.read_string(self.current_char);
+                self.create_string_token(parts, start)
             }
+            '|' => self.read_operator(&[
+                token::OR_EQUALS,
+                token::OR_SYMBOL,
+                token::BITWISE_OR_EQUALS,
+                token::PIPE,
+                token::BITWISE_OR,
+            ]),
+            '&' => self.read_operator(&[
+                token::AND_EQUALS,
+                token::AND_SYMBOL,
+                token::BITWISE_AND_EQUALS,
+                token::BITWISE_AND,
+            ]),
+            '?' => self.read_operator(&[
+                token::DOUBLE_QUESTION_MARK_EQUALS,
+                token::DOUBLE_QUESTION_MARK,
+                token::QUESTION_DOT,
+                token::QUESTION_MARK,
+            ]),
             // '$' => self.create_new_token(token::MACRO_SYMBOL, &self.current_char_str()),
             '@' => self.create_new_token(token::MACRO_SYMBOL, &self.current_char_str()),
             _ => {
+                // check for a raw string
+                if self.current_char == 'r' && (self.peek_char() == '"' || self.peek_char() == '\'')
+                {
+                    let string = self.read_raw_string();
+                    self.create_new_token(token::STRING, &string)
+                }
                 // check for identifier
-                if self.current_char.is_alphabetic()
+                else if self.current_char.is_alphabetic()
                     || self.current_char == '_'
                     || self.current_char == '#'
                 {
@@ -436,12 +633,8 @@ impl Lex {
                     self.create_new_token(ident, literal)
                 } else if self.current_char.is_numeric() {
                     // probably a integer
-                    let int = self.read_number();
-                    if int.contains('.') {
-                        self.create_new_token(token::FLOAT, int.as_str())
-                    } else {
-                        self.create_new_token(token::INT, int.as_str())
-                    }
+                    let (number, token_type) = self.read_number();
+                    self.create_new_token(token_type, number.as_str())
                 } else {
                     self.create_new_token(token::ILLEGAL, &self.current_char_str())
                 }
@@ -465,12 +658,61 @@ impl Lex {
             token_type,
             token_literal,
             &self.current_file,
-            self.current_line,
-            self.current_col,
+            self.token_line,
+            self.token_col,
         )
     }
 }
 
+/// Turn the escape sequences of a string literal into the characters they stand for.
+///
+/// Supports \n \t \r \0 \\ \' \" \` \$ \xFF \uFFFF and \u{1F600}. Anything else escapes itself.
+pub fn unescape_string(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let Some(escaped) = chars.next() else {
+            result.push(c);
+            break;
+        };
+
+        match escaped {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            'r' => result.push('\r'),
+            '0' => result.push('\0'),
+            'x' | 'u' => {
+                let hex: String = if escaped == 'u' && chars.peek() == Some(&'{') {
+                    chars.next(); // {
+                    chars.by_ref().take_while(|c| *c != '}').collect()
+                } else {
+                    let len = if escaped == 'x' { 2 } else { 4 };
+                    chars.by_ref().take(len).collect()
+                };
+
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(unicode) => result.push(unicode),
+                    None => {
+                        result.push(escaped);
+                        result.push_str(&hex);
+                    }
+                }
+            }
+            // a escaped new line continues the string on the next line.
+            '\n' => {}
+            _ => result.push(escaped),
+        }
+    }
+
+    result
+}
+
 /// Used for testing only.
 pub fn read_all_tokens(input: String) -> Vec<token::Token> {
     let mut tokens: Vec<token::Token> = vec![];
@@ -1,5 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 /// EasyJS compiler token.
-#[derive(Debug, PartialEq, Eq, Clone)]
+///
+/// Missing fields are empty when deserializing, so a procedural macro can write a token as `{}`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Token {
     /// The type of token (in string)
     pub typ: String,
@@ -11,6 +16,17 @@ pub struct Token {
     pub line_number: i32,
     /// The col number the token starts
     pub col_number: i32,
+    /// The parts of a TEMPLATE token, empty for every other token.
+    pub parts: Vec<TemplatePart>,
+}
+
+/// A part of a template string, i.e. "Hello ${name}!"
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum TemplatePart {
+    /// The text between expressions, escapes are kept as is.
+    Text(String),
+    /// The tokens of a $name or ${expression}.
+    Expression(Vec<Token>),
 }
 
 pub const EMPTY_TOKEN: Token = Token {
@@ -19,8 +35,15 @@ pub const EMPTY_TOKEN: Token = Token {
     file_name: String::new(),
     line_number: -1,
     col_number: -1,
+    parts: Vec::new(),
 };
 
+impl Default for Token {
+    fn default() -> Self {
+        EMPTY_TOKEN
+    }
+}
+
 /// Create a new token on the fly
 pub fn new_token(
     typ: &str,
@@ -35,6 +58,7 @@ pub fn new_token(
         file_name: file_name.to_owned(),
         line_number,
         col_number,
+        parts: vec![],
     }
 }
 
@@ -47,9 +71,11 @@ pub const EOF: &str = "EOF";
 pub const IDENT: &str = "IDENT"; // add, foobar, x, y, ....
 pub const INT: &str = "INT"; // 123456 (INT64)
 pub const STRING: &str = "STRING";
+pub const TEMPLATE: &str = "TEMPLATE"; // "Hello ${name}"
 pub const ARRAY: &str = "ARRAY";
 pub const BOOLEAN: &str = "BOOLEAN";
 pub const FLOAT: &str = "FLOAT"; // (FLOAT32)
+pub const BIGINT: &str = "BIGINT"; // 10n
 
 // Operators
 pub const ASSIGN: &str = "=";
@@ -67,17 +93,39 @@ pub const GT_OR_EQ: &str = ">=";
 pub const LT_OR_EQ: &str = "<=";
 pub const DOT: &str = ".";
 pub const DOTDOT: &str = "..";
+pub const DOTDOT_EQUALS: &str = "..=";
 pub const BITWISE_AND: &str = "&";
 pub const BITWISE_OR: &str = "|";
 pub const AND_SYMBOL: &str = "&&";
 pub const OR_SYMBOL: &str = "||";
+pub const PIPE: &str = "|>";
 pub const QUESTION_MARK: &str = "?";
 pub const DOUBLE_QUESTION_MARK: &str = "??";
+pub const QUESTION_DOT: &str = "?.";
 pub const MODULUS: &str = "%";
 pub const PLUS_EQUALS: &str = "+=";
 pub const MINUS_EQUALS: &str = "-=";
 pub const SLASH_EQUALS: &str = "/=";
 pub const ASTERISK_EQUALS: &str = "*=";
+pub const MODULUS_EQUALS: &str = "%=";
+pub const BITWISE_XOR: &str = "^";
+pub const BITWISE_NOT: &str = "~";
+pub const SHIFT_LEFT: &str = "<<";
+pub const SHIFT_RIGHT: &str = ">>";
+pub const UNSIGNED_SHIFT_RIGHT: &str = ">>>";
+pub const EXPONENT: &str = "**";
+pub const INCREMENT: &str = "++";
+pub const DECREMENT: &str = "--";
+pub const EXPONENT_EQUALS: &str = "**=";
+pub const BITWISE_AND_EQUALS: &str = "&=";
+pub const BITWISE_OR_EQUALS: &str = "|=";
+pub const BITWISE_XOR_EQUALS: &str = "^=";
+pub const SHIFT_LEFT_EQUALS: &str = "<<=";
+pub const SHIFT_RIGHT_EQUALS: &str = ">>=";
+pub const UNSIGNED_SHIFT_RIGHT_EQUALS: &str = ">>>=";
+pub const AND_EQUALS: &str = "&&=";
+pub const OR_EQUALS: &str = "||=";
+pub const DOUBLE_QUESTION_MARK_EQUALS: &str = "??=";
 pub const SPREAD: &str = "...";
 
 // Delimiters
@@ -113,6 +161,7 @@ pub const MACRO_SYMBOL: &str = "MACRO_SYMBOL";
 
 // Keywords
 pub const FUNCTION: &str = "FUNCTION";
+pub const GENERATOR: &str = "GENERATOR"; // fn*
 // pub const USE: &str = "USE";
 pub const STRUCT: &str = "STRUCT";
 pub const TRUE: &str = "TRUE";
@@ -124,10 +173,13 @@ pub const RETURN: &str = "RETURN";
 pub const AS: &str = "AS";
 pub const JAVASCRIPT: &str = "JAVASCRIPT";
 pub const FOR: &str = "FOR";
+pub const DO: &str = "DO";
+pub const WHILE: &str = "WHILE";
 pub const IN: &str = "IN";
 pub const OF: &str = "OF";
 pub const ASYNC: &str = "ASYNC";
 pub const AWAIT: &str = "AWAIT";
+pub const YIELD: &str = "YIELD";
 pub const NOT: &str = "NOT";
 // pub const FROM: &str = "FROM";
 pub const SELF: &str = "SELF";
@@ -145,6 +197,7 @@ pub const BREAK: &str = "BREAK";
 pub const CONTINUE: &str = "CONTINUE";
 pub const NULL: &str = "NULL";
 pub const CLASS: &str = "CLASS";
+pub const COMPTIME: &str = "COMPTIME";
 
 // Builtin methods
 pub const BUILTIN: &str = "BUILTIN";
@@ -164,9 +217,12 @@ pub fn lookup_ident(ident: &str) -> &'static str {
         "javascript" => JAVASCRIPT,
         "in" => IN,
         "for" => FOR,
+        "do" => DO,
+        "while" => WHILE,
         "of" => OF,
         "async" => ASYNC,
         "await" => AWAIT,
+        "yield" => YIELD,
         "not" => NOT,
         "enum" => ENUM,
         // "from" => FROM,
@@ -185,6 +241,7 @@ pub fn lookup_ident(ident: &str) -> &'static str {
         "continue" => CONTINUE,
         "null" => NULL,
         "class" => CLASS,
+        "comptime" => COMPTIME,
         _ => IDENT, // Default case for unknown identifiers
     }
 }
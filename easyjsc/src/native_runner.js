@@ -126,6 +126,97 @@ class __EasyJSNativeInterop {
 
         return result;
     }
+
+    /**
+     * Function for converting a object to a native struct.
+     */
+    static convert_struct_to_native(instance, name, obj) {
+        const layout = __easyjs_native_structs[name];
+
+        // allocate space and get pointer
+        const ptr = instance.exports.__struct_alloc(layout.size);
+
+        // Write fields to memory
+        for (const [field, type, offset] of layout.fields) {
+            const value = this.convert_field_to_native(instance, type, obj[field]);
+            // converting a field can grow memory, so get a new view each time.
+            const view = new DataView(instance.exports.memory.buffer);
+            switch (type) {
+                case 'float': view.setFloat32(ptr + offset, value, true); break;
+                case 'f64': view.setFloat64(ptr + offset, value, true); break;
+                case 'i64': view.setBigInt64(ptr + offset, value, true); break;
+                case 'u8': view.setUint8(ptr + offset, value); break;
+                default: view.setInt32(ptr + offset, value, true);
+            }
+        }
+
+        return ptr;
+    }
+
+    /**
+     * Function for converting a struct field to native.
+     */
+    static convert_field_to_native(instance, type, value) {
+        switch (type) {
+            case 'string': return this.convert_string_to_native(instance, value);
+            case 'array': return this.convert_array_to_native(instance, value);
+            case 'bool': return value ? 1 : 0;
+            case 'i64': return BigInt.asIntN(64, BigInt(value));
+            default: {
+                if (type in __easyjs_native_structs) {
+                    // null is a null pointer
+                    return value == null ? 0 : this.convert_struct_to_native(instance, type, value);
+                }
+                return value;
+            }
+        }
+    }
+
+    /**
+     * Function for reading a struct from native.
+     */
+    static read_struct_from_native(instance, name, ptr) {
+        if (ptr == 0) {
+            return null;
+        }
+
+        const layout = __easyjs_native_structs[name];
+        const view = new DataView(instance.exports.memory.buffer);
+
+        // The decoded object
+        let result = {};
+
+        for (const [field, type, offset] of layout.fields) {
+            switch (type) {
+                case 'float': result[field] = view.getFloat32(ptr + offset, true); continue;
+                case 'f64': result[field] = view.getFloat64(ptr + offset, true); continue;
+                case 'i64': result[field] = view.getBigInt64(ptr + offset, true); continue;
+                case 'u8': result[field] = view.getUint8(ptr + offset); continue;
+                case 'u32': result[field] = view.getUint32(ptr + offset, true); continue;
+            }
+
+            const value = view.getInt32(ptr + offset, true);
+            switch (type) {
+                case 'bool': {
+                    result[field] = value == 0 ? false : true;
+                    break;
+                }
+                case 'string': {
+                    result[field] = this.read_string_from_native(instance, value);
+                    break;
+                }
+                case 'array': {
+                    result[field] = this.read_array_from_native(instance, value);
+                    break;
+                }
+                default: {
+                    result[field] = type in __easyjs_native_structs ? this.read_struct_from_native(instance, type, value) : value;
+                }
+            }
+        }
+
+        return result;
+    }
 }
 
 function __easyjs_native_call(fnName, paramTypes, returnTypes, ...args) {
@@ -162,12 +253,29 @@ function __easyjs_native_call(fnName, paramTypes, returnTypes, ...args) {
                 }
                 break;
             }
-            case 'float': {
+            case 'float':
+            case 'f64': {
                 if (typeof arg !== 'number' || isNaN(arg)) {
                     throw new Error(`Argument ${i} is not a valid float`);
                 }
                 break;
             }
+            case 'u8':
+            case 'u32': {
+                if (typeof arg !== 'number' || !Number.isInteger(arg)) {
+                    throw new Error(`Argument ${i} is not an integer`);
+                }
+                break;
+            }
+            case 'i64': {
+                // a i64 is a BigInt in JS, integer Numbers are converted.
+                if (typeof arg === 'number' && Number.isInteger(arg)) {
+                    args[i] = BigInt(arg);
+                } else if (typeof arg !== 'bigint') {
+                    throw new Error(`Argument ${i} is not a BigInt`);
+                }
+                break;
+            }
             case 'bool': {
                 // booleans must be true/false or a number
                 if (typeof arg !== 'boolean' && typeof arg !== 'number') {
@@ -191,6 +299,18 @@ function __easyjs_native_call(fnName, paramTypes, returnTypes, ...args) {
                 args[i] = __EasyJSNativeInterop.convert_array_to_native(__easyjs_native_instance, args[i]);
                 break;
             }
+            default: {
+                if (!(paramType in __easyjs_native_structs)) {
+                    break;
+                }
+                if (typeof arg !== 'object' || arg === null) {
+                    throw new Error(`Argument ${i} is not a ${paramType}`);
+                }
+
+                // convert the object into a native struct
+                args[i] = __EasyJSNativeInterop.convert_struct_to_native(__easyjs_native_instance, paramType, args[i]);
+                break;
+            }
         }
     }
 
@@ -210,6 +330,11 @@ function __easyjs_native_call(fnName, paramTypes, returnTypes, ...args) {
         case 'float': {
             break;
         }
+        case 'u32': {
+            // wasm only knows signed ints
+            result = result >>> 0;
+            break;
+        }
         case 'bool': {
             result = result == 0 ? false : true
             break;
@@ -218,6 +343,12 @@ function __easyjs_native_call(fnName, paramTypes, returnTypes, ...args) {
             result = __EasyJSNativeInterop.read_array_from_native(__easyjs_native_instance, result);
             break;
         }
+        default: {
+            if (returnTypes[0] in __easyjs_native_structs) {
+                result = __EasyJSNativeInterop.read_struct_from_native(__easyjs_native_instance, returnTypes[0], result);
+            }
+            break;
+        }
     }
 
     return result;
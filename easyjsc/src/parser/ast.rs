@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::lexer::token as tk;
 
 pub enum NodeType {
@@ -5,7 +7,7 @@ pub enum NodeType {
     Expression,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Statement {
     EmptyStatement, // there was an issue
     VariableStatement(
@@ -23,10 +25,14 @@ pub enum Statement {
     // ConstVariableStatement(tk::Token, Box<Expression>, Option<Box<Expression>>, Box<Expression>, bool),
     // for condition { body }
     ForStatement(tk::Token, Box<Expression>, Box<Statement>),
+    /// do { body } while condition
+    DoWhileStatement(tk::Token, Box<Statement>, Box<Expression>),
+    /// label: for condition { body }
+    LabeledStatement(tk::Token, String, Box<Statement>),
     // javascript{}
     JavaScriptStatement(tk::Token, String),
     /// ```easyjs
-    /// struct Person[name,age] with GreetMixin, FarewellMixin {
+    /// struct Person[name,age] with GreetMixin, FarewellMixin derives eq, clone {
     ///     MAX_AGE = 150 // static variables
     ///
     ///     fn greet(self) { // methods
@@ -41,8 +47,10 @@ pub enum Statement {
         Box<Expression>,
         Option<Box<Vec<Expression>>>,
         Option<Box<Vec<Expression>>>,
+        Option<Box<Vec<Expression>>>,
         Box<Vec<Statement>>,
         Box<Vec<Expression>>,
+        Box<Vec<Expression>>, // Attributes
     ),
 
     /// pub fn
@@ -73,19 +81,23 @@ pub enum Statement {
     /// A enum statement
     EnumStatement(tk::Token, String, Box<Vec<Expression>>),
 
-    /// A break statement
-    BreakStatement(tk::Token),
+    /// A type alias. i.e. `type UserId = int`
+    TypeAliasStatement(tk::Token, String, Box<Expression>),
 
-    /// A continue statement
-    ContinueStatement(tk::Token),
+    /// A break statement (with a label or not)
+    BreakStatement(tk::Token, Option<String>),
 
-    /// Declaring the macro (macro, ident, arguments, body as BlockStatment) (bool is for hygenic or not)
+    /// A continue statement (with a label or not)
+    ContinueStatement(tk::Token, Option<String>),
+
+    /// Declaring the macro (macro, ident, arguments, body as BlockStatment) (bools are for hygenic and procedural)
     MacroStatement(
         tk::Token,
         Box<Expression>,
         Box<Vec<Expression>>,
         Box<Statement>,
-        bool
+        bool,
+        bool,
     ),
 
     /// A class statement. (Identifier, Extensions, variables and methods [static and non-static])
@@ -94,7 +106,14 @@ pub enum Statement {
         Box<Expression>, // Name
         Box<Vec<Expression>>, // Extensions
         Box<Vec<Statement>>, // Variables, Methods (static and non-static)
-    )
+        Box<Vec<Expression>>, // Attributes
+    ),
+
+    /// Conditional compilation. (condition, consequence, alternative)
+    ///
+    /// @if(target == "node") { ... } else { ... }
+    /// @cfg(feature = "logging") { ... }
+    CfgStatement(tk::Token, Box<Expression>, Box<Statement>, Box<Statement>),
 }
 
 impl Statement {
@@ -111,17 +130,21 @@ impl Statement {
             Statement::ExpressionStatement(token, _) => token,
             Statement::BlockStatement(token, _) => token,
             Statement::ForStatement(token, _, _) => token,
+            Statement::DoWhileStatement(token, _, _) => token,
+            Statement::LabeledStatement(token, _, _) => token,
             Statement::JavaScriptStatement(token, _) => token,
-            Statement::StructStatement(token, _, _, _, _, _) => token,
+            Statement::StructStatement(token, _, _, _, _, _, _, _) => token,
             Statement::ExportStatement(token, _) => token,
             Statement::AsyncBlockStatement(token, _) => token,
             Statement::MatchStatement(token, _, _) => token,
             Statement::NativeStatement(token, _) => token,
             Statement::EnumStatement(token, _, _) => token,
-            Statement::BreakStatement(token) => token,
-            Statement::ContinueStatement(token) => token,
-            Statement::MacroStatement(token, _, _, _, _) => token,
-            Statement::ClassStatement(token, _, _, _) => token,
+            Statement::TypeAliasStatement(token, _, _) => token,
+            Statement::BreakStatement(token, _) => token,
+            Statement::ContinueStatement(token, _) => token,
+            Statement::MacroStatement(token, _, _, _, _, _) => token,
+            Statement::ClassStatement(token, _, _, _, _) => token,
+            Statement::CfgStatement(token, _, _, _) => token,
         }
     }
 
@@ -134,17 +157,21 @@ impl Statement {
             Statement::ImportStatement(_, _, _) => "ImportStatement",
             Statement::BlockStatement(_, _) => "BlockStatement",
             Statement::ForStatement(_, _, _) => "ForStatement",
+            Statement::DoWhileStatement(_, _, _) => "DoWhileStatement",
+            Statement::LabeledStatement(_, _, _) => "LabeledStatement",
             Statement::JavaScriptStatement(_, _) => "JavaScriptStatement",
-            Statement::StructStatement(_, _, _, _, _, _) => "StructStatement",
+            Statement::StructStatement(_, _, _, _, _, _, _, _) => "StructStatement",
             Statement::ExportStatement(_, _) => "ExportStatement",
             Statement::AsyncBlockStatement(_, _) => "AsyncBlockStatement",
             Statement::MatchStatement(_, _, _) => "MatchStatement",
             Statement::NativeStatement(_, _) => "NativeStatement",
             Statement::EnumStatement(_, _, _) => "EnumStatement",
-            Statement::BreakStatement(_) => "BreakStatement",
-            Statement::ContinueStatement(_) => "ContinueStatement",
-            Statement::MacroStatement(_, _, _, _, _) => "MacroStatement",
-            Statement::ClassStatement(_, _, _, _) => "ClassStatement"
+            Statement::TypeAliasStatement(_, _, _) => "TypeAliasStatement",
+            Statement::BreakStatement(_, _) => "BreakStatement",
+            Statement::ContinueStatement(_, _) => "ContinueStatement",
+            Statement::MacroStatement(_, _, _, _, _, _) => "MacroStatement",
+            Statement::ClassStatement(_, _, _, _, _) => "ClassStatement",
+            Statement::CfgStatement(_, _, _, _) => "CfgStatement",
         }
         .to_string()
     }
@@ -174,13 +201,15 @@ impl Statement {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Expression {
     EmptyExpression,                                      // there was an issue
     Identifier(tk::Token, String),                        // token value
     PrefixExpression(tk::Token, String, Box<Expression>), // token operator expression
+    PostfixExpression(tk::Token, Box<Expression>, String), // token expression operator
     IntegerLiteral(tk::Token, i64),                       // token value(i64)
     StringLiteral(tk::Token, String),                     // token value(String)
+    TemplateLiteral(tk::Token, Vec<String>, Vec<Expression>), // token strings expressions, one more string than expressions
     CommentExpression(tk::Token, String),                 // token value(String)
     InfixExpression(tk::Token, Box<Expression>, String, Box<Expression>), // token left operator right
     Boolean(tk::Token, bool),                                             // token <- boolean
@@ -196,13 +225,17 @@ pub enum Expression {
     AsyncExpression(tk::Token, Box<Expression>),
     // await expression
     AwaitExpression(tk::Token, Box<Expression>),
-    // fn function_name paramaters {}
+    /// yield value (bool is for yield*)
+    YieldExpression(tk::Token, Box<Expression>, bool),
+    // @attribute fn function_name<type paramaters> paramaters {}
     FunctionLiteral(
         tk::Token,
         Box<Expression>,
         Box<Vec<Expression>>,
         Box<Expression>,
         Box<Statement>,
+        Box<Vec<Expression>>,
+        Box<Vec<Expression>>, // Attributes
     ),
     // fn(params) {statement} OR fn(params) stmt
     LambdaLiteral(tk::Token, Box<Vec<Expression>>, Box<Statement>),
@@ -212,8 +245,8 @@ pub enum Expression {
     InExpression(tk::Token, Box<Expression>, Box<Expression>),
     // left of right
     OfExpression(tk::Token, Box<Expression>, Box<Expression>),
-    // i64(left)..i64(right)
-    RangeExpression(tk::Token, Box<Expression>, Box<Expression>),
+    // i64(left)..i64(right) step i64, the token is ..= for a inclusive range
+    RangeExpression(tk::Token, Box<Expression>, Box<Expression>, Box<Expression>),
     // left.right
     DotExpression(tk::Token, Box<Expression>, Box<Expression>),
     // left.if {}
@@ -244,20 +277,41 @@ pub enum Expression {
     NewClassExpression(tk::Token, Box<Expression>),
     /// Float literal 0.0
     FloatLiteral(tk::Token, f64),
+    BigIntLiteral(tk::Token, String), // token value, i.e. 0xff for 0xffn
     /// Grouped Expression ()
     GroupedExpression(tk::Token, Box<Expression>),
     /// left is right (typeof(left) == right)
     IsExpression(tk::Token, Box<Expression>, Box<Expression>),
+    /// left as type, i.e. `x as i64`
+    CastExpression(tk::Token, Box<Expression>, Box<Expression>),
     /// Builtin function call
     BuiltinCall(tk::Token, Box<Vec<Expression>>),
     /// Identifier with type
     IdentifierWithType(tk::Token, String, Box<Expression>),
     /// Type expression
     Type(tk::Token, String),
+    /// Optional member access. i.e. `a?.b`
+    OptionalDotExpression(tk::Token, Box<Expression>, Box<Expression>),
+    /// Optional index access. i.e. `a?.[i]`
+    OptionalIndexExpression(tk::Token, Box<Expression>, Box<Expression>),
+    /// Optional call. i.e. `f?.()`
+    OptionalCallExpression(tk::Token, Box<Expression>, Box<Vec<Expression>>),
+    /// Type expression with type arguments. i.e. `Array<T>`, `Map<K, V>`
+    GenericType(tk::Token, String, Box<Vec<Expression>>),
+    /// Union type expression. i.e. `int | float`
+    UnionType(tk::Token, Box<Vec<Expression>>),
+    /// Optional type expression. i.e. `string?`
+    OptionalType(tk::Token, Box<Expression>),
+    /// Literal type expression. i.e. `"GET"`, `1`, `true`
+    LiteralType(tk::Token, Box<Expression>),
     /// IIFE
     ///
     /// var a = fn { return 1 } // a = 1
     IIFE(tk::Token, Box<Statement>),
+    /// Evaluated at compile time, the result is inlined as a literal.
+    ///
+    /// var table = comptime { return [1, 2, 3].map(fn(n) { return n * n }) }
+    ComptimeExpression(tk::Token, Box<Statement>),
     /// ...variable
     SpreadExpression(tk::Token, Box<Expression>),
     /// Doc comment '///'
@@ -275,20 +329,23 @@ impl Expression {
             }
             Expression::Identifier(token, _) => token,
             Expression::PrefixExpression(token, _, _) => token,
+            Expression::PostfixExpression(token, _, _) => token,
             Expression::IntegerLiteral(token, _) => token,
             Expression::StringLiteral(token, _) => token,
+            Expression::TemplateLiteral(token, _, _) => token,
             Expression::CommentExpression(token, _) => token,
             Expression::InfixExpression(token, _, _, _) => token,
             Expression::Boolean(token, _) => token,
             Expression::IfExpression(token, _, _, _, _) => token,
             Expression::AsyncExpression(token, _) => token,
             Expression::AwaitExpression(token, _) => token,
-            Expression::FunctionLiteral(token, _, _, _, _) => token,
+            Expression::YieldExpression(token, _, _) => token,
+            Expression::FunctionLiteral(token, _, _, _, _, _, _) => token,
             Expression::LambdaLiteral(token, _, _) => token,
             Expression::CallExpression(token, _, _) => token,
             Expression::InExpression(token, _, _) => token,
             Expression::OfExpression(token, _, _) => token,
-            Expression::RangeExpression(token, _, _) => token,
+            Expression::RangeExpression(token, _, _, _) => token,
             Expression::DotExpression(token, _, _) => token,
             Expression::DotIfExpression(token, _, _) => token,
             Expression::ArrayLiteral(token, _) => token,
@@ -304,12 +361,22 @@ impl Expression {
             Expression::DefaultIfNullExpression(token, _, _) => token,
             Expression::NewClassExpression(token, _) => token,
             Expression::FloatLiteral(token, _) => token,
+            Expression::BigIntLiteral(token, _) => token,
             Expression::GroupedExpression(token, _) => token,
             Expression::IsExpression(token, _, _) => token,
+            Expression::CastExpression(token, _, _) => token,
             Expression::BuiltinCall(token, _) => token,
             Expression::IdentifierWithType(token, _, _) => token,
             Expression::Type(token, _) => token,
+            Expression::GenericType(token, _, _) => token,
+            Expression::OptionalDotExpression(token, _, _) => token,
+            Expression::OptionalIndexExpression(token, _, _) => token,
+            Expression::OptionalCallExpression(token, _, _) => token,
+            Expression::UnionType(token, _) => token,
+            Expression::OptionalType(token, _) => token,
+            Expression::LiteralType(token, _) => token,
             Expression::IIFE(token, _) => token,
+            Expression::ComptimeExpression(token, _) => token,
             Expression::SpreadExpression(token, _) => token,
             Expression::DocCommentExpression(token, _) => token
         }
@@ -321,20 +388,23 @@ impl Expression {
             Expression::EmptyExpression => "EmptyExpression",
             Expression::Identifier(_, _) => "Identifier",
             Expression::PrefixExpression(_, _, _) => "PrefixExpression",
+            Expression::PostfixExpression(_, _, _) => "PostfixExpression",
             Expression::IntegerLiteral(_, _) => "IntegerLiteral",
             Expression::StringLiteral(_, _) => "StringLiteral",
+            Expression::TemplateLiteral(_, _, _) => "TemplateLiteral",
             Expression::CommentExpression(_, _) => "CommentExpression",
             Expression::InfixExpression(_, _, _, _) => "InfixExpression",
             Expression::Boolean(_, _) => "Boolean",
             Expression::IfExpression(_, _, _, _, _) => "IfExpression",
             Expression::AsyncExpression(_, _) => "AsyncExpression",
             Expression::AwaitExpression(_, _) => "AwaitExpression",
-            Expression::FunctionLiteral(_, _, _, _, _) => "FunctionLiteral",
+            Expression::YieldExpression(_, _, _) => "YieldExpression",
+            Expression::FunctionLiteral(_, _, _, _, _, _, _) => "FunctionLiteral",
             Expression::LambdaLiteral(_, _, _) => "LambdaLiteral",
             Expression::CallExpression(_, _, _) => "CallExpression",
             Expression::InExpression(_, _, _) => "InExpression",
             Expression::OfExpression(_, _, _) => "OfExpression",
-            Expression::RangeExpression(_, _, _) => "RangeExpression",
+            Expression::RangeExpression(_, _, _, _) => "RangeExpression",
             Expression::DotExpression(_, _, _) => "DotExpression",
             Expression::DotIfExpression(_, _, _) => "DotIfExpression",
             Expression::ArrayLiteral(_, _) => "ArrayLiteral",
@@ -350,12 +420,22 @@ impl Expression {
             Expression::DefaultIfNullExpression(_, _, _) => "DefaultIfNullExpression",
             Expression::NewClassExpression(_, _) => "NewClassExpression",
             Expression::FloatLiteral(_, _) => "FloatLiteral",
+            Expression::BigIntLiteral(_, _) => "BigIntLiteral",
             Expression::GroupedExpression(_, _) => "GroupedExpression",
             Expression::IsExpression(_, _, _) => "IsExpression",
+            Expression::CastExpression(_, _, _) => "CastExpression",
             Expression::BuiltinCall(_, _) => "BuiltinCall",
             Expression::IdentifierWithType(_, _, _) => "IdentifierWithType",
             Expression::Type(_, _) => "Type",
+            Expression::GenericType(_, _, _) => "GenericType",
+            Expression::OptionalDotExpression(_, _, _) => "OptionalDotExpression",
+            Expression::OptionalIndexExpression(_, _, _) => "OptionalIndexExpression",
+            Expression::OptionalCallExpression(_, _, _) => "OptionalCallExpression",
+            Expression::UnionType(_, _) => "UnionType",
+            Expression::OptionalType(_, _) => "OptionalType",
+            Expression::LiteralType(_, _) => "LiteralType",
             Expression::IIFE(_, _) => "IIFE",
+            Expression::ComptimeExpression(_, _) => "ComptimeExpression",
             Expression::SpreadExpression(_, _) => "SpreadExpression",
             Expression::DocCommentExpression(_, _) => "DocCommentExpression"
         }
@@ -368,6 +448,17 @@ impl Expression {
     pub fn is_empty(&self) -> bool {
         self.eq(Expression::EmptyExpression)
     }
+
+    /// Get the value of a integer literal, i.e. `10` or `-10`.
+    pub fn get_int_literal(&self) -> Option<i64> {
+        match self {
+            Expression::IntegerLiteral(_, value) => Some(*value),
+            Expression::PrefixExpression(_, op, value) if op == "-" => {
+                value.get_int_literal().map(|value| -value)
+            }
+            _ => None,
+        }
+    }
 }
 
 pub struct Program {
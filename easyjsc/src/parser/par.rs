@@ -17,38 +17,56 @@ pub struct Parser {
 
     /// is debug mode
     is_debug_mode: bool,
+
+    /// The labels of the loops we are in.
+    labels: Vec<String>,
+
+    /// Whether each function we are in is a generator.
+    generators: Vec<bool>,
 }
 
 // Constant values
 const LOWEST: i64 = 1;
+const PIPE: i64 = 2; // |>
 
-// math
-const EQUALS: i64 = 2; // == !=
-
-const LESSGREATER: i64 = 3; // < > >= <=
-const SUM: i64 = 4; // + -
-const PRODUCT: i64 = 5; // * /
+// bitwise
+const BITWISE_OR: i64 = 3; // |
+const BITWISE_XOR: i64 = 4; // ^
+const BITWISE_AND: i64 = 5; // &
 
-const DOT: i64 = 6; // .field or .method or ...spread
-
-const CALL: i64 = 7; // my_function(X)
-const BRACKET: i64 = 10; // [
-const BRACE: i64 = 11; // {
-const DOTDOT: i64 = 12; // ..
-const IN: i64 = 13; // in
-const OF: i64 = 14; // of
-const AWAIT: i64 = 15; // await
-
-const ASSIGN: i64 = 16;
-
-const AS: i64 = 18;
-const MACRO_SYMBOL: i64 = 19;
-// const DECORATOR: i64 = 20;
-const DOC_COMMENT: i64 = 20;
-const AND: i64 = 22;
-const OR: i64 = 23;
-const DOUBLE_QUESTION_MARK: i64 = 25;
-const NEW: i64 = 27;
+// math
+const EQUALS: i64 = 6; // == !=
+
+const LESSGREATER: i64 = 7; // < > >= <=
+const SHIFT: i64 = 8; // << >> >>>
+const SUM: i64 = 9; // + -
+const PRODUCT: i64 = 10; // * / %
+const EXPONENT: i64 = 11; // **
+const PREFIX: i64 = 12; // ~x ++x --x
+
+const DOT: i64 = 13; // .field or .method or ...spread
+
+const CALL: i64 = 14; // my_function(X) or x++
+const BRACKET: i64 = 15; // [
+const BRACE: i64 = 16; // {
+const DOTDOT: i64 = 17; // ..
+const IN: i64 = 18; // in
+const OF: i64 = 19; // of
+const AWAIT: i64 = 20; // await
+
+const ASSIGN: i64 = 21;
+
+const AS: i64 = 22;
+const MACRO_SYMBOL: i64 = 23;
+// const DECORATOR: i64 = 24;
+const DOC_COMMENT: i64 = 24;
+const AND: i64 = 25;
+const OR: i64 = 26;
+const DOUBLE_QUESTION_MARK: i64 = 27;
+const NEW: i64 = 28;
+
+/// Methods a struct can derive with `derives`.
+const DERIVABLES: [&str; 4] = ["eq", "clone", "json", "debug"];
 
 /// Find the precedence of a token.
 fn precedences(tk: &str) -> i64 {
@@ -63,12 +81,14 @@ fn precedences(tk: &str) -> i64 {
         token::ASTERISK => PRODUCT,
         token::L_PAREN => CALL,
         token::DOT => DOT,
+        token::QUESTION_DOT => DOT,
         token::SPREAD => DOT,
         token::LT_OR_EQ => LESSGREATER,
         token::GT_OR_EQ => LESSGREATER,
         token::L_BRACKET => BRACKET,
         token::L_BRACE => BRACE,
         token::DOTDOT => DOTDOT,
+        token::DOTDOT_EQUALS => DOTDOT,
         token::IN => IN,
         token::OF => OF,
         token::IS => IN,
@@ -80,6 +100,7 @@ fn precedences(tk: &str) -> i64 {
         // token::DECORATOR => DECORATOR,
         token::AND_SYMBOL => AND,
         token::OR_SYMBOL => OR,
+        token::PIPE => PIPE,
         token::DOUBLE_QUESTION_MARK => DOUBLE_QUESTION_MARK,
         token::MODULUS => PRODUCT,
         token::NEW => NEW,
@@ -87,6 +108,26 @@ fn precedences(tk: &str) -> i64 {
         token::MINUS_EQUALS => ASSIGN,
         token::SLASH_EQUALS => ASSIGN,
         token::ASTERISK_EQUALS => ASSIGN,
+        token::MODULUS_EQUALS => ASSIGN,
+        token::EXPONENT_EQUALS => ASSIGN,
+        token::BITWISE_AND_EQUALS => ASSIGN,
+        token::BITWISE_OR_EQUALS => ASSIGN,
+        token::BITWISE_XOR_EQUALS => ASSIGN,
+        token::SHIFT_LEFT_EQUALS => ASSIGN,
+        token::SHIFT_RIGHT_EQUALS => ASSIGN,
+        token::UNSIGNED_SHIFT_RIGHT_EQUALS => ASSIGN,
+        token::AND_EQUALS => ASSIGN,
+        token::OR_EQUALS => ASSIGN,
+        token::DOUBLE_QUESTION_MARK_EQUALS => ASSIGN,
+        token::BITWISE_AND => BITWISE_AND,
+        token::BITWISE_OR => BITWISE_OR,
+        token::BITWISE_XOR => BITWISE_XOR,
+        token::SHIFT_LEFT => SHIFT,
+        token::SHIFT_RIGHT => SHIFT,
+        token::UNSIGNED_SHIFT_RIGHT => SHIFT,
+        token::EXPONENT => EXPONENT,
+        token::INCREMENT => CALL,
+        token::DECREMENT => CALL,
         _ => LOWEST,
     }
 }
@@ -106,6 +147,8 @@ impl Parser {
             peek_token: token::new_token("", "", "", -1, -1),
             errors: vec![],
             is_debug_mode,
+            labels: vec![],
+            generators: vec![],
         };
         p.next_token();
         p.next_token();
@@ -130,9 +173,14 @@ impl Parser {
             token::SELF => parse_identifier(self, false),
             token::INT => parse_integer_literal(self),
             token::FLOAT => parse_float_literal(self),
+            token::BIGINT => parse_bigint_literal(self),
+            token::ILLEGAL => parse_illegal_token(self),
             token::BANG => parse_prefix_expression(self),
             token::NOT => parse_not_expression(self),
             token::MINUS => parse_prefix_expression(self),
+            token::BITWISE_NOT => parse_prefix_expression(self),
+            token::INCREMENT => parse_prefix_expression(self),
+            token::DECREMENT => parse_prefix_expression(self),
             token::TRUE => parse_boolean(self),
             token::FALSE => parse_boolean(self),
             token::NULL => parse_null(self),
@@ -140,12 +188,15 @@ impl Parser {
             token::IF => parse_if_expression(self),
             token::FUNCTION => parse_function_literal(self),
             token::STRING => parse_string_literal(self),
+            token::TEMPLATE => parse_template_literal(self),
             token::COMMENT => parse_comment(self),
             token::L_BRACKET => parse_array_literal(self),
             token::L_BRACE => parse_object_literal(self),
             token::ASYNC => parse_async_expressoin(self),
             token::AS => parse_as_expression(self),
             token::AWAIT => parse_await_expression(self),
+            token::COMPTIME => parse_comptime_expression(self),
+            token::YIELD => parse_yield_expression(self),
             token::MACRO_SYMBOL => parse_macro_expression(self),
             token::SPREAD => parse_spread_expression(self),
             token::DOC_COMMENT => parse_doc_comment_expression(self),
@@ -164,8 +215,13 @@ impl Parser {
             token::INT => true,
             token::BANG => true,
             token::FLOAT => true,
+            token::BIGINT => true,
+            token::ILLEGAL => true,
             token::NOT => true,
             token::MINUS => true,
+            token::BITWISE_NOT => true,
+            token::INCREMENT => true,
+            token::DECREMENT => true,
             token::TRUE => true,
             token::FALSE => true,
             token::NULL => true,
@@ -173,6 +229,7 @@ impl Parser {
             token::IF => true,
             token::FUNCTION => true,
             token::STRING => true,
+            token::TEMPLATE => true,
             token::COMMENT => true,
             token::AS => true,
             token::L_BRACKET => true,
@@ -180,6 +237,8 @@ impl Parser {
             token::ASYNC => true,
             token::DOC_COMMENT => true,
             token::AWAIT => true,
+            token::COMPTIME => true,
+            token::YIELD => true,
             token::MACRO_SYMBOL => true,
             token::SPREAD => true,
             // token::DECORATOR => true,
@@ -204,9 +263,11 @@ impl Parser {
             token::GT_OR_EQ => true,
             token::L_PAREN => true,
             token::DOT => true,
+            token::QUESTION_DOT => true,
             token::JAVASCRIPT => true,
             token::L_BRACKET => true,
             token::DOTDOT => true,
+            token::DOTDOT_EQUALS => true,
             token::IN => true,
             token::OF => true,
             token::ASSIGN => true,
@@ -219,7 +280,29 @@ impl Parser {
             token::MINUS_EQUALS => true,
             token::SLASH_EQUALS => true,
             token::ASTERISK_EQUALS => true,
+            token::MODULUS_EQUALS => true,
+            token::EXPONENT_EQUALS => true,
+            token::BITWISE_AND_EQUALS => true,
+            token::BITWISE_OR_EQUALS => true,
+            token::BITWISE_XOR_EQUALS => true,
+            token::SHIFT_LEFT_EQUALS => true,
+            token::SHIFT_RIGHT_EQUALS => true,
+            token::UNSIGNED_SHIFT_RIGHT_EQUALS => true,
+            token::AND_EQUALS => true,
+            token::OR_EQUALS => true,
+            token::DOUBLE_QUESTION_MARK_EQUALS => true,
+            token::BITWISE_AND => true,
+            token::BITWISE_OR => true,
+            token::BITWISE_XOR => true,
+            token::SHIFT_LEFT => true,
+            token::SHIFT_RIGHT => true,
+            token::UNSIGNED_SHIFT_RIGHT => true,
+            token::EXPONENT => true,
+            token::INCREMENT => true,
+            token::DECREMENT => true,
             token::IS => true,
+            token::AS => true,
+            token::PIPE => true,
             _ => false,
         }
     }
@@ -239,9 +322,11 @@ impl Parser {
             token::GT_OR_EQ => parse_infix_expression(self, left),
             token::L_PAREN => parse_call_expression(self, left),
             token::DOT => parse_dot_expression(self, left),
+            token::QUESTION_DOT => parse_optional_chain_expression(self, left),
             token::JAVASCRIPT => parse_infix_expression(self, left),
             token::L_BRACKET => parse_index_expression(self, left),
             token::DOTDOT => parse_range_expression(self, left),
+            token::DOTDOT_EQUALS => parse_range_expression(self, left),
             token::IN => parse_in_expression(self, left),
             token::OF => parse_of_expression(self, left),
             token::ASSIGN => parse_assign_expression(self, left),
@@ -253,7 +338,29 @@ impl Parser {
             token::MINUS_EQUALS => parse_infix_expression(self, left),
             token::SLASH_EQUALS => parse_infix_expression(self, left),
             token::ASTERISK_EQUALS => parse_infix_expression(self, left),
+            token::MODULUS_EQUALS => parse_infix_expression(self, left),
+            token::EXPONENT_EQUALS => parse_infix_expression(self, left),
+            token::BITWISE_AND_EQUALS => parse_infix_expression(self, left),
+            token::BITWISE_OR_EQUALS => parse_infix_expression(self, left),
+            token::BITWISE_XOR_EQUALS => parse_infix_expression(self, left),
+            token::SHIFT_LEFT_EQUALS => parse_infix_expression(self, left),
+            token::SHIFT_RIGHT_EQUALS => parse_infix_expression(self, left),
+            token::UNSIGNED_SHIFT_RIGHT_EQUALS => parse_infix_expression(self, left),
+            token::AND_EQUALS => parse_infix_expression(self, left),
+            token::OR_EQUALS => parse_infix_expression(self, left),
+            token::DOUBLE_QUESTION_MARK_EQUALS => parse_infix_expression(self, left),
+            token::BITWISE_AND => parse_infix_expression(self, left),
+            token::BITWISE_OR => parse_infix_expression(self, left),
+            token::BITWISE_XOR => parse_infix_expression(self, left),
+            token::SHIFT_LEFT => parse_infix_expression(self, left),
+            token::SHIFT_RIGHT => parse_infix_expression(self, left),
+            token::UNSIGNED_SHIFT_RIGHT => parse_infix_expression(self, left),
+            token::EXPONENT => parse_infix_expression(self, left),
+            token::INCREMENT => parse_postfix_expression(self, left),
+            token::DECREMENT => parse_postfix_expression(self, left),
             token::IS => parse_is_expression(self, left),
+            token::AS => parse_cast_expression(self, left),
+            token::PIPE => parse_pipe_expression(self, left),
             _ => ast::Expression::EmptyExpression,
         }
     }
@@ -269,7 +376,7 @@ impl Parser {
     fn add_error(&mut self, error: &str) {
         self.errors.push(format!(
             "File: {} Line {}.{}: {}",
-            self.l.current_file, self.l.current_line, self.l.current_col, error
+            self.l.current_file, self.c_token.line_number, self.c_token.col_number, error
         ))
     }
 
@@ -301,6 +408,40 @@ impl Parser {
         false
     }
 
+    /// A ++ or -- on the next line belongs to the next statement, i.e. `x\n++y`.
+    fn peek_token_is_prefix_on_new_line(&self) -> bool {
+        (self.peek_token_is(token::INCREMENT) || self.peek_token_is(token::DECREMENT))
+            && self.peek_token.line_number != self.c_token.line_number
+    }
+
+    /// Expect the peek token to close a type argument list.
+    ///
+    /// Nested type arguments like `Map<K, Array<V>>` end in a `>>` token, so the
+    /// first `>` is split off and the rest stays as the peek token.
+    fn expect_peek_closing_angle(&mut self) -> bool {
+        let rest = match self.peek_token.typ.as_str() {
+            token::SHIFT_RIGHT => token::GT,
+            token::UNSIGNED_SHIFT_RIGHT => token::SHIFT_RIGHT,
+            token::GT_OR_EQ => token::ASSIGN,
+            token::SHIFT_RIGHT_EQUALS => token::GT_OR_EQ,
+            token::UNSIGNED_SHIFT_RIGHT_EQUALS => token::SHIFT_RIGHT_EQUALS,
+            _ => return self.expect_peek(token::GT),
+        };
+
+        let mut closing = self.peek_token.clone();
+        closing.typ = token::GT.to_string();
+        closing.literal = token::GT.to_string();
+
+        let mut rest_token = self.peek_token.clone();
+        rest_token.typ = rest.to_string();
+        rest_token.literal = rest.to_string();
+        rest_token.col_number += 1;
+
+        self.c_token = closing;
+        self.peek_token = rest_token;
+        true
+    }
+
     /// Move forward in the token hierachy
     fn next_token(&mut self) {
         self.c_token = self.peek_token.clone();
@@ -358,8 +499,17 @@ impl Parser {
 
 /// Parse a statement, returns EmptyStatement on error.
 fn parse_statement(parser: &mut Parser) -> ast::Statement {
-    let stmt = match parser.c_token.typ.as_str() {
+    let typ = parser.c_token.typ.clone();
+    let stmt = match typ.as_str() {
         // token::VAR => parse_var_statement(parser),
+        // `type` is not a keyword so that `event.type` and co keep working.
+        token::IDENT if parser.c_token.literal == "type" && parser.peek_token_is(token::IDENT) => {
+            parse_type_alias_statement(parser)
+        }
+        // `proc` is not a keyword either.
+        token::IDENT if parser.c_token.literal == "proc" && parser.peek_token_is(token::MACRO) => {
+            parse_proc_macro_decleration(parser)
+        }
         token::IDENT => {
             if parser.peek_token_is(token::ASSIGN)
                 || parser.peek_token_is(token::COLON)
@@ -379,6 +529,7 @@ fn parse_statement(parser: &mut Parser) -> ast::Statement {
             parser.c_token.to_owned().literal,
         ),
         token::FOR => parse_for_statement(parser),
+        token::DO => parse_do_while_statement(parser),
         token::STRUCT => parse_struct_statement(parser),
         token::PUB => parse_export_statement(parser),
         token::ASYNC => parse_async_block_statement(parser),
@@ -389,6 +540,8 @@ fn parse_statement(parser: &mut Parser) -> ast::Statement {
         token::CONTINUE => parse_continue_statement(parser),
         token::MACRO => parse_macro_decleration(parser),
         token::CLASS => parse_class_statement(parser),
+        token::MACRO_SYMBOL if is_cfg_directive(parser) => parse_cfg_statement(parser),
+        token::MACRO_SYMBOL if is_attribute(parser) => parse_attributed_statement(parser),
         _ => parse_expression_statement(parser),
     };
 
@@ -400,16 +553,261 @@ fn parse_statement(parser: &mut Parser) -> ast::Statement {
     stmt
 }
 
+/// Is the current `@` the start of a `@if(...)` or `@cfg(...)`?
+fn is_cfg_directive(p: &Parser) -> bool {
+    p.peek_token_is(token::IF) || (p.peek_token_is(token::IDENT) && p.peek_token.literal == "cfg")
+}
+
+fn parse_cfg_statement(p: &mut Parser) -> ast::Statement {
+    p.debug_print("parse_cfg_statement");
+    p.next_token(); // if or cfg
+    let token = p.c_token.to_owned();
+
+    if !p.expect_peek(token::L_PAREN) {
+        return ast::empty_statement();
+    }
+    p.next_token();
+
+    let condition = parse_expression(p, LOWEST);
+    if condition.is_empty() {
+        return ast::empty_statement();
+    }
+
+    if !p.expect_peek(token::R_PAREN) || !p.expect_peek(token::L_BRACE) {
+        return ast::empty_statement();
+    }
+    let consequence = parse_block_statement(p);
+
+    let mut alternative = ast::Statement::EmptyStatement;
+    if p.peek_token_is(token::ELSE) {
+        p.next_token(); // else
+        if p.peek_token_is(token::MACRO_SYMBOL) {
+            // else @if(...)
+            p.next_token();
+            if !is_cfg_directive(p) {
+                p.add_error("Expected @if or @cfg after else");
+                return ast::empty_statement();
+            }
+            alternative = parse_cfg_statement(p);
+        } else {
+            if !p.expect_peek(token::L_BRACE) {
+                return ast::empty_statement();
+            }
+            alternative = parse_block_statement(p);
+        }
+    }
+
+    ast::Statement::CfgStatement(token, Box::new(condition), Box::new(consequence), Box::new(alternative))
+}
+
+/// What attributes can be put on.
+const ATTRIBUTE_TARGETS: [&str; 5] = [token::FUNCTION, token::ASYNC, token::STRUCT, token::CLASS, token::PUB];
+
+/// Is the current `@` the start of attributes? i.e. `@memoize fn f() {}` or `@route("/x") fn f() {}`
+///
+/// Otherwise it is a macro call. The tokens that are read ahead are put back.
+fn is_attribute(p: &mut Parser) -> bool {
+    if is_cfg_directive(p) {
+        return false;
+    }
+
+    let mut read = vec![];
+    let mut tk = p.peek_token.clone();
+    let result = loop {
+        if tk.typ != token::IDENT {
+            break false;
+        }
+        tk = read_ahead(p, &mut read);
+        // @name.path
+        while tk.typ == token::DOT {
+            read_ahead(p, &mut read);
+            tk = read_ahead(p, &mut read);
+        }
+        // @name(args)
+        if tk.typ == token::L_PAREN {
+            let mut depth = 1;
+            while depth > 0 && tk.typ != token::EOF {
+                tk = read_ahead(p, &mut read);
+                match tk.typ.as_str() {
+                    token::L_PAREN => depth += 1,
+                    token::R_PAREN => depth -= 1,
+                    _ => {}
+                }
+            }
+            tk = read_ahead(p, &mut read);
+        }
+
+        // `pub` has to be followed by a fn, struct or class too.
+        if tk.typ == token::PUB {
+            tk = read_ahead(p, &mut read);
+            break tk.typ != token::PUB && ATTRIBUTE_TARGETS.contains(&tk.typ.as_str());
+        }
+        if tk.typ != token::MACRO_SYMBOL {
+            break ATTRIBUTE_TARGETS.contains(&tk.typ.as_str());
+        }
+        tk = read_ahead(p, &mut read);
+    };
+
+    p.l.unread_tokens(read);
+    result
+}
+
+/// Read a token after the peek token, it is kept in `read` so it can be put back.
+fn read_ahead(p: &mut Parser, read: &mut Vec<token::Token>) -> token::Token {
+    let tk = p.l.next_token();
+    read.push(tk.clone());
+    tk
+}
+
+/// Parse `@name`, `@name.path` or `@name(args)` attributes. Ends on the last token of the last attribute.
+fn parse_attributes(p: &mut Parser) -> Vec<ast::Expression> {
+    p.debug_print("parse_attributes");
+    let mut attributes = vec![];
+
+    loop {
+        if !p.expect_peek(token::IDENT) {
+            return vec![];
+        }
+        let mut attribute = parse_identifier_without_macro_check(p, false);
+        while p.peek_token_is(token::DOT) {
+            p.next_token(); // .
+            let token = p.c_token.to_owned();
+            if !p.expect_peek(token::IDENT) {
+                return vec![];
+            }
+            let property = parse_identifier_without_macro_check(p, false);
+            attribute = Expression::DotExpression(token, Box::new(attribute), Box::new(property));
+        }
+        if p.peek_token_is(token::L_PAREN) {
+            p.next_token(); // (
+            attribute = parse_call_expression(p, attribute);
+        }
+        attributes.push(attribute);
+
+        if !p.peek_token_is(token::MACRO_SYMBOL) {
+            break;
+        }
+        p.next_token(); // @
+    }
+
+    attributes
+}
+
+/// `@attribute fn`, `@attribute struct`, `@attribute class` or `@attribute pub ...`
+fn parse_attributed_statement(p: &mut Parser) -> ast::Statement {
+    p.debug_print("parse_attributed_statement");
+    let attributes = parse_attributes(p);
+    if attributes.is_empty() {
+        return ast::empty_statement();
+    }
+    p.next_token(); // fn, struct, class or pub
+
+    let stmt = parse_statement(p);
+    add_attributes(p, stmt, attributes)
+}
+
+fn add_attributes(p: &mut Parser, stmt: ast::Statement, attributes: Vec<ast::Expression>) -> ast::Statement {
+    match stmt {
+        ast::Statement::StructStatement(tk, name, constructor_vars, mixins, derives, variables, methods, _) => {
+            ast::Statement::StructStatement(
+                tk,
+                name,
+                constructor_vars,
+                mixins,
+                derives,
+                variables,
+                methods,
+                Box::new(attributes),
+            )
+        }
+        ast::Statement::ClassStatement(tk, name, extensions, stmts, _) => {
+            ast::Statement::ClassStatement(tk, name, extensions, stmts, Box::new(attributes))
+        }
+        ast::Statement::ExportStatement(tk, stmt) => {
+            ast::Statement::ExportStatement(tk, Box::new(add_attributes(p, *stmt, attributes)))
+        }
+        ast::Statement::ExpressionStatement(tk, expr) => {
+            let function = add_function_attributes(p, *expr, attributes);
+            if function.is_empty() {
+                return ast::empty_statement();
+            }
+            ast::Statement::ExpressionStatement(tk, Box::new(function))
+        }
+        // the error is already reported.
+        ast::Statement::EmptyStatement => ast::empty_statement(),
+        _ => {
+            p.add_error("Attributes can only be put on a fn, struct or class");
+            ast::empty_statement()
+        }
+    }
+}
+
+fn add_function_attributes(p: &mut Parser, expr: ast::Expression, attributes: Vec<ast::Expression>) -> ast::Expression {
+    match expr {
+        ast::Expression::FunctionLiteral(tk, name, params, return_type, body, type_params, _) => {
+            ast::Expression::FunctionLiteral(tk, name, params, return_type, body, type_params, Box::new(attributes))
+        }
+        ast::Expression::AsyncExpression(tk, function) => {
+            let function = add_function_attributes(p, *function, attributes);
+            if function.is_empty() {
+                return ast::empty_expression();
+            }
+            ast::Expression::AsyncExpression(tk, Box::new(function))
+        }
+        ast::Expression::EmptyExpression => ast::empty_expression(),
+        _ => {
+            p.add_error("Attributes can only be put on a fn, struct or class");
+            ast::empty_expression()
+        }
+    }
+}
+
 fn parse_break_statement(p: &mut Parser) -> ast::Statement {
     p.debug_print("parse_break_statement");
     let token = p.c_token.clone();
+    let label = parse_loop_label(p);
 
-    ast::Statement::BreakStatement(token)
+    ast::Statement::BreakStatement(token, label)
 }
 
 fn parse_continue_statement(p: &mut Parser) -> ast::Statement {
     p.debug_print("parse_continue_statement");
-    ast::Statement::ContinueStatement(p.c_token.clone())
+    let token = p.c_token.clone();
+    let label = parse_loop_label(p);
+
+    ast::Statement::ContinueStatement(token, label)
+}
+
+/// Parse the label of a `break outer` or `continue outer`. The label has to be on the same line.
+fn parse_loop_label(p: &mut Parser) -> Option<String> {
+    if !p.peek_token_is(token::IDENT) || p.peek_token.line_number != p.c_token.line_number {
+        return None;
+    }
+    p.next_token();
+
+    let label = p.c_token.literal.clone();
+    if !p.labels.contains(&label) {
+        p.add_error(format!("No loop with the label {} found.", label).as_str());
+    }
+
+    Some(label)
+}
+
+/// Parse a labeled loop. i.e. `outer: for x in xs {}`
+fn parse_labeled_statement(p: &mut Parser, token: token::Token) -> ast::Statement {
+    p.debug_print("parse_labeled_statement");
+    let label = token.literal.clone();
+    p.next_token(); // the loop
+
+    p.labels.push(label.clone());
+    let stmt = parse_statement(p);
+    p.labels.pop();
+
+    if stmt.is_empty() {
+        return ast::empty_statement();
+    }
+
+    ast::Statement::LabeledStatement(token, label, Box::new(stmt))
 }
 
 fn parse_enum_statement(p: &mut Parser) -> ast::Statement {
@@ -441,6 +839,29 @@ fn parse_enum_statement(p: &mut Parser) -> ast::Statement {
     ast::Statement::EnumStatement(token, name, Box::new(options))
 }
 
+/// Parse a type alias. i.e. `type UserId = int`
+fn parse_type_alias_statement(p: &mut Parser) -> ast::Statement {
+    p.debug_print("parse_type_alias_statement");
+    let token = p.c_token.clone(); // type
+
+    if !p.expect_peek(token::IDENT) {
+        return ast::empty_statement();
+    }
+    let name = p.c_token.literal.clone();
+
+    if !p.expect_peek(token::ASSIGN) {
+        return ast::empty_statement();
+    }
+    p.next_token(); // be on the type
+
+    let alias_type = parse_union_type(p);
+    if alias_type.is_empty() {
+        return ast::empty_statement();
+    }
+
+    ast::Statement::TypeAliasStatement(token, name, Box::new(alias_type))
+}
+
 fn parse_import_statement(p: &mut Parser) -> ast::Statement {
     p.debug_print("parse_import_statement");
     let token = p.c_token.clone();
@@ -561,7 +982,7 @@ fn parse_async_block_statement(p: &mut Parser) -> ast::Statement {
     }
 
     p.next_token(); // {
-    let block = parse_block_statement(p);
+    let block = parse_function_body(p, false);
 
     ast::Statement::AsyncBlockStatement(token, Box::new(block))
 }
@@ -575,7 +996,15 @@ fn parse_var_statement(p: &mut Parser) -> ast::Statement {
     let mut var_type: Option<Box<ast::Expression>> = None;
     // check for type
     if p.peek_token_is(token::COLON) {
-        var_type = Some(Box::new(parse_type(p)));
+        p.next_token(); // the :
+
+        // outer: for ... is a label, not a type.
+        if p.peek_token_is(token::FOR) || p.peek_token_is(token::DO) {
+            return parse_labeled_statement(p, token);
+        }
+
+        p.next_token(); // the type
+        var_type = Some(Box::new(parse_union_type(p)));
     }
 
     if !p.peek_token_is(token::ASSIGN) && !p.peek_token_is(token::TYPE_ASSIGNMENT) {
@@ -656,14 +1085,59 @@ fn parse_for_statement(p: &mut Parser) -> ast::Statement {
         has_paren = true;
     }
 
+    // for await item of stream
+    let await_token = if p.peek_token_is(token::AWAIT) {
+        p.next_token();
+        Some(p.c_token.to_owned())
+    } else {
+        None
+    };
+
     // go to expression
     p.next_token();
 
-    let condition = parse_expression(p, LOWEST);
+    let mut condition = parse_expression(p, LOWEST);
     if condition.is_empty() {
         return ast::empty_statement();
     }
 
+    // for key, value in collection
+    if p.peek_token_is(token::COMMA) {
+        p.next_token(); // the ,
+        p.next_token(); // the value
+        condition = match (condition, parse_expression(p, LOWEST)) {
+            (key @ Expression::Identifier(_, _), Expression::InExpression(tk, value, collection))
+                if matches!(value.as_ref(), Expression::Identifier(_, _)) =>
+            {
+                let pair = Expression::ArrayLiteral(
+                    key.get_token().to_owned(),
+                    Box::new(vec![key, value.as_ref().to_owned()]),
+                );
+                Expression::InExpression(tk, Box::new(pair), collection)
+            }
+            _ => {
+                p.add_error("Expected a `for key, value in collection` loop.");
+                return ast::empty_statement();
+            }
+        };
+    }
+
+    if !is_valid_for_condition(p, &condition) {
+        return ast::empty_statement();
+    }
+
+    if let Some(await_token) = await_token {
+        match &condition {
+            ast::Expression::OfExpression(_, _, _) | ast::Expression::InExpression(_, _, _) => {
+                condition = ast::Expression::AwaitExpression(await_token, Box::new(condition));
+            }
+            _ => {
+                p.add_error("Expected a `item of stream` after for await.");
+                return ast::empty_statement();
+            }
+        }
+    }
+
     if has_paren && !p.expect_peek(token::R_PAREN) {
         return ast::empty_statement();
     }
@@ -677,6 +1151,66 @@ fn parse_for_statement(p: &mut Parser) -> ast::Statement {
     ast::Statement::ForStatement(token.to_owned(), Box::new(condition), Box::new(body))
 }
 
+/// Check that a for loop condition can be compiled, adding a error if it can not.
+fn is_valid_for_condition(p: &mut Parser, condition: &ast::Expression) -> bool {
+    let error = match condition {
+        Expression::InExpression(_, left, right) => match (left.as_ref(), right.as_ref()) {
+            (Expression::Identifier(_, _), Expression::RangeExpression(_, _, end, _))
+                if end.is_empty() =>
+            {
+                Some("A range in a for loop needs a end, i.e. 0..10")
+            }
+            (Expression::Identifier(_, _), _) => None,
+            (Expression::ArrayLiteral(_, _), Expression::RangeExpression(_, _, _, _)) => {
+                Some("Can not loop over the keys and values of a range.")
+            }
+            (Expression::ArrayLiteral(_, _), _) => None,
+            _ => Some("Expected a `for item in collection` loop."),
+        },
+        Expression::OfExpression(_, left, _) => match left.as_ref() {
+            Expression::Identifier(_, _) => None,
+            _ => Some("Expected a `for item of collection` loop."),
+        },
+        Expression::RangeExpression(_, _, _, _) => {
+            Some("Expected a `for i in start..end` loop, the range needs a variable.")
+        }
+        Expression::AssignExpression(_, _, _) => {
+            Some("Can not use a assignment as a for loop condition, did you mean ==?")
+        }
+        _ => None,
+    };
+
+    if let Some(error) = error {
+        p.add_error(error);
+        return false;
+    }
+
+    true
+}
+
+/// Parse a do while loop. i.e. `do { body } while condition`
+fn parse_do_while_statement(p: &mut Parser) -> ast::Statement {
+    p.debug_print("parse_do_while_statement");
+    let token = p.c_token.to_owned(); // do
+
+    if !p.expect_peek(token::L_BRACE) {
+        return ast::empty_statement();
+    }
+    let body = parse_block_statement(p);
+
+    if !p.expect_peek(token::WHILE) {
+        return ast::empty_statement();
+    }
+    p.next_token();
+
+    let condition = parse_expression(p, LOWEST);
+    if condition.is_empty() || !is_valid_for_condition(p, &condition) {
+        return ast::empty_statement();
+    }
+
+    ast::Statement::DoWhileStatement(token, Box::new(body), Box::new(condition))
+}
+
 fn parse_expression(p: &mut Parser, precedence: i64) -> ast::Expression {
     p.debug_print("parse_expression");
     let token_type = p.c_token.typ.clone();
@@ -691,7 +1225,7 @@ fn parse_expression(p: &mut Parser, precedence: i64) -> ast::Expression {
         && precedence < p.peek_precedence()
     {
         let peek_type = p.peek_token.typ.clone();
-        let infix = p.has_infix(&peek_type);
+        let infix = p.has_infix(&peek_type) && !p.peek_token_is_prefix_on_new_line();
         if !infix {
             return left_exp;
         }
@@ -707,9 +1241,15 @@ fn parse_prefix_expression(p: &mut Parser) -> ast::Expression {
     let token = p.c_token.clone();
     let operator = p.c_token.literal.to_owned();
 
+    // ~ ++ and -- only apply to the value right after them.
+    let precedence = match operator.as_str() {
+        token::BITWISE_NOT | token::INCREMENT | token::DECREMENT => PREFIX,
+        _ => LOWEST,
+    };
+
     p.next_token();
 
-    let right = parse_expression(p, LOWEST);
+    let right = parse_expression(p, precedence);
 
     ast::Expression::PrefixExpression(token, operator, Box::new(right))
 }
@@ -767,27 +1307,202 @@ fn parse_type(p: &mut Parser) -> ast::Expression {
 
     // consume the :
     p.next_token();
-    // and go to the identifer
-    if !p.expect_peek(token::IDENT) {
+    // and go to the type
+    p.next_token();
+
+    parse_union_type(p)
+}
+
+/// Parse a union of types. i.e. `int | float | null`
+fn parse_union_type(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_union_type");
+    let token = p.c_token.clone();
+
+    let mut types = vec![parse_optional_type(p)];
+    while p.peek_token_is(token::BITWISE_OR) {
+        p.next_token(); // consume the |
+        p.next_token(); // be on the type
+        types.push(parse_optional_type(p));
+    }
+
+    if types.len() == 1 {
+        return types.pop().unwrap();
+    }
+
+    ast::Expression::UnionType(token, Box::new(types))
+}
+
+/// Parse a type that can be followed by a `?`. i.e. `string?`
+fn parse_optional_type(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_optional_type");
+    let token = p.c_token.clone();
+    let val_type = match p.c_token.typ.as_str() {
+        token::IDENT => parse_type_name(p),
+        token::NULL => ast::Expression::Type(token.clone(), String::from("null")),
+        token::STRING => ast::Expression::LiteralType(token.clone(), Box::new(parse_string_literal(p))),
+        token::INT => ast::Expression::LiteralType(token.clone(), Box::new(parse_integer_literal(p))),
+        token::FLOAT => ast::Expression::LiteralType(token.clone(), Box::new(parse_float_literal(p))),
+        token::TRUE | token::FALSE => {
+            ast::Expression::LiteralType(token.clone(), Box::new(parse_boolean(p)))
+        }
+        _ => {
+            p.add_error(format!("Expected a type, got {} instead", p.c_token.literal).as_str());
+            return ast::Expression::EmptyExpression;
+        }
+    };
+
+    if p.peek_token_is(token::QUESTION_MARK) {
+        p.next_token(); // consume the ?
+        return ast::Expression::OptionalType(token, Box::new(val_type));
+    }
+
+    val_type
+}
+
+/// Parse a type name and its type arguments. i.e. `int`, `Array<T>`, `Map<K, Array<V>>`
+///
+/// Starts on the IDENT.
+fn parse_type_name(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_type_name");
+    let token = p.c_token.clone();
+    let mut name = p.c_token.literal.clone();
+
+    // a type from a imported module. i.e. `models.User`
+    if p.peek_token_is(token::DOT) {
+        p.next_token(); // consume the .
+        if !p.expect_peek(token::IDENT) {
+            return ast::Expression::EmptyExpression;
+        }
+        name = format!("{}.{}", name, p.c_token.literal);
+    }
+
+    if !p.peek_token_is(token::LT) {
+        return ast::Expression::Type(token, name);
+    }
+    p.next_token(); // consume the <
+
+    let mut type_args = vec![];
+    loop {
+        p.next_token(); // be on the type
+        type_args.push(parse_union_type(p));
+
+        if !p.peek_token_is(token::COMMA) {
+            break;
+        }
+        p.next_token(); // consume the ,
+    }
+
+    if !p.expect_peek_closing_angle() {
         return ast::Expression::EmptyExpression;
     }
 
-    ast::Expression::Type(token, p.c_token.literal.clone())
+    ast::Expression::GenericType(token, name, Box::new(type_args))
+}
+
+/// Parse the type paramaters of a function. i.e. `<T, U>`
+///
+/// Starts on the <
+fn parse_type_paramaters(p: &mut Parser) -> Vec<ast::Expression> {
+    p.debug_print("parse_type_paramaters");
+    let mut type_params = vec![];
+
+    loop {
+        if !p.expect_peek(token::IDENT) {
+            return vec![];
+        }
+        type_params.push(ast::Expression::Type(
+            p.c_token.clone(),
+            p.c_token.literal.clone(),
+        ));
+
+        if !p.peek_token_is(token::COMMA) {
+            break;
+        }
+        p.next_token(); // consume the ,
+    }
+
+    if !p.expect_peek(token::GT) {
+        return vec![];
+    }
+
+    type_params
 }
 
 /// parse an integer literal, returns EmptyExpression if not valid.
 fn parse_integer_literal(parser: &mut Parser) -> ast::Expression {
     parser.debug_print("parse_integer_literal");
     let tk = parser.c_token.clone();
+    if !check_number_literal(parser, &tk.literal) {
+        return ast::Expression::EmptyExpression;
+    }
+    let digits = tk.literal.replace('_', "");
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x") | Some("0X") => (16, &digits[2..]),
+        Some("0b") | Some("0B") => (2, &digits[2..]),
+        Some("0o") | Some("0O") => (8, &digits[2..]),
+        _ => (10, digits.as_str()),
+    };
+
     // check is number
-    let is_number = parser.c_token.literal.parse::<i64>().is_ok();
+    let is_number = !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix));
     if !is_number {
         parser.add_error(format!("Epected type INT got {} instead", tk.literal).as_str());
         return ast::Expression::EmptyExpression;
     }
-    let integer = parser.c_token.literal.parse::<i64>().unwrap();
 
-    ast::Expression::IntegerLiteral(tk, integer)
+    match i64::from_str_radix(digits, radix) {
+        Ok(integer) => ast::Expression::IntegerLiteral(tk, integer),
+        Err(_) => {
+            parser.add_error(&format!(
+                "Integer {} does not fit in 64 bits, use a BigInt ({}n) instead",
+                tk.literal, tk.literal
+            ));
+            ast::Expression::EmptyExpression
+        }
+    }
+}
+
+/// Report badly placed separators and exponents without digits. i.e. `0x_ff`, `1__0`, `1_`, `1e`
+fn check_number_literal(p: &mut Parser, literal: &str) -> bool {
+    let number = literal.trim_end_matches('n');
+    let (prefix, digits) = match number.get(..2) {
+        Some("0x" | "0X" | "0b" | "0B" | "0o" | "0O") => number.split_at(2),
+        _ => ("", number),
+    };
+
+    if !prefix.is_empty() && digits.starts_with('_') {
+        p.add_error(&format!("Numeric separator can not come right after {} in {}", prefix, literal));
+        return false;
+    }
+
+    // a separator has to be between two digits.
+    let chars: Vec<char> = digits.chars().collect();
+    let radix = match prefix.to_lowercase().as_str() {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => 10,
+    };
+    for (i, c) in chars.iter().enumerate() {
+        let is_between_digits = i > 0
+            && chars[i - 1].is_digit(radix)
+            && chars.get(i + 1).is_some_and(|next| next.is_digit(radix));
+        if *c == '_' && !is_between_digits {
+            p.add_error(&format!("Numeric separator must be between two digits in {}", literal));
+            return false;
+        }
+    }
+
+    let exponent = match digits.split_once(['e', 'E']) {
+        Some((_, exponent)) if radix == 10 => Some(exponent.trim_start_matches(['-', '+'])),
+        _ => None,
+    };
+    if exponent.is_some_and(str::is_empty) {
+        p.add_error(&format!("Exponent of {} has no digits", literal));
+        return false;
+    }
+
+    true
 }
 
 /// parse a boolean
@@ -868,6 +1583,13 @@ fn parse_if_expression(p: &mut Parser) -> ast::Expression {
 
 fn parse_function_literal(p: &mut Parser) -> ast::Expression {
     p.debug_print("parse_function_literal");
+
+    // fn* is a generator function
+    if p.peek_token_is(token::ASTERISK) {
+        p.next_token(); // the *
+        p.c_token.typ = token::GENERATOR.to_string();
+        p.c_token.literal = "fn*".to_string();
+    }
     let token = p.c_token.clone();
 
     if p.peek_token_is(token::L_PAREN) {
@@ -889,6 +1611,12 @@ fn parse_function_literal(p: &mut Parser) -> ast::Expression {
     p.next_token();
     let name = parse_identifier(p, false);
 
+    let mut type_params = vec![];
+    if p.peek_token_is(token::LT) {
+        p.next_token(); // consume the <
+        type_params = parse_type_paramaters(p);
+    }
+
     if !p.expect_peek(token::L_PAREN) {
         return ast::Expression::EmptyExpression;
     }
@@ -908,7 +1636,7 @@ fn parse_function_literal(p: &mut Parser) -> ast::Expression {
         return ast::Expression::EmptyExpression;
     }
 
-    let body = parse_block_statement(p);
+    let body = parse_function_body(p, token.typ == token::GENERATOR);
     if body.is_empty() {
         return ast::Expression::EmptyExpression;
     }
@@ -919,9 +1647,20 @@ fn parse_function_literal(p: &mut Parser) -> ast::Expression {
         Box::new(parameters),
         var_type,
         Box::new(body),
+        Box::new(type_params),
+        Box::default(),
     )
 }
 
+/// Parse the body of a function, keeping track of whether `yield` is allowed in it.
+fn parse_function_body(p: &mut Parser, is_generator: bool) -> ast::Statement {
+    p.generators.push(is_generator);
+    let body = parse_block_statement(p);
+    p.generators.pop();
+
+    body
+}
+
 fn parse_function_paramaters(p: &mut Parser) -> Vec<ast::Expression> {
     p.debug_print("parse_function_paramaters");
     // starts at (
@@ -965,11 +1704,18 @@ fn parse_lambda_literal(p: &mut Parser) -> ast::Expression {
     // params
     let paramaters = parse_function_paramaters(p);
 
+    if token.typ == token::GENERATOR && !p.peek_token_is(token::L_BRACE) {
+        p.add_error("A generator lambda needs a body, i.e. fn*() { yield 1 }");
+        return ast::Expression::EmptyExpression;
+    }
+
     // If not a brace that is ok...
     if !p.peek_token_is(token::L_BRACE) {
         // try and parse the expression
         p.next_token();
+        p.generators.push(false);
         let body = parse_expression(p, LOWEST);
+        p.generators.pop();
         if body.is_empty() {
             p.add_error("Could not parse One-Line lambda, next statement must be a Expression statement.");
             return ast::Expression::EmptyExpression;
@@ -987,7 +1733,7 @@ fn parse_lambda_literal(p: &mut Parser) -> ast::Expression {
     // Is a brace, go to next
     p.next_token();
 
-    let body = parse_block_statement(p);
+    let body = parse_function_body(p, token.typ == token::GENERATOR);
 
     if body.is_empty() {
         return ast::Expression::EmptyExpression;
@@ -1005,7 +1751,7 @@ fn parse_iife_literal(p: &mut Parser) -> ast::Expression {
     }
 
     // parse block
-    let block = parse_block_statement(p);
+    let block = parse_function_body(p, token.typ == token::GENERATOR);
 
     if block.is_empty() {
         return ast::Expression::EmptyExpression;
@@ -1019,6 +1765,50 @@ fn parse_string_literal(p: &mut Parser) -> ast::Expression {
     ast::Expression::StringLiteral(p.c_token.clone().to_owned(), p.c_token.to_owned().literal)
 }
 
+/// Parse a template string, i.e. "Hello ${name}!"
+///
+/// The tokens of each expression are parsed by their own parser.
+fn parse_template_literal(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_template_literal");
+    let token = p.c_token.clone();
+
+    let mut strings = vec![String::new()];
+    let mut expressions = vec![];
+    for part in token.parts.iter() {
+        match part {
+            token::TemplatePart::Text(text) => strings.last_mut().unwrap().push_str(text),
+            token::TemplatePart::Expression(tokens) => {
+                if tokens.is_empty() {
+                    p.add_error("Expected a expression within ${}.");
+                    return ast::Expression::EmptyExpression;
+                }
+
+                let mut parser = Parser::new(lex::Lex::from_tokens(
+                    tokens.to_owned(),
+                    p.l.current_file.clone(),
+                ));
+                let expression = parse_expression(&mut parser, LOWEST);
+                if !parser.peek_token_is(token::EOF) {
+                    parser.next_token();
+                    parser.add_error(
+                        format!(
+                            "Expected one expression within ${{}} but got {} too.",
+                            parser.c_token.literal
+                        )
+                        .as_str(),
+                    );
+                }
+                p.errors.append(&mut parser.errors);
+
+                expressions.push(expression);
+                strings.push(String::new());
+            }
+        }
+    }
+
+    ast::Expression::TemplateLiteral(token, strings, expressions)
+}
+
 fn parse_comment(p: &mut Parser) -> ast::Expression {
     p.debug_print("parse_comment");
     ast::Expression::CommentExpression(p.c_token.to_owned(), p.c_token.to_owned().literal)
@@ -1154,18 +1944,111 @@ fn parse_await_expression(p: &mut Parser) -> ast::Expression {
     ast::Expression::AwaitExpression(token, Box::new(value))
 }
 
+fn parse_comptime_expression(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_comptime_expression");
+    let token = p.c_token.to_owned();
+
+    // comptime { ... }
+    if p.peek_token_is(token::L_BRACE) {
+        p.next_token();
+        let block = parse_block_statement(p);
+        if block.is_empty() {
+            return ast::Expression::EmptyExpression;
+        }
+        return ast::Expression::ComptimeExpression(token, Box::new(block));
+    }
+
+    // comptime expression
+    p.next_token();
+    let value = parse_expression(p, LOWEST);
+    if value.is_empty() {
+        return value;
+    }
+
+    let body = ast::Statement::BlockStatement(
+        token.to_owned(),
+        Box::new(vec![ast::Statement::ReturnStatement(token.to_owned(), Box::new(value))]),
+    );
+    ast::Expression::ComptimeExpression(token, Box::new(body))
+}
+
+fn parse_yield_expression(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_yield_expression");
+    let token = p.c_token.to_owned();
+
+    if p.generators.last() != Some(&true) {
+        p.add_error("yield can only be used inside a generator, i.e. fn* gen() { yield 1 }");
+    }
+
+    let mut is_delegate = false;
+    if p.peek_token_is(token::ASTERISK) {
+        p.next_token(); // the *
+        is_delegate = true;
+    }
+
+    // a yield without a value, i.e. `x = yield`
+    let ends = p.peek_token_is_eos()
+        || p.peek_token_is(token::EOF)
+        || p.peek_token_is(token::R_PAREN)
+        || p.peek_token_is(token::R_BRACE)
+        || p.peek_token_is(token::R_BRACKET)
+        || p.peek_token_is(token::COMMA)
+        || p.peek_token.line_number != p.c_token.line_number;
+    if ends {
+        if is_delegate {
+            p.add_error("Expected a iterable after yield*");
+            return ast::Expression::EmptyExpression;
+        }
+        return ast::Expression::YieldExpression(
+            token,
+            Box::new(ast::Expression::EmptyExpression),
+            false,
+        );
+    }
+
+    p.next_token();
+    let value = parse_expression(p, LOWEST);
+    if value.is_empty() {
+        return value;
+    }
+
+    ast::Expression::YieldExpression(token, Box::new(value), is_delegate)
+}
+
 fn parse_infix_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
     p.debug_print("parse_infix_expression");
     let token = p.c_token.to_owned();
     let operator = p.c_token.to_owned().literal;
 
-    let precedence = p.cur_precedence();
+    let mut precedence = p.cur_precedence();
+    // ** is right associative, 2 ** 3 ** 2 is 2 ** (3 ** 2)
+    if p.cur_token_is(token::EXPONENT) {
+        precedence -= 1;
+    }
     p.next_token();
     let right = parse_expression(p, precedence);
 
     ast::Expression::InfixExpression(token, Box::new(left), operator, Box::new(right))
 }
 
+fn parse_postfix_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
+    p.debug_print("parse_postfix_expression");
+    let token = p.c_token.to_owned(); // ++ or --
+    let operator = p.c_token.to_owned().literal;
+
+    match left {
+        ast::Expression::Identifier(_, _)
+        | ast::Expression::DotExpression(_, _, _)
+        | ast::Expression::IndexExpression(_, _, _) => {}
+        _ => {
+            p.add_error(format!("Can not use {} on this expression.", operator).as_str());
+            return ast::Expression::EmptyExpression;
+        }
+    }
+
+    ast::Expression::PostfixExpression(token, Box::new(left), operator)
+}
+
 fn parse_call_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
     p.debug_print("parse_call_expression");
     let token = p.c_token.to_owned();
@@ -1214,11 +2097,20 @@ fn parse_dot_expression(p: &mut Parser, left: ast::Expression) -> ast::Expressio
     }
     p.next_token();
 
-    let right = parse_expression(p, LOWEST);
+    // stop at |> so `x.y |> f` pipes `x.y`
+    let right = parse_expression(p, PIPE);
     if right.is_empty() {
         return ast::Expression::EmptyExpression;
     }
 
+    // alias.@macro(...) calls a macro of the module imported as alias.
+    if let (Expression::Identifier(_, alias), Expression::MacroExpression(tk, name, args)) = (&left, &right)
+        && let Expression::Identifier(name_tk, name) = name.as_ref()
+    {
+        let name = Expression::Identifier(name_tk.to_owned(), format!("{}.{}", alias, name));
+        return Expression::MacroExpression(tk.to_owned(), Box::new(name), args.to_owned());
+    }
+
     // If right side is a in expression we have to grab it correctly
     match right {
         Expression::InExpression(tk, in_left, in_right) => {
@@ -1228,12 +2120,141 @@ fn parse_dot_expression(p: &mut Parser, left: ast::Expression) -> ast::Expressio
                 in_right,
             );
         }
+        // and so is a range, i.e. xs.length..0
+        Expression::RangeExpression(tk, start, end, step) => {
+            return Expression::RangeExpression(
+                tk,
+                Box::new(Expression::DotExpression(token, Box::new(left), start)),
+                end,
+                step,
+            );
+        }
         _ => {}
     }
 
     ast::Expression::DotExpression(token, Box::new(left), Box::new(right))
 }
 
+/// Parse `value |> f` into the call `f(value)`.
+///
+/// A `_` argument marks where the value goes, `x |> f(_, 2)` is `f(x, 2)`.
+/// A call without a `_` gets the value as its first argument.
+fn parse_pipe_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
+    p.debug_print("parse_pipe_expression");
+    let token = p.c_token.to_owned(); // |>
+    p.next_token();
+
+    let right = parse_expression(p, PIPE);
+    if right.is_empty() || is_pipe_placeholder(&right) {
+        p.add_error("Expected a function after |>.");
+        return ast::Expression::EmptyExpression;
+    }
+    if let Expression::DotExpression(_, object, _) = &right
+        && is_pipe_placeholder(object)
+    {
+        p.add_error("The _ placeholder can only be a argument of a call.");
+        return ast::Expression::EmptyExpression;
+    }
+
+    pipe_into_call(p, &token, left, right)
+}
+
+/// Put the piped value into the call on the right side of `|>`.
+fn pipe_into_call(
+    p: &mut Parser,
+    token: &token::Token,
+    value: ast::Expression,
+    right: ast::Expression,
+) -> ast::Expression {
+    match right {
+        Expression::CallExpression(tk, callee, args) => {
+            let placeholders = args.iter().filter(|arg| is_pipe_placeholder(arg)).count();
+            if placeholders > 1 {
+                p.add_error("A pipeline can only have one _ placeholder.");
+                return ast::Expression::EmptyExpression;
+            }
+
+            let args = if placeholders == 1 {
+                args.into_iter()
+                    .map(|arg| {
+                        if is_pipe_placeholder(&arg) {
+                            value.to_owned()
+                        } else {
+                            arg
+                        }
+                    })
+                    .collect()
+            } else {
+                let mut with_value = vec![value];
+                with_value.extend(*args);
+                with_value
+            };
+
+            Expression::CallExpression(tk, callee, Box::new(args))
+        }
+        // alias.f(_, 2) or obj.method(_), the call is on the right of the dot.
+        Expression::DotExpression(tk, object, member) if ends_in_call(&member) => {
+            let member = pipe_into_call(p, token, value, *member);
+            Expression::DotExpression(tk, object, Box::new(member))
+        }
+        Expression::Identifier(_, _)
+        | Expression::DotExpression(_, _, _)
+        | Expression::IndexExpression(_, _, _) => {
+            Expression::CallExpression(token.to_owned(), Box::new(right), Box::new(vec![value]))
+        }
+        // x |> fn(y) y + 1 calls the lambda, (fn)(x)
+        _ => {
+            let callee = Expression::GroupedExpression(token.to_owned(), Box::new(right));
+            Expression::CallExpression(token.to_owned(), Box::new(callee), Box::new(vec![value]))
+        }
+    }
+}
+
+fn is_pipe_placeholder(expr: &ast::Expression) -> bool {
+    matches!(expr, Expression::Identifier(_, name) if name == "_")
+}
+
+fn ends_in_call(expr: &ast::Expression) -> bool {
+    match expr {
+        Expression::CallExpression(_, _, _) => true,
+        Expression::DotExpression(_, _, right) => ends_in_call(right),
+        _ => false,
+    }
+}
+
+/// Parse `a?.b`, `a?.[i]` and `f?.()`
+fn parse_optional_chain_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
+    p.debug_print("parse_optional_chain_expression");
+    let token = p.c_token.to_owned(); // ?.
+
+    if p.peek_token_is(token::L_BRACKET) {
+        p.next_token();
+        return match parse_index_expression(p, left) {
+            Expression::IndexExpression(_, left, index) => {
+                Expression::OptionalIndexExpression(token, left, index)
+            }
+            _ => ast::Expression::EmptyExpression,
+        };
+    }
+
+    if p.peek_token_is(token::L_PAREN) {
+        p.next_token();
+        let arguments = parse_call_arguments(p);
+        return Expression::OptionalCallExpression(token, Box::new(left), Box::new(arguments));
+    }
+
+    if !p.expect_peek(token::IDENT) {
+        return ast::Expression::EmptyExpression;
+    }
+
+    let right = parse_expression(p, LOWEST);
+    if right.is_empty() {
+        return ast::Expression::EmptyExpression;
+    }
+
+    Expression::OptionalDotExpression(token, Box::new(left), Box::new(right))
+}
+
 fn parse_dot_if_expression(_p: &mut Parser, _left: ast::Expression) -> ast::Expression {
     ast::Expression::EmptyExpression
 }
@@ -1253,6 +2274,13 @@ fn parse_index_expression(p: &mut Parser, left: ast::Expression) -> ast::Express
         return ast::Expression::EmptyExpression;
     }
 
+    if let Expression::RangeExpression(_, _, _, step) = &index
+        && !step.is_empty()
+    {
+        p.add_error("A slice can not have a step.");
+        return ast::Expression::EmptyExpression;
+    }
+
     if !p.expect_peek(token::R_BRACKET) {
         return ast::Expression::EmptyExpression;
     }
@@ -1265,14 +2293,46 @@ fn parse_range_expression(p: &mut Parser, left: ast::Expression) -> ast::Express
     let token = p.c_token.to_owned();
     let mut right: Expression;
 
-    if p.peek_token_is(token::R_BRACKET) {
+    // no end, i.e. xs[1..] or a for loop without one
+    if p.peek_token_is(token::R_BRACKET) || p.peek_token_is(token::L_BRACE) {
+        if token.typ == token::DOTDOT_EQUALS {
+            p.add_error("A inclusive range needs a end, i.e. 0..=10");
+            return ast::Expression::EmptyExpression;
+        }
         right = Expression::EmptyExpression;
     } else {
         p.next_token();
         right = parse_expression(p, LOWEST);
     }
 
-    ast::Expression::RangeExpression(token, Box::new(left), Box::new(right))
+    // `step` is only a keyword after a range.
+    let mut step = Expression::EmptyExpression;
+    if p.peek_token_is(token::IDENT) && p.peek_token.literal == "step" {
+        p.next_token(); // step
+        p.next_token(); // the size
+        step = parse_expression(p, LOWEST);
+        if step.is_empty() {
+            return ast::Expression::EmptyExpression;
+        }
+
+        // a step that never reaches the end would loop forever.
+        match (left.get_int_literal(), right.get_int_literal(), step.get_int_literal()) {
+            (_, _, Some(0)) => {
+                p.add_error("A range step can not be 0");
+                return ast::Expression::EmptyExpression;
+            }
+            (Some(start), Some(end), Some(size)) if start <= end && size < 0 => {
+                p.add_error(&format!(
+                    "A range step of {} never reaches {} from {}, use a positive step",
+                    size, end, start
+                ));
+                return ast::Expression::EmptyExpression;
+            }
+            _ => {}
+        }
+    }
+
+    ast::Expression::RangeExpression(token, Box::new(left), Box::new(right), Box::new(step))
 }
 
 fn parse_in_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
@@ -1345,9 +2405,36 @@ fn parse_is_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression
     ast::Expression::IsExpression(token, Box::new(left), Box::new(right))
 }
 
+fn parse_cast_expression(p: &mut Parser, left: ast::Expression) -> ast::Expression {
+    p.debug_print("parse_cast_expression");
+    let token = p.c_token.to_owned(); // as
+
+    p.next_token(); // the type
+    let val_type = parse_union_type(p);
+
+    ast::Expression::CastExpression(token, Box::new(left), Box::new(val_type))
+}
+
 fn parse_macro_expression(p: &mut Parser) -> ast::Expression {
     p.debug_print("parse_macro_expression");
-    let token = p.c_token.to_owned(); // ident
+    let token = p.c_token.to_owned(); // ident or @
+
+    // @attribute fn method() {} inside a struct
+    if token.typ == token::MACRO_SYMBOL && is_attribute(p) {
+        let attributes = parse_attributes(p);
+        if attributes.is_empty() {
+            return ast::empty_expression();
+        }
+        p.next_token(); // fn or async
+        let function = parse_expression(p, LOWEST);
+        return add_function_attributes(p, function, attributes);
+    }
+
+    // @name(...) is the same as name!(...)
+    let is_symbol_call = token.typ == token::MACRO_SYMBOL;
+    if is_symbol_call && !p.expect_peek(token::IDENT) {
+        return ast::empty_expression();
+    }
 
     let mut idents = vec![];
     idents.push(parse_identifier_without_macro_check(p, false));
@@ -1358,9 +2445,13 @@ fn parse_macro_expression(p: &mut Parser) -> ast::Expression {
         idents.push(parse_identifier_without_macro_check(p, false));
     }
 
-    if !p.expect_peek(token::BANG) {
+    if !is_symbol_call && !p.expect_peek(token::BANG) {
         return ast::empty_expression();
     }
+    // the ! is optional after a @
+    if is_symbol_call && p.peek_token_is(token::BANG) {
+        p.next_token();
+    }
 
     if !p.expect_peek(token::L_PAREN) {
         return ast::empty_expression();
@@ -1443,7 +2534,7 @@ fn parse_macro_decleration(p: &mut Parser) -> ast::Statement {
             return ast::Statement::EmptyStatement;
         }
 
-        return ast::Statement::MacroStatement(token, Box::new(name), Box::new(args), Box::new(expression), false);
+        return ast::Statement::MacroStatement(token, Box::new(name), Box::new(args), Box::new(expression), false, false);
     }
     // Go to {
     p.next_token();
@@ -1454,7 +2545,8 @@ fn parse_macro_decleration(p: &mut Parser) -> ast::Statement {
         is_hygenic = true;
         p.next_token();
     }
-    let body = parse_block_statement(p);
+    // a macro can be expanded inside a generator, so allow yield in it.
+    let body = parse_function_body(p, true);
 
     // Consume final } if hygenic
     if is_hygenic {
@@ -1464,7 +2556,24 @@ fn parse_macro_decleration(p: &mut Parser) -> ast::Statement {
         p.next_token();
     }
 
-    ast::Statement::MacroStatement(token, Box::new(name), Box::new(args), Box::new(body), is_hygenic)
+    ast::Statement::MacroStatement(token, Box::new(name), Box::new(args), Box::new(body), is_hygenic, false)
+}
+
+/// `proc macro name(args) { ... }`, the body runs at compile time and returns AST.
+fn parse_proc_macro_decleration(p: &mut Parser) -> ast::Statement {
+    p.debug_print("parse_proc_macro_decleration");
+    p.next_token(); // macro
+
+    match parse_macro_decleration(p) {
+        ast::Statement::MacroStatement(token, name, args, body, is_hygenic, _) => {
+            if is_hygenic {
+                p.add_error("A proc macro can not be hygenic");
+                return ast::empty_statement();
+            }
+            ast::Statement::MacroStatement(token, name, args, body, false, true)
+        }
+        stmt => stmt,
+    }
 }
 
 fn parse_struct_statement(p: &mut Parser) -> ast::Statement {
@@ -1511,6 +2620,32 @@ fn parse_struct_statement(p: &mut Parser) -> ast::Statement {
         mixins = Some(Box::new(mixin_names));
     }
 
+    let mut derives: Option<Box<Vec<ast::Expression>>> = None;
+    // `derives` is only a keyword after a struct header.
+    if p.peek_token_is(token::IDENT) && p.peek_token.literal == "derives" {
+        let mut derive_names = vec![];
+        loop {
+            p.next_token(); // consume the derives or ,
+            if !p.expect_peek(token::IDENT) {
+                return ast::empty_statement();
+            }
+            if !DERIVABLES.contains(&p.c_token.literal.as_str()) {
+                p.add_error(&format!(
+                    "Can not derive '{}'. Expected one of: {}",
+                    p.c_token.literal,
+                    DERIVABLES.join(", ")
+                ));
+            }
+            derive_names.push(parse_identifier(p, false));
+
+            if !p.peek_token_is(token::COMMA) {
+                break;
+            }
+        }
+
+        derives = Some(Box::new(derive_names));
+    }
+
     if !p.expect_peek(token::L_BRACE) {
         return ast::empty_statement();
     }
@@ -1525,8 +2660,10 @@ fn parse_struct_statement(p: &mut Parser) -> ast::Statement {
             Box::new(ident),
             constructor_vars,
             mixins,
+            derives,
             Box::new(variables),
             Box::new(methods),
+            Box::default(),
         );
     }
 
@@ -1559,8 +2696,10 @@ fn parse_struct_statement(p: &mut Parser) -> ast::Statement {
                 Box::new(ident),
                 constructor_vars,
                 mixins,
+                derives,
                 Box::new(variables),
                 Box::new(methods),
+                Box::default(),
             );
         }
     }
@@ -1569,6 +2708,7 @@ fn parse_struct_statement(p: &mut Parser) -> ast::Statement {
     if !p.peek_token_is(token::FUNCTION)
         && !p.peek_token_is(token::ASYNC)
         && !p.peek_token_is(token::DOC_COMMENT)
+        && !p.peek_token_is(token::MACRO_SYMBOL)
     {
         return ast::empty_statement();
     }
@@ -1594,8 +2734,10 @@ fn parse_struct_statement(p: &mut Parser) -> ast::Statement {
         Box::new(ident),
         constructor_vars,
         mixins,
+        derives,
         Box::new(variables),
         Box::new(methods),
+        Box::default(),
     )
 }
 
@@ -1644,18 +2786,49 @@ fn parse_new_expression(p: &mut Parser) -> ast::Expression {
 fn parse_float_literal(p: &mut Parser) -> ast::Expression {
     p.debug_print("parse_float_literal");
     let token = p.c_token.to_owned(); // 1.0
+    if !check_number_literal(p, &token.literal) {
+        return ast::empty_expression();
+    }
+    let literal = token.literal.replace('_', "");
     // check is float
-    let is_float = p.c_token.literal.parse::<f64>().is_ok();
+    let is_float = literal.parse::<f64>().is_ok();
     if !is_float {
         p.add_error(format!("Epected type FLOAT got {} instead", token.literal).as_str());
         return ast::empty_expression();
     }
 
-    let float = p.c_token.literal.parse::<f64>().unwrap();
+    let float = literal.parse::<f64>().unwrap();
 
     ast::Expression::FloatLiteral(token, float)
 }
 
+fn parse_bigint_literal(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_bigint_literal");
+    let token = p.c_token.to_owned(); // 10n
+    if !check_number_literal(p, &token.literal) {
+        return ast::empty_expression();
+    }
+    let value = token.literal.trim_end_matches('n').replace('_', "");
+
+    ast::Expression::BigIntLiteral(token, value)
+}
+
+/// Report a token the lexer could not read, i.e. `0o19`, `1.5n` or `§`.
+fn parse_illegal_token(p: &mut Parser) -> ast::Expression {
+    p.debug_print("parse_illegal_token");
+    let literal = p.c_token.literal.clone();
+    if !literal.starts_with(|c: char| c.is_ascii_digit()) {
+        p.add_error(&format!("Illegal token {}", literal));
+    } else {
+        p.add_error(&format!(
+            "Illegal number {}, a digit is out of range for its base or a BigInt is not a whole number",
+            literal
+        ));
+    }
+
+    ast::Expression::EmptyExpression
+}
+
 fn parse_builtin_expression(p: &mut Parser) -> ast::Expression {
     p.debug_print("parse_builtin_expression");
     let token = p.c_token.to_owned(); // builtin
@@ -1792,5 +2965,5 @@ fn parse_class_statement(p: &mut Parser) -> ast::Statement {
         return ast::Statement::EmptyStatement;
     }
 
-    ast::Statement::ClassStatement(token, Box::new(class_name), Box::new(extensions), Box::new(stmts))
+    ast::Statement::ClassStatement(token, Box::new(class_name), Box::new(extensions), Box::new(stmts), Box::default())
 }
\ No newline at end of file
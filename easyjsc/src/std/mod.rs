@@ -11,35 +11,35 @@ class Agent {
     } 
 }"##;
 const DATE: &str = r##"// Get the days between 2 dates 
-macro days_between_dates(d1, d2) {  
+pub macro days_between_dates(d1, d2) {  
     Math.ceil(Math.abs(#d1 - #d2) / (1000 * 60 * 60 * 24))  
 } 
  
 // Get the weekday of a date. 
-macro get_week_day(d) {  
+pub macro get_week_day(d) {  
     #d.toLocaleString('en-US', {weekday: 'long'})  
 } 
  
 // Is a date a weekend? 
-macro is_weekend(d) { 
+pub macro is_weekend(d) { 
     [5,6].indexOf(#d.getDay()) != -1 
 }"##;
 const HTML: &str = r##"// A full HTML DSL using macros 
-macro html(elements) { 
+pub macro html(elements) { 
     "<html> 
         ${#elements} 
     </html>" 
 } 
  
-macro head(elements) { 
+pub macro head(elements) { 
     "<head>${#elements}</head>" 
 } 
  
-macro title(title) { 
+pub macro title(title) { 
     "<title>${#title}</title>" 
 } 
  
-macro body(elements, kwargs) { 
+pub macro body(elements, kwargs) { 
     javascript{ 
         <body style='${#kwargs?.style ?? ""}'> 
             #elements 
@@ -47,11 +47,11 @@ macro body(elements, kwargs) {
     } 
 } 
  
-macro h1(inner, kwargs) { 
+pub macro h1(inner, kwargs) { 
     "<h1 style='${#kwargs.style}'>${#inner}</h1>" 
 } 
  
-macro elements(els) { 
+pub macro elements(els) { 
     javascript{ 
         `${(#els.map(((e) => e)).join('\n'))}` 
     } 
@@ -66,7 +66,7 @@ import 'std' as _
 @const(fs_promises = require('node:fs/promises')) 
  
 // Read a file 
-macro read_file(file_path, encoding, is_async) { 
+pub macro read_file(file_path, encoding, is_async) { 
     fn { 
         if #is_async == true { 
             return ( 
@@ -89,7 +89,7 @@ macro read_file(file_path, encoding, is_async) {
 } 
  
 // Write a file 
-macro write_file(file_path, data, is_async) { 
+pub macro write_file(file_path, data, is_async) { 
     fn { 
         if #is_async == true { 
             return ( 
@@ -119,17 +119,17 @@ macro write_file(file_path, data, is_async) {
 } 
  
 // Check if a file exists (only synchronous) 
-macro file_exists(file_path) { 
+pub macro file_exists(file_path) { 
     fs.existsSync(#file_path) 
 } 
  
 // Check directory exists (only synchronous) 
-macro dir_exists(dir_path) { 
+pub macro dir_exists(dir_path) { 
     @file_exists(#dir_path) 
 } 
  
 // read files in a directory 
-macro read_dir(dir_path, is_async) { 
+pub macro read_dir(dir_path, is_async) { 
     fn { 
         if #is_async == true { 
             return ( 
@@ -150,7 +150,7 @@ macro read_dir(dir_path, is_async) {
 } 
  
 // Make a directory 
-macro make_dir(dir_path, is_async) { 
+pub macro make_dir(dir_path, is_async) { 
     if #is_async == true { 
         fs_promises.mkdir(#dir_path) 
     } else { 
@@ -175,27 +175,27 @@ const MALLOC: &str = r##"native {
     } 
  
 }"##;
-const MATH: &str = r##"macro radians(degrees) { 
+const MATH: &str = r##"pub macro radians(degrees) { 
     javascript{ 
         #degrees * (Math.PI / 180); 
     } 
 } 
  
 // Calculate the percentage in EasyJS. 
-macro calculate_percent(value,total) { 
+pub macro calculate_percent(value,total) { 
     Math.round((#value / #total) * 100) 
 } 
 "##;
 const RANDOM: &str = r##"// EasyJS implementation of random.uniform from Python. 
-macro uniform(a,b) { 
+pub macro uniform(a,b) { 
     Math.random() * (#b - #a + 1) + #a 
 } 
  
-macro choice(array) { 
+pub macro choice(array) { 
     #array[Math.floor(Math.random() * #array.length)] 
 } 
  
-macro normal(mean, std_dev) { 
+pub macro normal(mean, std_dev) { 
     u1 = Math.random() 
     u2 = Math.random() 
     z0 = Math.sqrt(-2.0 * Math.log(u1)) * Math.cos(2.0 * Math.PI * u2) // Box-Muller transform 
@@ -203,44 +203,44 @@ macro normal(mean, std_dev) {
 } 
  
 // Shuffle an array randomly. 
-macro shuffle(arr) { 
+pub macro shuffle(arr) { 
     #arr.slice().sort(fn() { 
         return Math.random() - 0.5 
     }) 
 } 
  
 // Get a random number from min max 
-macro random_number(min, max) {Math.floor(Math.random() * (#max - #min + 1) + #min)} 
+pub macro random_number(min, max) {Math.floor(Math.random() * (#max - #min + 1) + #min)} 
  
 // Get a random hex color 
-macro random_hex_color() { "#${Math.random().toString(16).slice(2, 8).padEnd(6, '0')}"} 
+pub macro random_hex_color() { "#${Math.random().toString(16).slice(2, 8).padEnd(6, '0')}"} 
  
 // Get a Random boolean 
-macro random_bool() { Math.random() >= 0.5} 
+pub macro random_bool() { Math.random() >= 0.5} 
  
 "##;
 const STD: &str = r##"// Get the last element of an array 
-macro last(array) { 
+pub macro last(array) { 
     #array[#array.length - 1] 
 } 
  
-macro print(...msg) { 
+pub macro print(...msg) { 
     console.log(#msg) 
 } 
  
 // Get the first element of an array 
-macro first(array) { 
+pub macro first(array) { 
     #array[0] 
 } 
  
-macro throw(error_msg) { 
+pub macro throw(error_msg) { 
     javascript { 
         throw new Error(#error_msg); 
     } 
 } 
  
 // Try to do an operation. 
-macro try(method, throw) { 
+pub macro try(method, throw) { 
     ___try = #method 
     javascript { 
         try { 
@@ -254,7 +254,7 @@ macro try(method, throw) {
 } 
  
 // Try and catch a operation 
-macro try_catch(method, on_catch) { 
+pub macro try_catch(method, on_catch) { 
     ___try = #method 
     ___catch = #on_catch 
     javascript { 
@@ -267,22 +267,22 @@ macro try_catch(method, on_catch) {
 } 
  
 // Decouple 2 objects. 1 of identifiers, and 1 of matching length/key of values. 
-macro decouple(idents, values) { 
+pub macro decouple(idents, values) { 
     #idents = #values 
 } 
  
 // declare a constant variable  
-macro const(expr) { 
+pub macro const(expr) { 
     javascript { 
         const #expr; 
     } 
 } 
  
-macro run_function(fun) { 
+pub macro run_function(fun) { 
     #fun() 
 } 
  
-macro sleep(ms) { 
+pub macro sleep(ms) { 
     const!(func = fn(ms) { 
         javascript{ 
             return new Promise(resolve => setTimeout(resolve, ms)) 
@@ -293,7 +293,7 @@ macro sleep(ms) {
 } 
  
 // Creates a range 
-macro range(kwargs) { 
+pub macro range(kwargs) { 
     run_function!(fn() { 
         start = #kwargs.start 
         end = #kwargs.end 
@@ -306,14 +306,14 @@ macro range(kwargs) {
 } 
  
 // Flatten a list in JS. 
-macro flatten(list) { 
+pub macro flatten(list) { 
     javascript { 
         [...new Set(#list)]; 
     } 
 } 
  
 // Merge 2 arrays 
-macro merge(arr1, arr2, flatten) { 
+pub macro merge(arr1, arr2, flatten) { 
     narr = [].concat(#arr1, #arr2) 
  
     fn { 
@@ -326,14 +326,14 @@ macro merge(arr1, arr2, flatten) {
 } 
  
 // Reverse a string 
-macro reverse_string(str) { 
+pub macro reverse_string(str) { 
     fn { 
         return #str.split("").reverse().join("") 
     } 
 } 
  
 // Get the EasyJS ASCII 
-macro easyjs_ascii() { 
+pub macro easyjs_ascii() { 
     "    ___       ___       ___       ___            ___       ___    
    /\\  \\     /\\  \\     /\\  \\     /\\__\\          /\\  \\     /\\  \\   
   /::\\  \\   /::\\  \\   /::\\  \\   |::L__L        _\\:\\  \\   /::\\  \\  
@@ -344,7 +344,7 @@ macro easyjs_ascii() {
 } 
  
 // add a to_string method for a enum 
-macro add_to_string_to_enum(enum_name) { 
+pub macro add_to_string_to_enum(enum_name) { 
     /// Convert #enum_name to a String representation. 
     fn #enum_name_to_string(val) { 
         keys = Object.keys(#enum_name) 
@@ -360,55 +360,55 @@ macro add_to_string_to_enum(enum_name) {
 } 
  
 // Call Object.keys on a object 
-macro keys(object) { 
+pub macro keys(object) { 
     Object.keys(#object) 
 } 
  
 // Call Object.freeze on a object 
-macro freeze(object) { 
+pub macro freeze(object) { 
     Object.freeze(#object) 
 } 
  
 // Check type 
-macro is_type(variable, type_name) { 
+pub macro is_type(variable, type_name) { 
     typeof(#variable) == #type_name 
 } 
  
 // log error 
-macro log_error(err) { 
+pub macro log_error(err) { 
     console.error(#err) 
 } 
  
 // JS comment 
-macro jsc(comment) { 
+pub macro jsc(comment) { 
     javascript{ 
         // #comment 
     } 
 } 
  
 // Null Dot operation 
-macro null_dot(object, field_or_method) { 
+pub macro null_dot(object, field_or_method) { 
     javascript{ 
         #object?.#field_or_method 
     } 
 } 
  
 // Is null 
-macro is_null(object) { 
+pub macro is_null(object) { 
     javascript{ 
         (#object === null) 
     } 
 } 
  
 // Is undefined 
-macro is_undefined(object) { 
+pub macro is_undefined(object) { 
     javascript{ 
         (#object === undefined) 
     } 
 } 
  
 // Is null or undefined 
-macro is_null_undefined(object) { 
+pub macro is_null_undefined(object) { 
     javascript{ 
         (#object === null || #object === undefined) 
     } 
@@ -416,7 +416,7 @@ macro is_null_undefined(object) {
 const STRINGS: &str = r##"// String manipulations 
  
 // Capitalize a string 
-macro make_capital(str) { 
+pub macro make_capital(str) { 
     "${#str.charAt(0).toUpperCase()}${#str.slice(1)}" 
 }"##;
 const SYS: &str = r##"// copywright of easyjs 
@@ -429,13 +429,13 @@ import 'std' as _
 @const(file_name = process.argv[1]) 
  
 /// Execute a shell command. 
-macro exec(command) { 
+pub macro exec(command) { 
     (async fn() { 
         err = null 
         stdout = null 
         stderr = null 
         // use exec 
-        if ___runtime == 'deno' { 
+        @if(target == "deno") { 
             split_command = #command.split(' ') 
             @const(command = new Deno.command(split_command[0], { 
                 args: [ 
@@ -0,0 +1,1452 @@
+// The easyjs (non native) type checker.
+// This is a opt-in pass (--strict-types) that runs after transpilation so that the
+// Namespace is already filled with every function, struct and variable.
+//
+// Untyped code is treated as `any` (StrongValType::None) and is never reported.
+
+use std::collections::HashMap;
+
+use crate::compiler::namespaces::{Function, Namespace, Struct, Variable};
+use crate::errors::{
+    type_argument_mismatch, type_mismatch, type_possibly_null, type_unknown_struct_member,
+    type_unsupported_operation, type_wrong_number_of_arguments,
+};
+use crate::lexer::token::{self, Token};
+use crate::parser::ast::{Expression, Statement};
+use crate::typechecker::{
+    StrongValType, bind_type_params, get_literal_type, get_param_type_by_expression_ej,
+    get_string_rep_of_type, is_array_type, is_nullable, make_union, remove_null, typeof_name,
+    widen,
+};
+
+/// Check the types of a easyjs program.
+///
+/// Returns a list of diagnostics. Empty if everything checks out.
+pub fn check_types(
+    statements: &[Statement],
+    namespace: &Namespace,
+    modules: &[Namespace],
+) -> Vec<String> {
+    let mut checker = TypeChecker::new(namespace, modules);
+    checker.check_program(statements);
+    checker.diagnostics
+}
+
+/// A variable known to the checker.
+#[derive(Debug, Clone)]
+struct ScopedVariable {
+    /// The current type, narrowed by `if` checks.
+    val_type: StrongValType,
+    /// The type written by the user. Only declared types are enforced on reassignment.
+    declared_type: Option<StrongValType>,
+}
+
+/// Variables narrowed by a condition.
+type Narrowing = Vec<(String, StrongValType)>;
+
+/// Minimum and maximum (None for spread) number of arguments.
+type Arity = (usize, Option<usize>);
+
+pub struct TypeChecker<'a> {
+    /// The namespace of the program being checked.
+    namespace: &'a Namespace,
+    /// All imported namespaces.
+    modules: &'a [Namespace],
+    /// Variable scopes.
+    scopes: Vec<HashMap<String, ScopedVariable>>,
+    /// The return types of the functions we are currently in.
+    return_types: Vec<StrongValType>,
+    /// Arity of the functions and structs declared in this program.
+    arities: HashMap<String, Arity>,
+    /// Async functions declared in this program. Calling them returns a Promise.
+    async_functions: Vec<String>,
+    /// Fields that struct methods add with `self.field = value`.
+    dynamic_fields: HashMap<String, Vec<String>>,
+    /// Type diagnostics
+    pub diagnostics: Vec<String>,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(namespace: &'a Namespace, modules: &'a [Namespace]) -> Self {
+        TypeChecker {
+            namespace,
+            modules,
+            scopes: vec![HashMap::new()],
+            return_types: vec![],
+            arities: HashMap::new(),
+            async_functions: vec![],
+            dynamic_fields: HashMap::new(),
+            diagnostics: vec![],
+        }
+    }
+
+    /// Check every statement of the program.
+    pub fn check_program(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.collect_declarations(stmt);
+        }
+        for stmt in statements {
+            self.check_statement(stmt);
+        }
+    }
+
+    /// Collect arity and async-ness of top level functions and structs.
+    fn collect_declarations(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::ExportStatement(_, stmt) => self.collect_declarations(stmt),
+            Statement::ExpressionStatement(_, expr) => match expr.as_ref() {
+                Expression::FunctionLiteral(_, name, params, _, _, _, _) => {
+                    if let Expression::Identifier(_, name) = name.as_ref() {
+                        self.arities.insert(name.to_owned(), get_arity(params));
+                    }
+                }
+                Expression::AsyncExpression(_, function) => {
+                    if let Expression::FunctionLiteral(_, name, params, _, _, _, _) = function.as_ref()
+                        && let Expression::Identifier(_, name) = name.as_ref()
+                    {
+                        self.arities.insert(name.to_owned(), get_arity(params));
+                        self.async_functions.push(name.to_owned());
+                    }
+                }
+                _ => {}
+            },
+            Statement::StructStatement(_, name, constructor_vars, _, _, _, methods, _) => {
+                if let Expression::Identifier(_, name) = name.as_ref() {
+                    let params = constructor_vars.to_owned().unwrap_or_default();
+                    self.arities.insert(name.to_owned(), get_arity(&params));
+
+                    let mut fields = vec![];
+                    for method in methods.iter() {
+                        collect_self_assignments_expr(method, &mut fields);
+                    }
+                    let struct_name = self.namespace.get_obj_name(name);
+                    self.dynamic_fields.insert(struct_name, fields);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn remove_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Declare a variable in the current scope.
+    fn declare(&mut self, name: &str, val_type: StrongValType, declared_type: Option<StrongValType>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(
+                name.to_owned(),
+                ScopedVariable {
+                    val_type,
+                    declared_type,
+                },
+            );
+        }
+    }
+
+    /// Assign to a variable. Declared types are enforced and any narrowing is reset.
+    fn assign(&mut self, tk: &Token, name: &str, value_type: &StrongValType) {
+        match self.lookup(name).and_then(|var| var.declared_type) {
+            Some(declared) => {
+                self.expect(tk, &declared, value_type);
+                for scope in self.scopes.iter_mut().rev() {
+                    if let Some(var) = scope.get_mut(name) {
+                        var.val_type = declared;
+                        break;
+                    }
+                }
+            }
+            None => self.declare(name, widen(value_type), None),
+        }
+    }
+
+    /// Find a variable in any scope, innermost first.
+    fn lookup(&self, name: &str) -> Option<ScopedVariable> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(var) = scope.get(name) {
+                return Some(var.to_owned());
+            }
+        }
+
+        let name = self.namespace.get_obj_name(&name.to_string());
+        self.namespace
+            .variables
+            .iter()
+            .find(|var| var.name == name)
+            .map(|var| ScopedVariable {
+                val_type: var.val_type.to_owned(),
+                declared_type: match var.val_type {
+                    StrongValType::None => None,
+                    _ => Some(var.val_type.to_owned()),
+                },
+            })
+    }
+
+    fn find_function(&self, name: &str) -> Option<&'a Function> {
+        let name = self.namespace.get_obj_name(&name.to_string());
+        self.namespace.functions.iter().find(|f| f.name == name)
+    }
+
+    /// Find a struct by name, or by its namespaced name when it comes from a module.
+    fn find_struct(&self, name: &str) -> Option<&'a Struct> {
+        let local_name = self.namespace.get_obj_name(&name.to_string());
+        self.namespace
+            .structs
+            .iter()
+            .find(|s| s.name == local_name)
+            .or_else(|| {
+                self.modules
+                    .iter()
+                    .flat_map(|m| m.structs.iter())
+                    .find(|s| s.name == name)
+            })
+    }
+
+    /// Is `name` a enum of this program, and not shadowed by a variable?
+    fn find_enum(&self, name: &str) -> Option<String> {
+        let full_name = self.namespace.get_obj_name(&name.to_string());
+        if self.lookup(name).is_none() && self.namespace.enums.contains(&full_name) {
+            Some(full_name)
+        } else {
+            None
+        }
+    }
+
+    /// Get the type of a type expression, resolved through the namespace.
+    fn type_of(&self, expr: &Expression) -> StrongValType {
+        self.namespace
+            .resolve_type(&get_param_type_by_expression_ej(expr), self.modules)
+    }
+
+    fn find_module(&self, alias: &str) -> Option<&'a Namespace> {
+        self.modules.iter().find(|m| m.has_name(&alias.to_string()))
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::VariableStatement(tk, name, var_type, value, _) => {
+                self.check_var_stmt(tk, name, var_type, value)
+            }
+            Statement::ExpressionStatement(_, expr) => {
+                self.infer(expr);
+            }
+            Statement::ReturnStatement(tk, value) => {
+                let value_type = self.infer(value);
+                if let Some(expected) = self.return_types.last().cloned() {
+                    self.expect(tk, &expected, &value_type);
+                }
+            }
+            Statement::BlockStatement(_, stmts) => {
+                self.add_scope();
+                for stmt in stmts.iter() {
+                    self.check_statement(stmt);
+                }
+                self.remove_scope();
+            }
+            Statement::ForStatement(_, condition, body) => {
+                self.add_scope();
+                self.check_for_condition(condition);
+                self.check_statement(body);
+                self.remove_scope();
+            }
+            Statement::DoWhileStatement(_, body, condition) => {
+                self.add_scope();
+                self.check_statement(body);
+                self.remove_scope();
+                self.infer(condition);
+            }
+            Statement::LabeledStatement(_, _, stmt) => self.check_statement(stmt),
+            Statement::StructStatement(_, name, constructor_vars, _, _, variables, methods, _) => {
+                let constructor_vars = constructor_vars.as_deref().map(Vec::as_slice).unwrap_or_default();
+                self.check_struct(name, constructor_vars, variables, methods);
+            }
+            Statement::ExportStatement(_, stmt) => self.check_statement(stmt),
+            Statement::AsyncBlockStatement(_, block) => self.check_statement(block),
+            Statement::MatchStatement(_, expr, arms) => {
+                self.infer(expr);
+                for (condition, body) in arms.iter() {
+                    self.infer(condition);
+                    self.check_statement(body);
+                }
+            }
+            Statement::ClassStatement(_, _, _, stmts, _) => {
+                self.add_scope();
+                for stmt in stmts.iter() {
+                    self.check_statement(stmt);
+                }
+                self.remove_scope();
+            }
+            _ => {}
+        }
+    }
+
+    fn check_var_stmt(
+        &mut self,
+        tk: &Token,
+        name: &Expression,
+        var_type: &Option<Box<Expression>>,
+        value: &Expression,
+    ) {
+        let name = match name {
+            Expression::Identifier(_, name) => name.to_owned(),
+            _ => return,
+        };
+        let value_type = self.infer(value);
+
+        if let Some(var_type) = var_type {
+            let declared = self.type_of(var_type);
+            self.expect(tk, &declared, &value_type);
+            self.declare(&name, declared.to_owned(), Some(declared));
+            return;
+        }
+
+        self.assign(tk, &name, &value_type);
+    }
+
+    /// Type the loop variable of `for x in y`.
+    fn check_for_condition(&mut self, condition: &Expression) {
+        match condition {
+            Expression::InExpression(_, left, right) => {
+                let iter_type = self.infer(right);
+                let item_type = match right.as_ref() {
+                    Expression::RangeExpression(_, _, _, _) => StrongValType::Int,
+                    _ => get_item_type(&iter_type),
+                };
+                match left.as_ref() {
+                    Expression::Identifier(_, name) => self.declare(name, item_type, None),
+                    // for key, value in collection
+                    Expression::ArrayLiteral(_, pair) => {
+                        let key_type = match widen(&iter_type) {
+                            StrongValType::Generic(name, _) if name == "Array" => StrongValType::Int,
+                            StrongValType::Array => StrongValType::Int,
+                            _ => StrongValType::None,
+                        };
+                        if let [Expression::Identifier(_, key), Expression::Identifier(_, value)] =
+                            pair.as_slice()
+                        {
+                            self.declare(key, key_type, None);
+                            self.declare(value, item_type, None);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Expression::OfExpression(_, left, right) => {
+                self.infer(right);
+                if let Expression::Identifier(_, name) = left.as_ref() {
+                    self.declare(name, StrongValType::String, None);
+                }
+            }
+            // for await item of stream
+            Expression::AwaitExpression(_, iterable) => match iterable.as_ref() {
+                Expression::InExpression(_, left, right) | Expression::OfExpression(_, left, right) => {
+                    let item_type = get_item_type(&self.infer(right));
+                    if let Expression::Identifier(_, name) = left.as_ref() {
+                        self.declare(name, item_type, None);
+                    }
+                }
+                _ => {
+                    self.infer(condition);
+                }
+            },
+            _ => {
+                self.infer(condition);
+            }
+        }
+    }
+
+    fn check_struct(
+        &mut self,
+        name: &Expression,
+        constructor_vars: &[Expression],
+        variables: &[Statement],
+        methods: &[Expression],
+    ) {
+        let struct_name = match name {
+            Expression::Identifier(_, name) => name.to_owned(),
+            _ => return,
+        };
+
+        self.add_scope();
+        let this_type = StrongValType::Custom(self.namespace.get_obj_name(&struct_name));
+        self.declare("this", this_type.to_owned(), Some(this_type));
+
+        for var in constructor_vars {
+            self.check_paramater(var, &[]);
+        }
+        for var in variables {
+            if let Statement::VariableStatement(tk, _, Some(var_type), value, _) = var {
+                let declared = self.type_of(var_type);
+                let value_type = self.infer(value);
+                self.expect(tk, &declared, &value_type);
+            }
+        }
+        for method in methods {
+            match method {
+                Expression::FunctionLiteral(..) => self.check_function(method),
+                Expression::AsyncExpression(_, function) => self.check_function(function),
+                _ => {}
+            }
+        }
+
+        self.remove_scope();
+    }
+
+    /// Check a function body against its paramaters and return type.
+    fn check_function(&mut self, function: &Expression) {
+        if let Expression::FunctionLiteral(tk, _, params, return_type, body, type_params, _) = function {
+            let type_params: Vec<String> = type_params
+                .iter()
+                .filter_map(|type_param| match type_param {
+                    Expression::Type(_, name) => Some(name.to_owned()),
+                    _ => None,
+                })
+                .collect();
+
+            self.add_scope();
+            for param in params.iter() {
+                self.check_paramater(param, &type_params);
+            }
+
+            // the return value of a generator is not what it's called for.
+            let return_type = if tk.typ == token::GENERATOR {
+                StrongValType::None
+            } else {
+                self.type_of(return_type)
+            };
+            self.return_types
+                .push(bind_type_params(&return_type, &type_params));
+            self.check_statement(body);
+            self.return_types.pop();
+            self.remove_scope();
+        }
+    }
+
+    /// Declare a function paramater in the current scope.
+    fn check_paramater(&mut self, param: &Expression, type_params: &[String]) {
+        match param {
+            // self is already declared.
+            Expression::Identifier(_, name) if name != "this" => {
+                self.declare(name, StrongValType::None, None);
+            }
+            Expression::IdentifierWithType(_, name, var_type) => {
+                let declared =
+                    bind_type_params(&self.type_of(var_type), type_params);
+                self.declare(name, declared.to_owned(), Some(declared));
+            }
+            Expression::AssignExpression(tk, left, default_value) => {
+                self.check_paramater(left, type_params);
+                let default_type = self.infer(default_value);
+                if let Expression::IdentifierWithType(_, name, _) = left.as_ref()
+                    && let Some(var) = self.lookup(name)
+                {
+                    self.expect(tk, &var.val_type, &default_type);
+                }
+            }
+            Expression::SpreadExpression(_, inner) => {
+                if let Expression::Identifier(_, name) = inner.as_ref() {
+                    self.declare(name, StrongValType::Array, None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Infer the type of a expression, reporting any mismatches along the way.
+    fn infer(&mut self, expr: &Expression) -> StrongValType {
+        match expr {
+            Expression::IntegerLiteral(_, _)
+            | Expression::FloatLiteral(_, _)
+            | Expression::StringLiteral(_, _)
+            | Expression::Boolean(_, _) => get_literal_type(expr).unwrap_or(StrongValType::None),
+            Expression::NullExpression(_) => StrongValType::Null,
+            Expression::TemplateLiteral(_, _, expressions) => {
+                for expression in expressions {
+                    self.infer(expression);
+                }
+                StrongValType::String
+            }
+            Expression::ArrayLiteral(_, items) => {
+                let item_types: Vec<StrongValType> =
+                    items.iter().map(|item| widen(&self.infer(item))).collect();
+                match item_types.first() {
+                    Some(first)
+                        if *first != StrongValType::None
+                            && item_types.iter().all(|t| t == first) =>
+                    {
+                        StrongValType::Generic("Array".to_string(), vec![first.to_owned()])
+                    }
+                    _ => StrongValType::Array,
+                }
+            }
+            Expression::Identifier(_, name) => self
+                .lookup(name)
+                .map(|var| var.val_type)
+                .unwrap_or(StrongValType::None),
+            Expression::IdentifierWithType(_, _, var_type) => {
+                self.type_of(var_type)
+            }
+            Expression::PrefixExpression(tk, op, right) => {
+                let right_type = widen(&self.infer(right));
+                match op.as_str() {
+                    "-" | "+" | "++" | "--" | "~" => {
+                        if !is_numeric_or_any(&right_type) {
+                            self.diagnostics.push(type_unsupported_operation(
+                                tk,
+                                "",
+                                op,
+                                &type_name(&right_type),
+                            ));
+                            StrongValType::None
+                        } else if op == "~" {
+                            StrongValType::Int
+                        } else {
+                            right_type
+                        }
+                    }
+                    "!" => StrongValType::Bool,
+                    _ => StrongValType::None,
+                }
+            }
+            Expression::PostfixExpression(tk, left, op) => {
+                let left_type = widen(&self.infer(left));
+                if !is_numeric_or_any(&left_type) {
+                    self.diagnostics.push(type_unsupported_operation(
+                        tk,
+                        &type_name(&left_type),
+                        op,
+                        "",
+                    ));
+                    StrongValType::None
+                } else {
+                    left_type
+                }
+            }
+            Expression::NotExpression(_, right) => {
+                self.infer(right);
+                StrongValType::Bool
+            }
+            Expression::InfixExpression(tk, left, op, right) => {
+                let left_type = self.infer(left);
+                let right_type = self.infer(right);
+                let result = self.infer_infix(tk, &left_type, op, &right_type);
+
+                // compound assignments must keep the declared type.
+                if op.ends_with('=') && !is_comparison(op)
+                    && let Expression::Identifier(_, name) = left.as_ref()
+                    && let Some(declared) = self.lookup(name).and_then(|var| var.declared_type)
+                {
+                    self.expect(tk, &declared, &result);
+                }
+                result
+            }
+            Expression::AndExpression(_, left, right) | Expression::OrExpression(_, left, right) => {
+                let left_type = widen(&self.infer(left));
+                let right_type = widen(&self.infer(right));
+                if left_type == StrongValType::Bool && right_type == StrongValType::Bool {
+                    StrongValType::Bool
+                } else {
+                    StrongValType::None
+                }
+            }
+            Expression::DefaultIfNullExpression(_, left, right) => {
+                let left_type = self.infer(left);
+                let right_type = self.infer(right);
+                match left_type {
+                    StrongValType::None => right_type,
+                    _ if is_nullable(&left_type) => {
+                        make_union(vec![remove_null(&left_type), right_type])
+                    }
+                    _ => left_type,
+                }
+            }
+            Expression::GroupedExpression(_, inner) => self.infer(inner),
+            Expression::CallExpression(tk, callee, args) => self.check_call(tk, callee, args),
+            Expression::DotExpression(tk, left, right) => self.check_dot(tk, left, right),
+            Expression::OptionalDotExpression(_, left, right) => {
+                if let Expression::Identifier(_, alias) = left.as_ref()
+                    && self.lookup(alias).is_none()
+                    && let Some(module) = self.find_module(alias)
+                {
+                    return self.check_module_member(module, right);
+                }
+                let left_type = self.infer(left);
+                let member_type = self.check_member(&remove_null(&left_type), right);
+                propagate_null(&left_type, member_type)
+            }
+            Expression::OptionalIndexExpression(_, left, index) => {
+                let left_type = self.infer(left);
+                self.infer(index);
+                propagate_null(&left_type, get_item_type(&remove_null(&left_type)))
+            }
+            Expression::OptionalCallExpression(_, callee, args) => {
+                self.infer(callee);
+                self.infer_all(args);
+                StrongValType::None
+            }
+            Expression::IndexExpression(_, left, index) => {
+                let left_type = self.infer(left);
+                self.infer(index);
+                get_item_type(&left_type)
+            }
+            Expression::AssignExpression(tk, left, right) => {
+                let right_type = self.infer(right);
+                if let Expression::Identifier(_, name) = left.as_ref() {
+                    self.assign(tk, name, &right_type);
+                } else {
+                    self.infer(left);
+                }
+                right_type
+            }
+            Expression::FunctionLiteral(..) => {
+                self.check_function(expr);
+                StrongValType::None
+            }
+            Expression::LambdaLiteral(_, params, body) => {
+                self.add_scope();
+                for param in params.iter() {
+                    self.check_paramater(param, &[]);
+                }
+                self.return_types.push(StrongValType::None);
+                self.check_statement(body);
+                self.return_types.pop();
+                self.remove_scope();
+                StrongValType::None
+            }
+            Expression::AsyncExpression(_, inner) => {
+                self.infer(inner);
+                StrongValType::None
+            }
+            Expression::YieldExpression(_, value, _) => {
+                if !value.is_empty() {
+                    self.infer(value);
+                }
+                StrongValType::None
+            }
+            Expression::AwaitExpression(_, inner) => match self.infer(inner) {
+                StrongValType::Generic(name, type_args) if name == "Promise" => type_args
+                    .first()
+                    .cloned()
+                    .unwrap_or(StrongValType::None),
+                _ => StrongValType::None,
+            },
+            Expression::IfExpression(_, condition, consequence, elseif, else_stmt) => {
+                self.check_if(condition, consequence, elseif, else_stmt);
+                StrongValType::None
+            }
+            Expression::IsExpression(_, left, _) => {
+                self.infer(left);
+                StrongValType::Bool
+            }
+            Expression::CastExpression(_, left, val_type) => {
+                self.infer(left);
+                self.type_of(val_type)
+            }
+            Expression::InExpression(_, left, right) | Expression::OfExpression(_, left, right) => {
+                self.infer(left);
+                self.infer(right);
+                StrongValType::Bool
+            }
+            Expression::RangeExpression(tk, start, end, step) => {
+                for bound in [start, end, step] {
+                    if bound.is_empty() {
+                        continue;
+                    }
+                    let bound_type = widen(&self.infer(bound));
+                    if !is_numeric_or_any(&bound_type) {
+                        self.diagnostics.push(type_unsupported_operation(
+                            tk,
+                            "",
+                            &tk.literal,
+                            &type_name(&bound_type),
+                        ));
+                    }
+                }
+                StrongValType::Generic("Array".to_string(), vec![StrongValType::Int])
+            }
+            Expression::ObjectLiteral(_, pairs) => {
+                for pair in pairs {
+                    if let Some(value) = pair.get(1) {
+                        self.infer(value);
+                    }
+                }
+                StrongValType::None
+            }
+            Expression::NewClassExpression(_, inner) => {
+                self.infer(inner);
+                match inner.as_ref() {
+                    Expression::CallExpression(_, callee, _) => match callee.as_ref() {
+                        Expression::Identifier(_, name)
+                            if self
+                                .namespace
+                                .classes
+                                .contains(&self.namespace.get_obj_name(name)) =>
+                        {
+                            StrongValType::Custom(self.namespace.get_obj_name(name))
+                        }
+                        _ => StrongValType::None,
+                    },
+                    _ => StrongValType::None,
+                }
+            }
+            Expression::IIFE(_, body) | Expression::ComptimeExpression(_, body) => {
+                self.return_types.push(StrongValType::None);
+                self.check_statement(body);
+                self.return_types.pop();
+                StrongValType::None
+            }
+            Expression::SpreadExpression(_, inner) => {
+                self.infer(inner);
+                StrongValType::None
+            }
+            _ => StrongValType::None,
+        }
+    }
+
+    /// Check a if expression, narrowing variables by its condition.
+    fn check_if(
+        &mut self,
+        condition: &Expression,
+        consequence: &Statement,
+        elseif: &Expression,
+        else_stmt: &Statement,
+    ) {
+        self.infer(condition);
+        let (when_true, when_false) = self.narrow(condition);
+
+        self.add_scope();
+        self.apply_narrowing(&when_true);
+        self.check_statement(consequence);
+        self.remove_scope();
+
+        self.add_scope();
+        self.apply_narrowing(&when_false);
+        if !elseif.is_empty() {
+            self.infer(elseif);
+        }
+        self.check_statement(else_stmt);
+        self.remove_scope();
+
+        // `if x == null { return }` narrows x for the rest of the block.
+        if elseif.is_empty() && else_stmt.is_empty() && always_exits(consequence) {
+            self.apply_narrowing(&when_false);
+        }
+    }
+
+    /// Variables narrowed when `condition` is true and when it is false.
+    fn narrow(&self, condition: &Expression) -> (Narrowing, Narrowing) {
+        match condition {
+            Expression::GroupedExpression(_, inner) => self.narrow(inner),
+            Expression::NotExpression(_, inner) => {
+                let (when_true, when_false) = self.narrow(inner);
+                (when_false, when_true)
+            }
+            Expression::PrefixExpression(_, op, inner) if op == "!" => {
+                let (when_true, when_false) = self.narrow(inner);
+                (when_false, when_true)
+            }
+            Expression::AndExpression(_, left, right) => {
+                let (mut when_true, _) = self.narrow(left);
+                when_true.extend(self.narrow(right).0);
+                (when_true, vec![])
+            }
+            Expression::OrExpression(_, left, right) => {
+                let (_, mut when_false) = self.narrow(left);
+                when_false.extend(self.narrow(right).1);
+                (vec![], when_false)
+            }
+            // x != null, null == x
+            Expression::InfixExpression(_, left, op, right) if op == "==" || op == "!=" => {
+                let name = match (left.as_ref(), right.as_ref()) {
+                    (Expression::Identifier(_, name), Expression::NullExpression(_))
+                    | (Expression::NullExpression(_), Expression::Identifier(_, name)) => name,
+                    _ => return (vec![], vec![]),
+                };
+                let val_type = match self.narrowable_type(name) {
+                    Some(val_type) if is_nullable(&val_type) => val_type,
+                    _ => return (vec![], vec![]),
+                };
+                let not_null = vec![(name.to_owned(), remove_null(&val_type))];
+                let null = vec![(name.to_owned(), StrongValType::Null)];
+                if op == "==" {
+                    (null, not_null)
+                } else {
+                    (not_null, null)
+                }
+            }
+            // x is "string", x is int, x is SomeClass
+            Expression::IsExpression(tk, left, right) => {
+                let Expression::Identifier(_, name) = left.as_ref() else {
+                    return (vec![], vec![]);
+                };
+                let Some(val_type) = self.narrowable_type(name) else {
+                    return (vec![], vec![]);
+                };
+                match right.as_ref() {
+                    Expression::StringLiteral(_, kind) => (
+                        vec![(name.to_owned(), narrow_by_typeof(&val_type, kind, true))],
+                        vec![(name.to_owned(), narrow_by_typeof(&val_type, kind, false))],
+                    ),
+                    Expression::Identifier(_, type_name) if self.lookup(type_name).is_none() => {
+                        let check = self.type_of(&Expression::Type(tk.to_owned(), type_name.to_owned()));
+                        // same checks as the transpiler, anything else is a typeof of a variable.
+                        let checkable = match &check {
+                            StrongValType::Custom(class) => self.namespace.classes.contains(class),
+                            StrongValType::Array => true,
+                            _ => typeof_name(&check).is_some_and(|kind| kind != "object"),
+                        };
+                        if !checkable {
+                            return (vec![], vec![]);
+                        }
+                        (
+                            vec![(name.to_owned(), narrow_by_type(&val_type, &check, true))],
+                            vec![(name.to_owned(), narrow_by_type(&val_type, &check, false))],
+                        )
+                    }
+                    _ => (vec![], vec![]),
+                }
+            }
+            // if x?.y { ... }
+            Expression::OptionalDotExpression(_, left, _)
+            | Expression::OptionalIndexExpression(_, left, _)
+            | Expression::OptionalCallExpression(_, left, _) => (self.narrow(left).0, vec![]),
+            // if x { ... }
+            Expression::Identifier(_, name) => match self.narrowable_type(name) {
+                Some(val_type) if is_nullable(&val_type) => {
+                    (vec![(name.to_owned(), remove_null(&val_type))], vec![])
+                }
+                _ => (vec![], vec![]),
+            },
+            _ => (vec![], vec![]),
+        }
+    }
+
+    /// The type of a variable that can be narrowed. `any` is never narrowed.
+    fn narrowable_type(&self, name: &str) -> Option<StrongValType> {
+        self.lookup(name)
+            .map(|var| var.val_type)
+            .filter(|val_type| *val_type != StrongValType::None)
+    }
+
+    /// Narrow variables in the current scope, keeping their declared type.
+    fn apply_narrowing(&mut self, narrowing: &Narrowing) {
+        for (name, val_type) in narrowing {
+            let declared_type = self.lookup(name).and_then(|var| var.declared_type);
+            self.declare(name, val_type.to_owned(), declared_type);
+        }
+    }
+
+    fn infer_infix(
+        &mut self,
+        tk: &Token,
+        left: &StrongValType,
+        op: &str,
+        right: &StrongValType,
+    ) -> StrongValType {
+        if is_comparison(op) {
+            return StrongValType::Bool;
+        }
+        let left = &widen(left);
+        let right = &widen(right);
+
+        let op = op.trim_end_matches('=');
+        match op {
+            "+" => {
+                if *left == StrongValType::String || *right == StrongValType::String {
+                    return StrongValType::String;
+                }
+                self.infer_arithmetic(tk, left, op, right)
+            }
+            "-" | "*" | "/" | "%" | "**" => self.infer_arithmetic(tk, left, op, right),
+            "&" | "|" | "^" | "<<" | ">>" | ">>>" => self.infer_bitwise(tk, left, op, right),
+            "??" => make_union(vec![remove_null(left), right.clone()]),
+            _ => StrongValType::None,
+        }
+    }
+
+    /// Bitwise operators work on numbers and always give back an int.
+    fn infer_bitwise(
+        &mut self,
+        tk: &Token,
+        left: &StrongValType,
+        op: &str,
+        right: &StrongValType,
+    ) -> StrongValType {
+        if !is_numeric_or_any(left) || !is_numeric_or_any(right) {
+            self.diagnostics.push(type_unsupported_operation(
+                tk,
+                &type_name(left),
+                op,
+                &type_name(right),
+            ));
+            return StrongValType::None;
+        }
+        StrongValType::Int
+    }
+
+    fn infer_arithmetic(
+        &mut self,
+        tk: &Token,
+        left: &StrongValType,
+        op: &str,
+        right: &StrongValType,
+    ) -> StrongValType {
+        if !is_numeric_or_any(left) || !is_numeric_or_any(right) {
+            self.diagnostics.push(type_unsupported_operation(
+                tk,
+                &type_name(left),
+                op,
+                &type_name(right),
+            ));
+            return StrongValType::None;
+        }
+
+        match (left, right) {
+            (StrongValType::Int, StrongValType::Int) if op != "/" => StrongValType::Int,
+            (StrongValType::Int | StrongValType::Float, StrongValType::Int | StrongValType::Float) => {
+                StrongValType::Float
+            }
+            _ => StrongValType::None,
+        }
+    }
+
+    fn check_call(
+        &mut self,
+        tk: &Token,
+        callee: &Expression,
+        args: &[Expression],
+    ) -> StrongValType {
+        if let Expression::Identifier(_, name) = callee {
+            // a local variable shadows functions.
+            if self.scopes.iter().any(|scope| scope.contains_key(name)) {
+                self.infer_all(args);
+                return StrongValType::None;
+            }
+            if let Some(function) = self.find_function(name) {
+                let arity = self.arities.get(name).cloned();
+                let return_type = self.check_arguments(tk, name, &function.params, args, arity);
+                if self.async_functions.contains(name) {
+                    return StrongValType::None;
+                }
+                return substitute(&function.return_type, &return_type);
+            }
+            if let Some(strct) = self.find_struct(name) {
+                let arity = self.arities.get(name).cloned();
+                self.check_arguments(tk, name, &strct.params, args, arity);
+                return StrongValType::Custom(strct.name.to_owned());
+            }
+        } else {
+            self.infer(callee);
+        }
+
+        self.infer_all(args);
+        StrongValType::None
+    }
+
+    /// Check call arguments against the paramaters of a function.
+    ///
+    /// Returns the bound type paramaters.
+    fn check_arguments(
+        &mut self,
+        tk: &Token,
+        fn_name: &str,
+        params: &[Variable],
+        args: &[Expression],
+        arity: Option<Arity>,
+    ) -> HashMap<String, StrongValType> {
+        let mut bindings = HashMap::new();
+        let has_spread_or_kwargs = args.iter().any(|arg| {
+            matches!(
+                arg,
+                Expression::SpreadExpression(_, _) | Expression::AssignExpression(_, _, _)
+            )
+        });
+
+        if let Some((min, max)) = arity
+            && !has_spread_or_kwargs
+            && (args.len() < min || max.is_some_and(|max| args.len() > max))
+        {
+            let expected = match max {
+                Some(max) if max == min => min.to_string(),
+                Some(max) => format!("{} to {}", min, max),
+                None => format!("at least {}", min),
+            };
+            self.diagnostics.push(type_wrong_number_of_arguments(
+                tk,
+                fn_name,
+                &expected,
+                args.len(),
+            ));
+        }
+
+        let arg_types: Vec<StrongValType> = args.iter().map(|arg| self.infer(arg)).collect();
+        if has_spread_or_kwargs {
+            return bindings;
+        }
+
+        for (param, arg_type) in params.iter().zip(arg_types.iter()) {
+            bind(&param.val_type, arg_type, &mut bindings);
+        }
+        for (i, (param, arg_type)) in params.iter().zip(arg_types.iter()).enumerate() {
+            let expected = substitute(&param.val_type, &bindings);
+            if !is_assignable(&expected, arg_type) {
+                self.diagnostics.push(type_argument_mismatch(
+                    tk,
+                    fn_name,
+                    i + 1,
+                    &type_name(&expected),
+                    &type_name(arg_type),
+                ));
+            }
+        }
+
+        bindings
+    }
+
+    fn check_dot(&mut self, tk: &Token, left: &Expression, right: &Expression) -> StrongValType {
+        // alias.function() or alias.variable
+        if let Expression::Identifier(_, alias) = left {
+            // Enum.Option
+            if let Some(enum_name) = self.find_enum(alias) {
+                return StrongValType::Custom(enum_name);
+            }
+            if self.lookup(alias).is_none()
+                && let Some(module) = self.find_module(alias)
+            {
+                return self.check_module_member(module, right);
+            }
+        }
+
+        let left_type = self.infer(left);
+        let left_type = self.check_not_null(tk, left, &left_type);
+        self.check_member(&left_type, right)
+    }
+
+    /// Report member access on a nullable value. Returns the type without `null`.
+    fn check_not_null(&mut self, tk: &Token, expr: &Expression, val_type: &StrongValType) -> StrongValType {
+        if is_nullable(val_type) {
+            let name = match expr {
+                Expression::Identifier(_, name) => name.to_owned(),
+                _ => format!("Value of type {}", type_name(val_type)),
+            };
+            self.diagnostics.push(type_possibly_null(tk, &name));
+        }
+        remove_null(val_type)
+    }
+
+    fn check_module_member(&mut self, module: &'a Namespace, right: &Expression) -> StrongValType {
+        match right {
+            Expression::CallExpression(tk, callee, args) => {
+                if let Expression::Identifier(_, name) = callee.as_ref() {
+                    let full_name = module.get_obj_name(name);
+                    if let Some(function) = module.functions.iter().find(|f| f.name == full_name) {
+                        let bindings =
+                            self.check_arguments(tk, name, &function.params, args, None);
+                        return substitute(&function.return_type, &bindings);
+                    }
+                    if let Some(strct) = module.structs.iter().find(|s| s.name == full_name) {
+                        self.check_arguments(tk, name, &strct.params, args, None);
+                        return StrongValType::Custom(strct.name.to_owned());
+                    }
+                }
+                self.infer_all(args);
+                StrongValType::None
+            }
+            Expression::Identifier(_, name) => {
+                let full_name = module.get_obj_name(name);
+                module
+                    .variables
+                    .iter()
+                    .find(|v| v.name == full_name)
+                    .map(|v| v.val_type.to_owned())
+                    .unwrap_or(StrongValType::None)
+            }
+            // alias.Enum.Option
+            Expression::DotExpression(_, left, _) => match left.as_ref() {
+                Expression::Identifier(_, name) if module.enums.contains(&module.get_obj_name(name)) => {
+                    StrongValType::Custom(module.get_obj_name(name))
+                }
+                _ => self.check_member(&StrongValType::None, right),
+            },
+            _ => self.check_member(&StrongValType::None, right),
+        }
+    }
+
+    /// Check `right` as a member of a value of type `object_type`.
+    fn check_member(&mut self, object_type: &StrongValType, right: &Expression) -> StrongValType {
+        let strct = match object_type {
+            StrongValType::Custom(name) => self.find_struct(name),
+            _ => None,
+        };
+
+        match right {
+            Expression::Identifier(tk, name) => {
+                if let Some(strct) = strct {
+                    if let Some(field) = find_field(strct, name) {
+                        return field.val_type.to_owned();
+                    }
+                    if find_method(strct, name).is_none() && !self.is_open_struct(strct, name) {
+                        self.diagnostics
+                            .push(type_unknown_struct_member(tk, &strct.name, name));
+                    }
+                    return StrongValType::None;
+                }
+                if name == "length" && (is_array_type(object_type) || widen(object_type) == StrongValType::String) {
+                    return StrongValType::Int;
+                }
+                StrongValType::None
+            }
+            Expression::CallExpression(tk, callee, args) => {
+                if let (Some(strct), Expression::Identifier(_, name)) = (strct, callee.as_ref()) {
+                    let name = name.as_str();
+                    if let Some(method) = find_method(strct, name) {
+                        let bindings = self.check_arguments(tk, name, &method.params, args, None);
+                        return substitute(&method.return_type, &bindings);
+                    }
+                    if find_field(strct, name).is_none() && !self.is_open_struct(strct, name) {
+                        self.diagnostics
+                            .push(type_unknown_struct_member(tk, &strct.name, name));
+                    }
+                }
+                self.infer_all(args);
+                StrongValType::None
+            }
+            Expression::AssignExpression(tk, left, value) => {
+                let value_type = self.infer(value);
+                let field_type = self.check_member(object_type, left);
+                if strct.is_some() {
+                    self.expect(tk, &field_type, &value_type);
+                }
+                value_type
+            }
+            Expression::DotExpression(tk, left, inner_right) => {
+                let left_type = self.check_member(object_type, left);
+                let left_type = self.check_not_null(tk, left, &left_type);
+                self.check_member(&left_type, inner_right)
+            }
+            Expression::IndexExpression(_, left, index) => {
+                let left_type = self.check_member(object_type, left);
+                self.infer(index);
+                get_item_type(&left_type)
+            }
+            Expression::InfixExpression(tk, left, op, inner_right) => {
+                let left_type = self.check_member(object_type, left);
+                let right_type = self.infer(inner_right);
+                self.infer_infix(tk, &left_type, op, &right_type)
+            }
+            _ => StrongValType::None,
+        }
+    }
+
+    /// Can `name` be on the struct without being declared? i.e. it has mixins or sets `self.name`.
+    fn is_open_struct(&self, strct: &Struct, name: &str) -> bool {
+        !strct.mixins.is_empty()
+            || self
+                .dynamic_fields
+                .get(&strct.name)
+                .is_some_and(|fields| fields.iter().any(|field| field == name))
+    }
+
+    fn infer_all(&mut self, exprs: &[Expression]) {
+        for expr in exprs {
+            self.infer(expr);
+        }
+    }
+
+    /// Report a mismatch if `got` can not be used as `expected`.
+    fn expect(&mut self, tk: &Token, expected: &StrongValType, got: &StrongValType) {
+        if !is_assignable(expected, got) {
+            self.diagnostics
+                .push(type_mismatch(tk, &type_name(expected), &type_name(got)));
+        }
+    }
+}
+
+/// Get the arity of a list of paramaters.
+fn get_arity(params: &[Expression]) -> Arity {
+    let mut min = 0;
+    let mut max = Some(0);
+    for param in params {
+        match param {
+            Expression::SpreadExpression(_, _) => max = None,
+            Expression::AssignExpression(_, _, _) => max = max.map(|max| max + 1),
+            _ => {
+                min += 1;
+                max = max.map(|max| max + 1);
+            }
+        }
+    }
+
+    (min, max)
+}
+
+/// `a?.b` is null when `a` is null.
+fn propagate_null(object_type: &StrongValType, member_type: StrongValType) -> StrongValType {
+    if is_nullable(object_type) {
+        make_union(vec![member_type, StrongValType::Null])
+    } else {
+        member_type
+    }
+}
+
+/// Does this statement always leave the current block? i.e. ends with `return`.
+fn always_exits(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::BlockStatement(_, stmts) => stmts.last().is_some_and(always_exits),
+        Statement::ReturnStatement(_, _)
+        | Statement::BreakStatement(_, _)
+        | Statement::ContinueStatement(_, _) => true,
+        _ => false,
+    }
+}
+
+/// Keep the members of `strong` whose `typeof` is (or is not) `kind`.
+fn narrow_by_typeof(strong: &StrongValType, kind: &str, matches: bool) -> StrongValType {
+    narrow_members(strong, |member| {
+        typeof_name(member).is_none_or(|name| (name == kind) == matches)
+    })
+}
+
+/// Keep the members of `strong` that pass (or fail) `x is check`. i.e. `x is int`, `x is SomeClass`
+fn narrow_by_type(strong: &StrongValType, check: &StrongValType, matches: bool) -> StrongValType {
+    narrow_members(strong, |member| {
+        passes_is_check(member, check).is_none_or(|passes| passes == matches)
+    })
+}
+
+/// Keep the members of `strong` for which `keep` is true.
+fn narrow_members(strong: &StrongValType, keep: impl Fn(&StrongValType) -> bool) -> StrongValType {
+    let members = match strong {
+        StrongValType::Union(types) => types.to_owned(),
+        _ => vec![strong.to_owned()],
+    };
+    let narrowed: Vec<StrongValType> = members.into_iter().filter(&keep).collect();
+
+    // a check that can never pass tells us nothing.
+    if narrowed.is_empty() {
+        strong.to_owned()
+    } else {
+        make_union(narrowed)
+    }
+}
+
+/// Does a value of type `member` pass `x is check`? None if it can not be known.
+///
+/// Classes are checked with `instanceof`, arrays with `Array.isArray` and everything else by `typeof`.
+fn passes_is_check(member: &StrongValType, check: &StrongValType) -> Option<bool> {
+    let member_kind = typeof_name(member)?;
+    match check {
+        StrongValType::Custom(_) if member == check => Some(true),
+        StrongValType::Array if is_array_type(member) => Some(true),
+        StrongValType::Custom(_) | StrongValType::Array => {
+            // a object might be a subclass, null never is.
+            if member_kind == "object" && *member != StrongValType::Null {
+                None
+            } else {
+                Some(false)
+            }
+        }
+        _ => Some(typeof_name(check)? == member_kind),
+    }
+}
+
+/// Collect every `self.field = value` in a struct method.
+fn collect_self_assignments_expr(expr: &Expression, fields: &mut Vec<String>) {
+    match expr {
+        Expression::DotExpression(_, left, right) => {
+            if let (Expression::Identifier(_, this), Expression::AssignExpression(_, field, _)) =
+                (left.as_ref(), right.as_ref())
+                && this == "this"
+                && let Expression::Identifier(_, field) = field.as_ref()
+            {
+                fields.push(field.to_owned());
+            }
+        }
+        Expression::FunctionLiteral(_, _, _, _, body, _, _) => {
+            collect_self_assignments_stmt(body, fields)
+        }
+        Expression::AsyncExpression(_, inner) => collect_self_assignments_expr(inner, fields),
+        Expression::IfExpression(_, _, consequence, elseif, else_stmt) => {
+            collect_self_assignments_stmt(consequence, fields);
+            collect_self_assignments_expr(elseif, fields);
+            collect_self_assignments_stmt(else_stmt, fields);
+        }
+        _ => {}
+    }
+}
+
+fn collect_self_assignments_stmt(stmt: &Statement, fields: &mut Vec<String>) {
+    match stmt {
+        Statement::BlockStatement(_, stmts) => {
+            for stmt in stmts.iter() {
+                collect_self_assignments_stmt(stmt, fields);
+            }
+        }
+        Statement::ExpressionStatement(_, expr) => collect_self_assignments_expr(expr, fields),
+        Statement::ForStatement(_, _, body)
+        | Statement::DoWhileStatement(_, body, _)
+        | Statement::LabeledStatement(_, _, body) => collect_self_assignments_stmt(body, fields),
+        _ => {}
+    }
+}
+
+fn find_field<'s>(strct: &'s Struct, name: &str) -> Option<&'s Variable> {
+    strct
+        .params
+        .iter()
+        .chain(strct.variables.iter())
+        .find(|v| v.name == name)
+}
+
+fn find_method<'s>(strct: &'s Struct, name: &str) -> Option<&'s Function> {
+    strct.methods.iter().find(|m| m.name == name)
+}
+
+/// Bind type paramaters in `param` to the types in `arg`.
+fn bind(param: &StrongValType, arg: &StrongValType, bindings: &mut HashMap<String, StrongValType>) {
+    match (param, arg) {
+        (StrongValType::TypeParam(name), _)
+            if *arg != StrongValType::None && !bindings.contains_key(name) =>
+        {
+            bindings.insert(name.to_owned(), widen(arg));
+        }
+        // `T?` binds T to the non null part.
+        (StrongValType::Union(params), _) => {
+            for param in params.iter().filter(|param| **param != StrongValType::Null) {
+                bind(param, &remove_null(arg), bindings);
+            }
+        }
+        (StrongValType::Generic(name, params), StrongValType::Generic(arg_name, args))
+            if name == arg_name =>
+        {
+            for (param, arg) in params.iter().zip(args.iter()) {
+                bind(param, arg, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace bound type paramaters.
+fn substitute(strong: &StrongValType, bindings: &HashMap<String, StrongValType>) -> StrongValType {
+    match strong {
+        StrongValType::TypeParam(name) => bindings
+            .get(name)
+            .cloned()
+            .unwrap_or(StrongValType::None),
+        StrongValType::Generic(name, type_args) => StrongValType::Generic(
+            name.to_owned(),
+            type_args
+                .iter()
+                .map(|type_arg| substitute(type_arg, bindings))
+                .collect(),
+        ),
+        StrongValType::Union(types) => {
+            make_union(types.iter().map(|member| substitute(member, bindings)).collect())
+        }
+        _ => strong.to_owned(),
+    }
+}
+
+/// Can a value of type `got` be used where `expected` is required?
+///
+/// `any` (None) and unbound type paramaters match everything.
+pub fn is_assignable(expected: &StrongValType, got: &StrongValType) -> bool {
+    match (expected, got) {
+        (
+            StrongValType::None | StrongValType::NotSupported | StrongValType::TypeParam(_),
+            _,
+        )
+        | (
+            _,
+            StrongValType::None | StrongValType::NotSupported | StrongValType::TypeParam(_),
+        ) => true,
+        // a value that can not exist fits anywhere.
+        (_, StrongValType::Never) => true,
+        (_, StrongValType::Union(types)) => types.iter().all(|t| is_assignable(expected, t)),
+        (StrongValType::Union(types), _) => types.iter().any(|t| is_assignable(t, got)),
+        (StrongValType::Literal(_, _), _) => expected == got,
+        (_, StrongValType::Literal(base, _)) => is_assignable(expected, base),
+        (StrongValType::Float, StrongValType::Int) => true,
+        // sized numbers are all a Number in JS, only native code cares about the size.
+        (StrongValType::F64 | StrongValType::U8 | StrongValType::U32, _)
+        | (_, StrongValType::F64 | StrongValType::U8 | StrongValType::U32)
+            if is_number(expected) && is_number(got) =>
+        {
+            true
+        }
+        (StrongValType::Array, _) | (_, StrongValType::Array)
+            if is_array_type(expected) && is_array_type(got) =>
+        {
+            true
+        }
+        (StrongValType::Generic(name, params), StrongValType::Generic(got_name, args)) => {
+            name == got_name
+                && params
+                    .iter()
+                    .zip(args.iter())
+                    .all(|(param, arg)| is_assignable(param, arg))
+        }
+        _ => expected == got,
+    }
+}
+
+/// The type of a item when iterating over or indexing `iter_type`.
+fn get_item_type(iter_type: &StrongValType) -> StrongValType {
+    match widen(iter_type) {
+        StrongValType::Generic(name, type_args)
+            if name == "Array" || name == "Generator" || name == "AsyncGenerator" =>
+        {
+            type_args
+            .first()
+            .cloned()
+            .unwrap_or(StrongValType::None)
+        }
+        StrongValType::String => StrongValType::String,
+        _ => StrongValType::None,
+    }
+}
+
+fn is_numeric_or_any(strong: &StrongValType) -> bool {
+    if let StrongValType::Union(types) = strong {
+        return types.iter().all(|t| matches!(t, StrongValType::Int | StrongValType::Float));
+    }
+    matches!(
+        strong,
+        StrongValType::Int
+            | StrongValType::Float
+            | StrongValType::None
+            | StrongValType::NotSupported
+            | StrongValType::TypeParam(_)
+    )
+}
+
+/// Is a type a JS Number?
+fn is_number(strong: &StrongValType) -> bool {
+    matches!(
+        strong,
+        StrongValType::Int
+            | StrongValType::Float
+            | StrongValType::F64
+            | StrongValType::U8
+            | StrongValType::U32
+    )
+}
+
+fn is_comparison(op: &str) -> bool {
+    matches!(op, "==" | "!=" | "<" | ">" | "<=" | ">=")
+}
+
+/// Name of a type for diagnostics.
+fn type_name(strong: &StrongValType) -> String {
+    let name = get_string_rep_of_type(strong);
+    if name.is_empty() {
+        "any".to_string()
+    } else {
+        name
+    }
+}
@@ -1,3 +1,5 @@
+pub mod checker;
+
 use wasm_encoder::ValType;
 
 use crate::parser::ast::Expression;
@@ -20,15 +22,35 @@ pub enum StrongValType {
     Int,
     /// js: Number, native: f32
     Float,
+    /// js: BigInt, native: i64
+    I64,
+    /// js: Number, native: f64
+    F64,
+    /// js: Number, native: i32 (wraps at 8 bits)
+    U8,
+    /// js: Number, native: i32 (unsigned)
+    U32,
     /// js: bool, native: i32
     Bool,
-    /// Custom schema type. TODO
-    Custom,
+    /// A struct, class or enum by its namespaced name. i.e. `User`, `_models_User`
+    Custom(String),
     NotSupported, // i.e. THROW AN ERROR
     /// js: string, native: i32 (pointer to string in memory)
     String,
     /// js: Array, native: i32 (pointer to array in memory)
     Array,
+    /// A type with type arguments. i.e. `Array<int>`, `Map<string, int>`, `Promise<T>`
+    Generic(String, Vec<StrongValType>),
+    /// A function type paramater. i.e. `T` in `fn first<T>(xs: Array<T>): T`
+    TypeParam(String),
+    /// One of many types. i.e. `int | float`, `string?` is `string | null`
+    Union(Vec<StrongValType>),
+    /// js: null
+    Null,
+    /// A value that can not exist. i.e. a `null` variable after a `!= null` check
+    Never,
+    /// A single value of a type. i.e. `"GET"`, `1`, `true`
+    Literal(Box<StrongValType>, String),
 }
 
 /// String representation of type
@@ -40,17 +62,142 @@ pub fn get_string_rep_of_type(strong: &StrongValType) -> String {
         StrongValType::Float => "float",
         StrongValType::String => "string",
         StrongValType::Int => "int",
+        StrongValType::I64 => "i64",
+        StrongValType::F64 => "f64",
+        StrongValType::U8 => "u8",
+        StrongValType::U32 => "u32",
         StrongValType::Array => "array",
+        StrongValType::Custom(name) => name,
+        StrongValType::TypeParam(name) => name,
+        StrongValType::Generic(name, type_args) => {
+            return format!(
+                "{}<{}>",
+                name,
+                type_args
+                    .iter()
+                    .map(get_string_rep_of_type)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+        }
+        StrongValType::Union(types) => {
+            return types
+                .iter()
+                .map(get_string_rep_of_type)
+                .collect::<Vec<String>>()
+                .join(" | ");
+        }
+        StrongValType::Null => "null",
+        StrongValType::Never => "never",
+        StrongValType::Literal(_, value) => value,
         _ => "" // ?
     }.to_string()
 }
 
+/// Get the literal type of a literal expression. i.e. `"GET"`, `1`, `true`
+pub fn get_literal_type(expr: &Expression) -> Option<StrongValType> {
+    match expr {
+        Expression::StringLiteral(_, value) => Some(StrongValType::Literal(
+            Box::new(StrongValType::String),
+            format!("\"{}\"", value),
+        )),
+        Expression::IntegerLiteral(_, value) => Some(StrongValType::Literal(
+            Box::new(StrongValType::Int),
+            value.to_string(),
+        )),
+        Expression::FloatLiteral(_, value) => Some(StrongValType::Literal(
+            Box::new(StrongValType::Float),
+            value.to_string(),
+        )),
+        Expression::Boolean(_, value) => Some(StrongValType::Literal(
+            Box::new(StrongValType::Bool),
+            value.to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// Make a union out of `types`. Nested unions are flattened and duplicates removed.
+///
+/// A union of one type is just that type, `any` swallows everything and `never` is dropped.
+pub fn make_union(types: Vec<StrongValType>) -> StrongValType {
+    let mut members: Vec<StrongValType> = vec![];
+    let mut has_never = false;
+    for strong in types {
+        let flat = match strong {
+            StrongValType::Union(inner) => inner,
+            StrongValType::None | StrongValType::NotSupported => return StrongValType::None,
+            StrongValType::Never => {
+                has_never = true;
+                continue;
+            }
+            _ => vec![strong],
+        };
+        for member in flat {
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+    }
+
+    // `"GET" | string` is just `string`
+    let bases = members.clone();
+    members.retain(|member| match member {
+        StrongValType::Literal(base, _) => !bases.contains(base),
+        _ => true,
+    });
+
+    match members.len() {
+        0 if has_never => StrongValType::Never,
+        0 => StrongValType::None,
+        1 => members.pop().unwrap(),
+        _ => StrongValType::Union(members),
+    }
+}
+
+/// Can this type be null? i.e. `string?`, `int | null`
+pub fn is_nullable(strong: &StrongValType) -> bool {
+    match strong {
+        StrongValType::Null => true,
+        StrongValType::Union(types) => types.iter().any(is_nullable),
+        _ => false,
+    }
+}
+
+/// Remove `null` from a type. i.e. `string?` becomes `string` and `null` becomes `never`
+pub fn remove_null(strong: &StrongValType) -> StrongValType {
+    match strong {
+        StrongValType::Null => StrongValType::Never,
+        StrongValType::Union(types) => make_union(
+            types
+                .iter()
+                .filter(|t| **t != StrongValType::Null)
+                .cloned()
+                .collect(),
+        ),
+        _ => strong.to_owned(),
+    }
+}
+
+/// Widen a literal type to its base type. i.e. `"GET"` becomes `string`
+pub fn widen(strong: &StrongValType) -> StrongValType {
+    match strong {
+        StrongValType::Literal(base, _) => base.as_ref().to_owned(),
+        StrongValType::Union(types) => make_union(types.iter().map(widen).collect()),
+        _ => strong.to_owned(),
+    }
+}
+
 /// Get the param type for native context.
 pub fn get_param_type_by_string(string: &str) -> StrongValType {
     match string {
-        "int" => StrongValType::Int,
+        "int" | "i32" => StrongValType::Int,
         "bool" => StrongValType::Bool,
-        "float" => StrongValType::Float,
+        "float" | "f32" => StrongValType::Float,
+        "i64" => StrongValType::I64,
+        "f64" => StrongValType::F64,
+        "u8" => StrongValType::U8,
+        "u32" => StrongValType::U32,
         "string" => StrongValType::String,
         "array" => StrongValType::Array,
         "" => StrongValType::None,
@@ -69,6 +216,96 @@ pub fn get_param_type_by_string_ej(string: &str) -> StrongValType {
     }
 }
 
+/// Get the param type for easyjs context from a type expression.
+///
+/// Unknown names become `Custom`, i.e. structs. `Array<T>`, `Map<K, V>` and co become `Generic`.
+pub fn get_param_type_by_expression_ej(expr: &Expression) -> StrongValType {
+    match expr {
+        Expression::Type(_, name) => match get_param_type_by_string(name) {
+            StrongValType::NotSupported => match name.as_str() {
+                "any" => StrongValType::None,
+                "null" => StrongValType::Null,
+                "Array" => StrongValType::Array,
+                _ => StrongValType::Custom(name.to_owned()),
+            },
+            strong => strong,
+        },
+        Expression::GenericType(_, name, type_args) => StrongValType::Generic(
+            name.to_owned(),
+            type_args
+                .iter()
+                .map(get_param_type_by_expression_ej)
+                .collect(),
+        ),
+        Expression::UnionType(_, types) => {
+            make_union(types.iter().map(get_param_type_by_expression_ej).collect())
+        }
+        Expression::OptionalType(_, val_type) => make_union(vec![
+            get_param_type_by_expression_ej(val_type),
+            StrongValType::Null,
+        ]),
+        Expression::LiteralType(_, literal) => {
+            get_literal_type(literal).unwrap_or(StrongValType::None)
+        }
+        Expression::IdentifierWithType(_, _, var_type) => {
+            get_param_type_by_expression_ej(var_type.as_ref())
+        }
+        _ => StrongValType::None,
+    }
+}
+
+/// Replace `Custom` types that are really type paramaters with `TypeParam`.
+pub fn bind_type_params(strong: &StrongValType, type_params: &[String]) -> StrongValType {
+    match strong {
+        StrongValType::Custom(name) if type_params.contains(name) => {
+            StrongValType::TypeParam(name.to_owned())
+        }
+        StrongValType::Generic(name, type_args) => StrongValType::Generic(
+            name.to_owned(),
+            type_args
+                .iter()
+                .map(|type_arg| bind_type_params(type_arg, type_params))
+                .collect(),
+        ),
+        StrongValType::Union(types) => StrongValType::Union(
+            types
+                .iter()
+                .map(|member| bind_type_params(member, type_params))
+                .collect(),
+        ),
+        _ => strong.to_owned(),
+    }
+}
+
+/// Is this a array type? i.e. `array`, `Array<T>`
+pub fn is_array_type(strong: &StrongValType) -> bool {
+    match strong {
+        StrongValType::Array => true,
+        StrongValType::Generic(name, _) => name == "Array",
+        _ => false,
+    }
+}
+
+/// The `typeof` of a type. None if it can not be known.
+pub fn typeof_name(strong: &StrongValType) -> Option<&'static str> {
+    match strong {
+        StrongValType::Int
+        | StrongValType::Float
+        | StrongValType::F64
+        | StrongValType::U8
+        | StrongValType::U32 => Some("number"),
+        StrongValType::I64 => Some("bigint"),
+        StrongValType::String => Some("string"),
+        StrongValType::Bool => Some("boolean"),
+        StrongValType::Null
+        | StrongValType::Array
+        | StrongValType::Generic(_, _)
+        | StrongValType::Custom(_) => Some("object"),
+        StrongValType::Literal(base, _) => typeof_name(base),
+        _ => None,
+    }
+}
+
 /// Get a param type by named expression
 pub fn get_param_type_by_named_expression(param: Expression) -> StrongValType {
     match param {
@@ -87,10 +324,16 @@ pub fn get_val_type_from_strong(strong: &StrongValType) -> Option<ValType> {
     match strong {
         StrongValType::Int => Some(ValType::I32),
         StrongValType::Float => Some(ValType::F32),
+        StrongValType::I64 => Some(ValType::I64),
+        StrongValType::F64 => Some(ValType::F64),
+        StrongValType::U8 => Some(ValType::I32),
+        StrongValType::U32 => Some(ValType::I32),
         StrongValType::Bool => Some(ValType::I32),
         StrongValType::String => Some(ValType::I32),
         StrongValType::Array => Some(ValType::I32),
         StrongValType::None => Some(ValType::I32),
+        // native structs are pointers into memory
+        StrongValType::Custom(_) => Some(ValType::I32),
         // TODO: Implement StrongValType::None
         _ => None,
     }
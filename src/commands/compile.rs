@@ -9,9 +9,13 @@ use easyjs_utils::utils::version;
 /// 
 /// `place_watermark:bool` Does the watermark 'compiled by easjs...' go on?
 /// `file_name: &str` The name of the file.
+/// `strict_types: bool` Run the type checker?
+/// `emit_dts: bool` Emit TypeScript declarations?
+/// `target: &str` The target for `@if(target == "...")`.
+/// `features: &[String]` The features for `@cfg(feature = "...")`.
 /// 
 /// return `String`
-fn compile(input: String, place_watermark: bool, file_name: &str) -> Result<(String, Transpiler), Box<dyn Error>> {
+fn compile(input: String, place_watermark: bool, file_name: &str, strict_types: bool, emit_dts: bool, target: &str, features: &[String]) -> Result<(String, Transpiler), Box<dyn Error>> {
     let lexer = lex::Lex::new_with_file(input, file_name.to_owned());
     let mut parser = par::Parser::new(lexer);
     let program = parser.parse_program();
@@ -24,8 +28,29 @@ fn compile(input: String, place_watermark: bool, file_name: &str) -> Result<(Str
     }
 
     let mut transpiler = Transpiler::new();
+    transpiler.strict_types = strict_types;
+    transpiler.emit_dts = emit_dts;
+    transpiler.target = target.to_string();
+    transpiler.features = features.to_vec();
 
     let js = transpiler.transpile(program);
+
+    if transpiler.errors.len() > 0 {
+        for e in &transpiler.errors {
+            println!("{}", e);
+        }
+        return Err("Failed to compile input".into());
+    }
+
+    if transpiler.type_errors.len() > 0 {
+        for e in &transpiler.type_errors {
+            println!("{}", e);
+        }
+        return Err("Failed to type check input".into());
+    }
+    for w in &transpiler.warnings {
+        println!("{}", w);
+    }
     let watermark = if place_watermark {
         format!("// Compiled by EasyJS version {}\n", version::VERSION_CODE)
     } else {
@@ -35,11 +60,17 @@ fn compile(input: String, place_watermark: bool, file_name: &str) -> Result<(Str
 }
 
 /// Compile the main source of the program or the original file.
-pub fn compile_main(input: String, file_name: &str) -> String {
-    compile(input, true, file_name).expect("Could not compile").0
+pub fn compile_main(input: String, file_name: &str, strict_types: bool, target: &str, features: &[String]) -> String {
+    compile(input, true, file_name, strict_types, false, target, features).expect("Could not compile").0
+}
+
+/// Compile the main source of the program along with its TypeScript declarations.
+pub fn compile_main_with_dts(input: String, file_name: &str, strict_types: bool, target: &str, features: &[String]) -> (String, String) {
+    let (js, transpiler) = compile(input, true, file_name, strict_types, true, target, features).expect("Could not compile");
+    (js, transpiler.dts)
 }
 
 /// Compile for repl
 pub fn compile_for_repl(input: String) -> String {
-    compile(input, false, "").expect("Could not compile0").0
+    compile(input, false, "", false, false, "easyjsr", &[]).expect("Could not compile0").0
 }
\ No newline at end of file
@@ -0,0 +1,47 @@
+use easyjsc::compiler::transpile::Transpiler;
+use easyjsc::lexer::lex;
+use easyjsc::parser::par;
+
+/// Expand the macros of a EasyJS file, each expansion is marked with where the macro was called.
+///
+/// `file_name: &str` The name of the file.
+/// `only: Option<&str>` Only show the expansions of this macro.
+/// `target: &str` The target for `@if(target == "...")`.
+/// `features: &[String]` The features for `@cfg(feature = "...")`.
+///
+/// return `String` the JS program, or just the expansions of `only`.
+pub fn expand(input: String, file_name: &str, only: Option<&str>, target: &str, features: &[String]) -> String {
+    let lexer = lex::Lex::new_with_file(input, file_name.to_owned());
+    let mut parser = par::Parser::new(lexer);
+    let program = parser.parse_program();
+
+    if parser.errors.len() > 0 {
+        for e in parser.errors {
+            println!("{}", e);
+        }
+        return String::new();
+    }
+
+    let mut transpiler = Transpiler::new();
+    transpiler.annotate_macros = true;
+    transpiler.target = target.to_string();
+    transpiler.features = features.to_vec();
+
+    let js = transpiler.transpile(program);
+
+    for e in &transpiler.errors {
+        println!("{}", e);
+    }
+
+    let Some(only) = only else {
+        return js;
+    };
+
+    transpiler
+        .macro_expansions
+        .iter()
+        .filter(|expansion| expansion.name == only)
+        .map(|expansion| format!("// {}! at {}\n{}\n", expansion.name, expansion.location, expansion.js))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
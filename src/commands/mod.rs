@@ -1,4 +1,5 @@
 pub mod compile;
+pub mod expand;
 pub mod repl;
 pub mod run;
 pub mod install;
@@ -1,5 +1,5 @@
 use crate::repl::runtime;
 
 pub fn run(input: String, runtime: &str) {
-    runtime::run_file(runtime, &input, vec![]);
+    runtime::run_file(runtime, &input, vec![], false, runtime, &[]);
 }
\ No newline at end of file
@@ -1,23 +1,30 @@
 use core::{panic, str};
 use std::fs::read_dir;
+use std::path::Path;
 
 pub mod commands;
 pub mod repl;
 
-use crate::commands::{compile::compile_main, install::install, repl::start_repl};
+use crate::commands::{compile::{compile_main, compile_main_with_dts}, expand::expand, install::install, repl::start_repl};
 use crate::repl::runtime::run_file;
 
 use clap::{Parser, Subcommand, Arg};
+use easyjs_utils::utils::ej_config::{get_ej_config, parse_ej_config};
+use easyjsc::compiler::cfg::TARGETS;
 use minifier::js::minify as js_minify;
 
 #[derive(Parser, Debug)]
 #[command(name = "EasyJS", version = easyjs_utils::utils::version::VERSION_CODE, author = "Jordan Castro <jorda@grupojvm.com>")]
 #[command(about = "EasyJS compiler, repl, and runner.")]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
     /// Activate debug mode
     #[arg(short, long)]
     debug: bool,
 
+    #[command(subcommand)]
+    command: Option<Subcommands>,
+
     /// input .ej file
     ej_file: Option<String>,
 
@@ -37,14 +44,44 @@ struct Args {
     terminal: bool,
     
     /// Runtime option
-    #[arg(short, long, default_value="easyjsr")]
+    #[arg(short, long, default_value="easyjsr", global = true)]
     runtime: String,
+
+    /// Type check non native code
+    #[arg(long)]
+    strict_types: bool,
+
+    /// Write a TypeScript declaration (.d.ts) file next to the compiled JS
+    #[arg(long)]
+    emit_dts: bool,
+
+    /// Target for @if(target == "...") (browser, node, deno, bun, easyjsr). Defaults to the runtime
+    #[arg(long, global = true)]
+    target: Option<String>,
+
+    /// Enable a feature for @cfg(feature = "..."). Can be used more than once
+    #[arg(long = "feature", global = true)]
+    features: Vec<String>,
     
     /// Trailing arguments
     #[arg(long, allow_hyphen_values = true, num_args = 0..)]
     args: Vec<String>
 }
 
+/// A file named like a subcommand can still be compiled with `easyjs -- expand out.js`
+#[derive(Subcommand, Debug)]
+enum Subcommands {
+    /// Print a EasyJS file with its macros expanded
+    Expand {
+        /// input .ej file
+        file: String,
+
+        /// Only show the expansions of this macro
+        #[arg(long = "macro")]
+        macro_name: Option<String>,
+    },
+}
+
 #[derive(Debug)]
 enum Commands {
     /// open the repl
@@ -53,6 +90,11 @@ enum Commands {
     Compile,
     /// Run a EasyJS file/project
     Run,
+    /// Print a EasyJS file with its macros expanded
+    Expand {
+        file: String,
+        macro_name: Option<String>,
+    },
     // /// Install a easyjs package
     // Install {
     //     /// The path to the .ejconfig file
@@ -64,17 +106,52 @@ enum Commands {
     // },
 }
 
+/// Get the target and features from the flags and the project manifest (.ejconfig) next to the file.
+///
+/// The --target flag wins over the manifest, without either the target is the runtime.
+fn get_target_and_features(args: &Args, file: &str) -> (String, Vec<String>) {
+    let dir = Path::new(file)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or(".".to_string());
+    let config = get_ej_config(&dir)
+        .first()
+        .and_then(|path| parse_ej_config(path.to_owned()).ok());
+
+    let mut target = args.target.clone();
+    let mut features = args.features.clone();
+    if let Some(config) = config {
+        target = target.or(config.target);
+        features.extend(config.features);
+    }
+    let target = target.unwrap_or(args.runtime.clone());
+
+    if !TARGETS.contains(&target.as_str()) {
+        println!("Unknown target {}. Please use ({}) instead.", target, TARGETS.join(", "));
+        std::process::exit(1);
+    }
+
+    (target, features)
+}
+
 fn easyjs(args: Args, cmd: Commands) {
         match cmd {
             Commands::Repl => {
                 start_repl(&args.runtime, false, args.debug);
             }
             Commands::Compile => {
-                let file = args.ej_file.unwrap();
+                let file = args.ej_file.clone().unwrap();
+                let (target, features) = get_target_and_features(&args, &file);
                 // Get path.
                 let ej_code_bytes: Vec<u8> = std::fs::read(&file).expect("Failed to read file.");
                 let ej_code = str::from_utf8(&ej_code_bytes).expect("Unable to parse bytes.");
-                let mut js_code = compile_main(ej_code.to_string(), &file);
+                let (mut js_code, dts) = if args.emit_dts {
+                    let (js_code, dts) = compile_main_with_dts(ej_code.to_string(), &file, args.strict_types, &target, &features);
+                    (js_code, Some(dts))
+                } else {
+                    (compile_main(ej_code.to_string(), &file, args.strict_types, &target, &features), None)
+                };
 
                 let extension = {
                     if args.minify {
@@ -110,11 +187,25 @@ fn easyjs(args: Args, cmd: Commands) {
                     .unwrap()
                     .to_string();
 
+                // write the declarations next to the JS file
+                if let Some(dts) = dts {
+                    let dts_file = format!("{}.d.ts", out_file.trim_end_matches(extension).trim_end_matches(".js"));
+                    std::fs::write(dts_file, dts).expect("Filed to write file.");
+                }
+
                 // write to file
                 std::fs::write(out_file, js_code).expect("Filed to write file.");
             }
+            Commands::Expand { file, macro_name } => {
+                let (target, features) = get_target_and_features(&args, &file);
+                let ej_code = std::fs::read_to_string(&file).expect("Failed to read file.");
+
+                println!("{}", expand(ej_code, &file, macro_name.as_deref(), &target, &features));
+            }
             Commands::Run => {
-                run_file(&args.runtime, &args.ej_file.unwrap(), args.args);
+                let file = args.ej_file.clone().unwrap();
+                let (target, features) = get_target_and_features(&args, &file);
+                run_file(&args.runtime, &file, args.args, args.strict_types, &target, &features);
             }
             // Commands::Install {
                 // path_to_js_file,
@@ -127,7 +218,13 @@ fn easyjs(args: Args, cmd: Commands) {
 }
 
 fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    // Expand is `easyjs expand file.ej`
+    if let Some(Subcommands::Expand { file, macro_name }) = args.command.take() {
+        easyjs(args, Commands::Expand { file, macro_name });
+        return;
+    }
 
     // Repl is no ej_file exists.
     if args.ej_file.is_none() {
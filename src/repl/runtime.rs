@@ -121,9 +121,9 @@ impl RT for Runtime {
 }
 
 /// run a ej file.
-pub fn run_file(runtime: &str, path: &str, arguments: Vec<String>) {
+pub fn run_file(runtime: &str, path: &str, arguments: Vec<String>, strict_types: bool, target: &str, features: &[String]) {
     let input = std::fs::read_to_string(path).expect("FAILED TO READ FILE");
-    let js_content = compile::compile_main(input, path);
+    let js_content = compile::compile_main(input, path, strict_types, target, features);
     let js_content = format!("const EASYJS_RUNTIME='{}';\n{}", runtime, js_content);
 
     let js_file_path = format!("{}.js", utils::h::generate_hash(path));
@@ -55,4 +55,137 @@ mod tests {
 
         assert_eq!(lex::read_all_tokens(input.to_string()), results);
     }
+
+    #[test]
+    fn test_operators() {
+        let input = "& | ^ ~ << >> >>> ** %= **= &= |= ^= <<= >>= >>>= &&= ||= ??= ++ -- && || ?? >= <= |>";
+        let results = vec![
+            token::BITWISE_AND,
+            token::BITWISE_OR,
+            token::BITWISE_XOR,
+            token::BITWISE_NOT,
+            token::SHIFT_LEFT,
+            token::SHIFT_RIGHT,
+            token::UNSIGNED_SHIFT_RIGHT,
+            token::EXPONENT,
+            token::MODULUS_EQUALS,
+            token::EXPONENT_EQUALS,
+            token::BITWISE_AND_EQUALS,
+            token::BITWISE_OR_EQUALS,
+            token::BITWISE_XOR_EQUALS,
+            token::SHIFT_LEFT_EQUALS,
+            token::SHIFT_RIGHT_EQUALS,
+            token::UNSIGNED_SHIFT_RIGHT_EQUALS,
+            token::AND_EQUALS,
+            token::OR_EQUALS,
+            token::DOUBLE_QUESTION_MARK_EQUALS,
+            token::INCREMENT,
+            token::DECREMENT,
+            token::AND_SYMBOL,
+            token::OR_SYMBOL,
+            token::DOUBLE_QUESTION_MARK,
+            token::GT_OR_EQ,
+            token::LT_OR_EQ,
+            token::PIPE,
+        ];
+
+        let tokens = lex::read_all_tokens(input.to_string());
+        let types: Vec<&str> = tokens.iter().map(|tk| tk.typ.as_str()).collect();
+
+        assert_eq!(types, results);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let input = "10 1.5 0xff 0b1010 0o17 1_000_000 1e-9 2.5E+3 10n 0..10 1..=9 1__0 1e 0o19 1.5n";
+        let results = vec![
+            (token::INT, "10"),
+            (token::FLOAT, "1.5"),
+            (token::INT, "0xff"),
+            (token::INT, "0b1010"),
+            (token::INT, "0o17"),
+            (token::INT, "1_000_000"),
+            (token::FLOAT, "1e-9"),
+            (token::FLOAT, "2.5E+3"),
+            (token::BIGINT, "10n"),
+            (token::INT, "0"),
+            (token::DOTDOT, ".."),
+            (token::INT, "10"),
+            (token::INT, "1"),
+            (token::DOTDOT_EQUALS, "..="),
+            (token::INT, "9"),
+            // bad literals stay one token, the parser reports them.
+            (token::INT, "1__0"),
+            (token::FLOAT, "1e"),
+            // a digit out of range and a BigInt that is not whole are illegal.
+            (token::ILLEGAL, "0o19"),
+            (token::ILLEGAL, "1.5n"),
+        ];
+
+        let tokens = lex::read_all_tokens(input.to_string());
+        let tokens: Vec<(&str, &str)> = tokens
+            .iter()
+            .map(|tk| (tk.typ.as_str(), tk.literal.as_str()))
+            .collect();
+
+        assert_eq!(tokens, results);
+    }
+
+    #[test]
+    fn test_comments_and_strings() {
+        let input = "/* a /* nested */ comment
+            */ a = r\"C:\\path\\$a\"
+            b = \"multi
+            line \\\"quoted\\\"\"
+            c";
+        let results = vec![
+            (token::IDENT, "a", 2),
+            (token::ASSIGN, "=", 2),
+            (token::STRING, "C:\\\\path\\\\\\$a", 2),
+            (token::IDENT, "b", 3),
+            (token::ASSIGN, "=", 3),
+            (token::STRING, "multi\n            line \\\"quoted\\\"", 3),
+            (token::IDENT, "c", 5),
+        ];
+
+        let tokens = lex::read_all_tokens(input.to_string());
+        let tokens: Vec<(&str, &str, i32)> = tokens
+            .iter()
+            .map(|tk| (tk.typ.as_str(), tk.literal.as_str(), tk.line_number))
+            .collect();
+
+        assert_eq!(tokens, results);
+    }
+
+    #[test]
+    fn test_template_strings() {
+        let input = "\"a ${f(\"}\")} $b!\"";
+        let tokens = lex::read_all_tokens(input.to_string());
+
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].typ, token::TEMPLATE);
+
+        let parts: Vec<String> = tokens[0]
+            .parts
+            .iter()
+            .map(|part| match part {
+                token::TemplatePart::Text(text) => text.to_owned(),
+                token::TemplatePart::Expression(tokens) => tokens
+                    .iter()
+                    .map(|tk| tk.literal.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+            })
+            .collect();
+
+        assert_eq!(parts, vec!["a ", "f ( } )", " ", "b", "!"]);
+    }
+
+    #[test]
+    fn test_unescape_string() {
+        assert_eq!(
+            lex::unescape_string("a\\n\\t\\$b\\u{1F600}\\x41\\\\"),
+            "a\n\t$b\u{1F600}A\\"
+        );
+    }
 }
@@ -495,4 +495,626 @@ mod tests {
         assert_eq!(p.errors.len(), 0);
         assert_eq!(program.statements.len(), 1);
     }
+
+    #[test]
+    fn test_struct_derives() {
+        let input = "
+            struct Point[x: float, y: float] derives eq, clone, json, debug {}
+
+            struct Person[name: string] with GreetMixin derives debug {
+                fn greet(self) {
+                }
+            }
+
+            struct Bad[x] derives hash {}
+
+            derives = ['eq']
+            fn has(derives) {
+                return derives.length > 0
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 1);
+        assert_eq!(program.statements.len(), 5);
+    }
+
+    #[test]
+    fn test_generic_types() {
+        let input = "
+            fn first<T>(xs: Array<T>): T {
+                return xs[0]
+            }
+            fn lookup<K, V>(m: Map<K, Array<V>>, k: K): Promise<V> {
+                return m.get(k)
+            }
+            tools: Array<Tool> = []
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 3);
+    }
+
+    #[test]
+    fn test_union_types() {
+        let input = "
+            fn find(name: string?): User | null {
+                return null
+            }
+            fn request(method: \"GET\" | \"POST\", retries: 1 | 2 | 3, xs: Array<int | float>) {}
+            status: string | int? = 200
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 3);
+    }
+
+    #[test]
+    fn test_type_alias() {
+        let input = "
+            type UserId = int
+            pub type Users = Array<models.User>
+            type Method = \"GET\" | \"POST\"
+            event.type = 'click'
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 4);
+        assert_eq!(program.statements[0].variant_type(), "TypeAliasStatement");
+        assert_eq!(program.statements[3].variant_type(), "ExpressionStatement");
+    }
+
+    #[test]
+    fn test_optional_chaining() {
+        let input = "
+            name = user?.profile?.name
+            first = items?.[0]
+            callback?.(1, 2)
+            user?.greet()
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 4);
+    }
+
+    #[test]
+    fn test_operators() {
+        let input = "
+            a = 5 & 3 | 2 ^ 1
+            b = ~a << 2 >> 1 >>> 0
+            c = 2 ** 3 ** 2
+            d %= 2
+            e ??= 1
+            d++
+            --d
+            m: Map<string, Array<int>> = null
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 8);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let input = "0xff 0b1010 0o17 1_000_000 1e-9 10n".to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+
+        let values: Vec<String> = program
+            .statements
+            .iter()
+            .map(|stmt| match stmt {
+                ast::Statement::ExpressionStatement(_, expr) => match expr.as_ref() {
+                    ast::Expression::IntegerLiteral(_, value) => format!("int {}", value),
+                    ast::Expression::FloatLiteral(_, value) => format!("float {}", value),
+                    ast::Expression::BigIntLiteral(_, value) => format!("bigint {}", value),
+                    other => format!("{:?}", other),
+                },
+                other => format!("{:?}", other),
+            })
+            .collect();
+
+        assert_eq!(
+            values,
+            vec![
+                "int 255",
+                "int 10",
+                "int 15",
+                "int 1000000",
+                "float 0.000000001",
+                "bigint 10",
+            ]
+        );
+
+        let input = "0x_ff 1__0 1_ 1e 1e+ 1_.5 18446744073709551616 0o19 0b102 1.5n 1e3n".to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        p.parse_program();
+
+        println!("{:?}", p.errors);
+
+        assert_eq!(p.errors.len(), 11);
+        assert!(p.errors[0].contains("Numeric separator can not come right after 0x in 0x_ff"));
+        assert!(p.errors[1].contains("Numeric separator must be between two digits in 1__0"));
+        assert!(p.errors[2].contains("Numeric separator must be between two digits in 1_"));
+        assert!(p.errors[3].contains("Exponent of 1e has no digits"));
+        assert!(p.errors[4].contains("Exponent of 1e+ has no digits"));
+        assert!(p.errors[5].contains("Numeric separator must be between two digits in 1_.5"));
+        assert!(p.errors[6].contains("Integer 18446744073709551616 does not fit in 64 bits"));
+        assert!(p.errors[7].contains("Illegal number 0o19"));
+        assert!(p.errors[8].contains("Illegal number 0b102"));
+        assert!(p.errors[9].contains("Illegal number 1.5n"));
+        assert!(p.errors[10].contains("Illegal number 1e3n"));
+    }
+
+    #[test]
+    fn test_casts() {
+        let input = "
+            a = x + y as i64
+            b = v.total as f64
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 2);
+
+        // `as` binds tighter than +
+        match &program.statements[0] {
+            ast::Statement::VariableStatement(_, _, _, value, _) => match value.as_ref() {
+                ast::Expression::InfixExpression(_, _, op, right) => {
+                    assert_eq!(op, "+");
+                    assert!(matches!(right.as_ref(), ast::Expression::CastExpression(_, _, _)));
+                }
+                other => panic!("Expected a InfixExpression, got {:?}", other),
+            },
+            other => panic!("Expected a VariableStatement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_template_literals() {
+        let input = "\"sum: ${a + b} of ${ {x: 1}.x } $name\"".to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+
+        let ast::Statement::ExpressionStatement(_, expr) = &program.statements[0] else {
+            panic!("Expected a expression statement");
+        };
+        let ast::Expression::TemplateLiteral(_, strings, expressions) = expr.as_ref() else {
+            panic!("Expected a template literal got {:?}", expr);
+        };
+
+        assert_eq!(strings, &vec!["sum: ", " of ", " ", ""]);
+        assert_eq!(
+            expressions
+                .iter()
+                .map(|e| e.variant_type())
+                .collect::<Vec<&str>>(),
+            vec!["InfixExpression", "DotExpression", "Identifier"]
+        );
+    }
+
+    #[test]
+    fn test_generators() {
+        let input = "
+            fn* gen() {
+                x = yield
+                yield* other()
+            }
+            for await x of stream {}
+            fn*(x) x
+            for await x {}
+            fn notgen() { yield 1 }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        // a generator lambda needs a body, for await needs a `x of y` and yield needs a generator
+        assert_eq!(p.errors.len(), 3);
+        assert!(p.errors.iter().any(|e| e.contains("yield can only be used inside a generator")));
+
+        let ast::Statement::ExpressionStatement(_, function) = &program.statements[0] else {
+            panic!("Expected a function");
+        };
+        let ast::Expression::FunctionLiteral(tk, _, _, _, body, _, _) = function.as_ref() else {
+            panic!("Expected a function got {:?}", function);
+        };
+        assert_eq!(tk.typ, token::GENERATOR);
+
+        let ast::Statement::BlockStatement(_, stmts) = body.as_ref() else {
+            panic!("Expected a block");
+        };
+        match &stmts[0] {
+            ast::Statement::VariableStatement(_, _, _, value, _) => match value.as_ref() {
+                ast::Expression::YieldExpression(_, value, false) => assert!(value.is_empty()),
+                other => panic!("Expected a yield got {:?}", other),
+            },
+            other => panic!("Expected a variable got {:?}", other),
+        }
+        match &stmts[1] {
+            ast::Statement::ExpressionStatement(_, value) => {
+                assert!(matches!(
+                    value.as_ref(),
+                    ast::Expression::YieldExpression(_, _, true)
+                ))
+            }
+            other => panic!("Expected a yield* got {:?}", other),
+        }
+
+        match &program.statements[1] {
+            ast::Statement::ForStatement(_, condition, _) => {
+                assert_eq!(condition.variant_type(), "AwaitExpression")
+            }
+            other => panic!("Expected a for got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_loops() {
+        let input = "
+            outer: for i in 10..=0 step 2 {
+                for k, v in obj {
+                    continue outer
+                }
+            }
+            do {
+                break
+            } while x < 10
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+
+        let ast::Statement::LabeledStatement(_, label, stmt) = &program.statements[0] else {
+            panic!("Expected a labeled statement got {:?}", program.statements[0]);
+        };
+        assert_eq!(label, "outer");
+        let ast::Statement::ForStatement(_, condition, _) = stmt.as_ref() else {
+            panic!("Expected a for statement got {:?}", stmt);
+        };
+        let Expression::InExpression(_, _, range) = condition.as_ref() else {
+            panic!("Expected a in expression got {:?}", condition);
+        };
+        let Expression::RangeExpression(tk, _, _, step) = range.as_ref() else {
+            panic!("Expected a range got {:?}", range);
+        };
+        assert_eq!(tk.typ, token::DOTDOT_EQUALS);
+        assert_eq!(step.variant_type(), "IntegerLiteral");
+
+        assert_eq!(program.statements[1].variant_type(), "DoWhileStatement");
+    }
+
+    #[test]
+    fn test_loop_errors() {
+        let inputs = vec![
+            "for 0..10 {}",
+            "for i in 0.. {}",
+            "for i, x in 0..3 {}",
+            "for i = 1 {}",
+            "for i, 3 in xs {}",
+            "for x in xs { break nowhere }",
+            "x = xs[0..4 step 2]",
+            "for i in 0..n step 0 {}",
+            "for i in 0..10 step -1 {}",
+        ];
+
+        for input in inputs {
+            let l = lex::Lex::new(input.to_string());
+            let mut p = par::Parser::new(l);
+            p.parse_program();
+
+            println!("{}: {:?}", input, p.errors);
+            assert!(p.errors.len() > 0);
+        }
+    }
+
+    #[test]
+    fn test_pipelines() {
+        let input = "
+            x = 5 |> double |> add(_, 1)
+            y = xs.length
+                |> std.scale(2)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 2);
+
+        let ast::Statement::VariableStatement(_, _, _, value, _) = &program.statements[0] else {
+            panic!("Expected a variable statement got {:?}", program.statements[0]);
+        };
+        let Expression::CallExpression(_, callee, args) = value.as_ref() else {
+            panic!("Expected a call got {:?}", value);
+        };
+        assert!(matches!(callee.as_ref(), Expression::Identifier(_, name) if name == "add"));
+        assert_eq!(args[0].variant_type(), "CallExpression");
+        assert_eq!(args[1].variant_type(), "IntegerLiteral");
+
+        let errors = vec!["x |> _", "x |> f(_, _)", "x |> _.length"];
+        for input in errors {
+            let l = lex::Lex::new(input.to_string());
+            let mut p = par::Parser::new(l);
+            p.parse_program();
+
+            assert_eq!(p.errors.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_macro_calls() {
+        let input = "
+            macro log(...msgs) {
+                console.log(#msgs)
+            }
+            log!(1, 2)
+            @log(1, 2)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 3);
+        for stmt in &program.statements[1..] {
+            let ast::Statement::ExpressionStatement(_, expr) = stmt else {
+                panic!("Expected a expression statement got {:?}", stmt);
+            };
+            let Expression::MacroExpression(_, name, args) = expr.as_ref() else {
+                panic!("Expected a macro call got {:?}", expr);
+            };
+            assert!(matches!(name.as_ref(), Expression::Identifier(_, name) if name == "log"));
+            assert_eq!(args.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_proc_macro() {
+        let input = "
+            proc macro count(list) {
+                return {IntegerLiteral: [{}, list.ArrayLiteral[1].length]}
+            }
+            macro double(x) {
+                #x * 2
+            }
+            proc = 1
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 3);
+        assert!(matches!(&program.statements[0], ast::Statement::MacroStatement(_, _, _, _, false, true)));
+        assert!(matches!(&program.statements[1], ast::Statement::MacroStatement(_, _, _, _, false, false)));
+        assert!(matches!(&program.statements[2], ast::Statement::VariableStatement(_, _, _, _, _)));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let input = "
+            @memoize
+            @route(\"/users\")
+            fn users() {}
+
+            @deprecated(\"use Point3\")
+            pub struct Point[x, y] {
+                @log
+                fn len(self) {}
+            }
+
+            @component
+            class Button {}
+
+            @print(\"hi\")
+            x = 1
+
+            @print(\"bye\")
+            pub y = 2
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 7);
+
+        let ast::Statement::ExpressionStatement(_, function) = &program.statements[0] else {
+            panic!("Expected a function got {:?}", program.statements[0]);
+        };
+        let Expression::FunctionLiteral(_, _, _, _, _, _, attributes) = function.as_ref() else {
+            panic!("Expected a function got {:?}", function);
+        };
+        assert_eq!(attributes.len(), 2);
+        assert!(matches!(&attributes[0], Expression::Identifier(_, name) if name == "memoize"));
+        assert!(matches!(&attributes[1], Expression::CallExpression(_, _, args) if args.len() == 1));
+
+        let ast::Statement::ExportStatement(_, stmt) = &program.statements[1] else {
+            panic!("Expected an export got {:?}", program.statements[1]);
+        };
+        let ast::Statement::StructStatement(_, _, _, _, _, _, methods, attributes) = stmt.as_ref() else {
+            panic!("Expected a struct got {:?}", stmt);
+        };
+        assert_eq!(attributes.len(), 1);
+        assert!(matches!(&methods[0], Expression::FunctionLiteral(_, _, _, _, _, _, attributes) if attributes.len() == 1));
+
+        assert!(matches!(&program.statements[2], ast::Statement::ClassStatement(_, _, _, _, attributes) if attributes.len() == 1));
+        // not on a fn, struct or class so it is still a macro call.
+        assert!(matches!(&program.statements[3], ast::Statement::ExpressionStatement(_, expr) if matches!(expr.as_ref(), Expression::MacroExpression(_, _, _))));
+        assert!(matches!(&program.statements[5], ast::Statement::ExpressionStatement(_, expr) if matches!(expr.as_ref(), Expression::MacroExpression(_, _, _))));
+    }
+
+    #[test]
+    fn test_comptime() {
+        let input = "
+            squares = comptime {
+                return [1, 2, 3].map(fn(n) { return n * n })
+            }
+            sum = comptime 1 + 2
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 2);
+        for stmt in &program.statements {
+            let ast::Statement::VariableStatement(_, _, _, value, _) = stmt else {
+                panic!("Expected a variable statement got {:?}", stmt);
+            };
+            let Expression::ComptimeExpression(_, body) = value.as_ref() else {
+                panic!("Expected a comptime expression got {:?}", value);
+            };
+            assert!(matches!(body.as_ref(), ast::Statement::BlockStatement(_, stmts) if stmts.len() == 1));
+        }
+    }
+
+    #[test]
+    fn test_cfg() {
+        let input = "
+            @if(target == \"node\") {
+                fs = require(\"fs\")
+            } else @if(target == \"deno\" or target == \"bun\") {
+                fs = null
+            } else {
+                fs = undefined
+            }
+            @cfg(feature = \"logging\") {
+                console.log(\"logging\")
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        println!("{:?}", p.errors);
+        println!("{:#?}", program.statements);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(program.statements.len(), 2);
+
+        let ast::Statement::CfgStatement(_, _, _, alternative) = &program.statements[0] else {
+            panic!("Expected a cfg statement got {:?}", program.statements[0]);
+        };
+        let ast::Statement::CfgStatement(_, _, _, alternative) = alternative.as_ref() else {
+            panic!("Expected a else @if got {:?}", alternative);
+        };
+        assert!(matches!(alternative.as_ref(), ast::Statement::BlockStatement(_, _)));
+
+        let ast::Statement::CfgStatement(_, condition, _, alternative) = &program.statements[1] else {
+            panic!("Expected a cfg statement got {:?}", program.statements[1]);
+        };
+        assert!(matches!(condition.as_ref(), Expression::AssignExpression(_, _, _)));
+        assert!(alternative.is_empty());
+    }
 }
@@ -1 +1,1103 @@
-// TODO: transpiler tests.
\ No newline at end of file
+// TODO: transpiler tests.
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use easyjsc::compiler::transpile::Transpiler;
+    use easyjsc::lexer::lex;
+    use easyjsc::parser::par;
+
+    #[test]
+    fn test_strict_types() {
+        let input = "
+            fn add(x: int, y: int): int {
+                return x + y
+            }
+
+            struct Point[x: float, y: float] {}
+
+            a = add(1, 2)
+            b: string = add(1, 2)
+            c = add('a', 2)
+            d = add(1)
+            p = Point(1.5, 2)
+            p.z
+            e = 1 - 'x'
+            var u = untyped()
+            u.whatever(1)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.strict_types = true;
+        t.transpile(program);
+
+        println!("{:#?}", t.type_errors);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.type_errors.len(), 5);
+    }
+
+    #[test]
+    fn test_strict_nullable_types() {
+        let input = "
+            struct User[name: string] {
+                fn greet(self): string {
+                    return self.name
+                }
+            }
+
+            fn find(name: string): User? {
+                return null
+            }
+
+            fn greet(user: User?): string {
+                if user == null {
+                    return 'nobody'
+                }
+                return user.greet()
+            }
+
+            fn request(method: 'GET' | 'POST') {}
+
+            fn pick(name: string?): string {
+                if name != null {
+                    return name
+                }
+                return name ?? 'x'
+            }
+
+            class Dog {}
+
+            fn speak(pet: Dog | string): string {
+                if pet is Dog {
+                    return 'woof'
+                }
+                return pet
+            }
+
+            fn size(v: int | string): int {
+                if v is int {
+                    return v
+                }
+                return v.length
+            }
+
+            maybe: string? = null
+            name: string = maybe ?? 'anon'
+            bad: string = maybe
+            find('a').greet()
+            request('GET')
+            request('PUT')
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.strict_types = true;
+        let result = t.transpile(program);
+
+        println!("{:#?}", t.type_errors);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.type_errors.len(), 3);
+        assert!(t.type_errors[0].contains("Expected string but got string | null"));
+        assert!(t.type_errors[1].contains("Value of type User | null may be null"));
+        assert!(t.type_errors[2].contains("Argument 1 of request expected \"GET\" | \"POST\" but got \"PUT\""));
+        assert!(result.contains("pet instanceof Dog"));
+        assert!(result.contains("typeof(v) == \"number\""));
+
+        // structs are plain objects and can not be checked.
+        let input = "
+            struct User[name: string] {}
+            u = User('a')
+            u is User
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.transpile(program);
+
+        assert_eq!(t.errors.len(), 1);
+        assert!(t.errors[0].contains("Can not check for User with `is`"));
+    }
+
+    #[test]
+    fn test_strict_type_aliases() {
+        let input = "
+            fn find(id: UserId, users: Users): User? {
+                return null
+            }
+
+            type UserId = int
+            type Users = Array<User>
+
+            struct User[id: int] {}
+            enum Role { Admin, Guest }
+
+            fn allow(role: Role) {}
+
+            find(1, [User(1)])
+            find('1', [User(1)])
+            find(1, [1])
+            allow(Role.Admin)
+            allow(0)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.strict_types = true;
+        t.transpile(program);
+
+        println!("{:#?}", t.type_errors);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.type_errors.len(), 3);
+    }
+
+    #[test]
+    fn test_emit_dts() {
+        let input = "
+            pub type UserId = int
+
+            pub struct User[id: UserId, name: string] derives eq {
+                fn greet(self, greeting: string = 'hi'): string {
+                    return greeting + self.name
+                }
+            }
+
+            pub enum Role { Admin, Guest }
+
+            pub fn first<T>(xs: Array<T>): T? {
+                return xs[0]
+            }
+            pub async fn load(id: UserId, tags: array, ...rest): User {
+                return User(id, '')
+            }
+            pub limit: float = 10
+            fn private_fn() {}
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.emit_dts = true;
+        t.transpile(program);
+
+        println!("{}", t.dts);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(t.dts.contains("export type UserId = number;"));
+        assert!(t.dts.contains("greet(greeting?: string): string;"));
+        assert!(t.dts.contains("equals(other: User): boolean;"));
+        assert!(t.dts.contains("export declare function User(id: UserId, name: string): User;"));
+        assert!(t.dts.contains("export declare enum Role { Admin = 0, Guest = 1 }"));
+        assert!(t.dts.contains("export declare function first<T>(xs: T[]): T | null;"));
+        assert!(t.dts.contains(
+            "export declare function load(id: UserId, tags: any[], ...rest: any[]): Promise<User>;"
+        ));
+        assert!(t.dts.contains("export declare let limit: number;"));
+        assert!(!t.dts.contains("private_fn"));
+    }
+
+    #[test]
+    fn test_optional_chaining() {
+        let input = "
+            name = user?.profile?.name
+            first = items?.[0]
+            rest = items?.[1..]
+            callback?.(1, 2)
+            user?.greet()
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("user?.profile?.name"));
+        assert!(js.contains("items?.[0]"));
+        assert!(js.contains("items?.slice(1)"));
+        assert!(js.contains("callback?.(1,2)"));
+        assert!(js.contains("user?.greet()"));
+    }
+
+    #[test]
+    fn test_operators() {
+        let input = "
+            var x = 1
+            y = x & 3 | 2 ^ ~x
+            z = x << 2 >> 1 >>> 0
+            w = 2 ** 3
+            x %= 2
+            x &&= y
+            x ??= - -z
+            x++
+            --x
+            // comptime runs the JS, so these have to be valid.
+            e = comptime -2 ** 2
+            f = comptime (-2) ** 2
+            g = comptime ~2 ** 2
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("x & 3 | 2 ^ ~x"));
+        assert!(js.contains("x << 2 >> 1 >>> 0"));
+        assert!(js.contains("2 ** 3"));
+        assert!(js.contains("x %= 2"));
+        assert!(js.contains("x &&= y"));
+        assert!(js.contains("x ??= - -z"));
+        assert!(js.contains("x++"));
+        assert!(js.contains("--x"));
+        assert_eq!(t.errors.len(), 0);
+        assert!(js.contains("let e = -4;"));
+        assert!(js.contains("let f = 4;"));
+        assert!(js.contains("let g = 9;"));
+    }
+
+    #[test]
+    fn test_numbers() {
+        let input = "
+            a = 0xff + 0b1010 + 0o17
+            b = 1_000_000
+            c = 1e-9
+            d = 10n * 0xffn
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("0xff + 0b1010 + 0o17"));
+        // numbers are emitted as written, separators included.
+        assert!(js.contains("let b = 1_000_000;"));
+        assert!(js.contains("1e-9"));
+        assert!(js.contains("10n * 0xffn"));
+    }
+
+    #[test]
+    fn test_strings() {
+        let input = r#"
+            /* block /* nested */ comment */
+            a = "hello $name"
+            b = "cost: \$5"
+            c = r"C:\path\$name"
+            d = 'it\'s "quoted"'
+            e = "use `ticks` $name"
+            f = "nested ${"inner $name"} and ${items["}"]}"
+        "#
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("`hello ${name}`"));
+        assert!(js.contains(r"`cost: \$5`"));
+        assert!(js.contains(r"`C:\\path\\\$name`"));
+        assert!(js.contains(r#""it\'s \"quoted\"""#));
+        assert!(js.contains(r"`use \`ticks\` ${name}`"));
+        assert!(js.contains("`nested ${`inner ${name}`} and ${items['}']}`"));
+    }
+
+    #[test]
+    fn test_derive_json() {
+        let input = "
+            struct P[x: int, y: int?] derives json {
+                z: int = 5
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        // missing keys are undefined instead of NaN, and optional fields keep null.
+        assert!(js.contains("'x' in data ? Math.trunc(Number(data.x)) : undefined"));
+        assert!(js.contains("'y' in data ? (data.y == null ? data.y : Math.trunc(Number(data.y))) : undefined"));
+        // fields with a default keep it when the key is missing.
+        assert!(js.contains("if ('z' in data) {obj.z = Math.trunc(Number(data.z));}"));
+    }
+
+    #[test]
+    fn test_generators() {
+        let input = "
+            fn* count(n) {
+                for i in 0..n {
+                    yield i
+                }
+            }
+
+            async fn* lines(stream) {
+                for await chunk of stream {
+                    yield* chunk.split(\",\")
+                }
+                yield
+            }
+
+            evens = fn*(xs) {
+                yield xs
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("function* count(n)"));
+        assert!(js.contains("yield i;"));
+        assert!(js.contains("async function* lines(stream)"));
+        assert!(js.contains("for await (let chunk of stream)"));
+        assert!(js.contains("yield* chunk.split(',');"));
+        assert!(js.contains("yield;"));
+        assert!(js.contains("let evens = function*(xs) {"));
+    }
+
+    #[test]
+    fn test_loops() {
+        let input = "
+            for i in 0..=3 {}
+            for i in 10..0 step 2 {}
+            for i in 0..n {}
+            for i in n..0 step -1 {}
+            for i in 0..n step s {}
+            for i in 10..0 step s {}
+            for i, x in xs {}
+            for k, v in {a: 1} {}
+            outer: for x in xs {
+                break outer
+            }
+            do {
+                x += 1
+            } while x < 10
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("for (let i = 0;i <= 3;i++)"));
+        assert!(js.contains("for (let i = 10;i > 0;i -= 2)"));
+        // only ranges of literals or a negative step count down.
+        assert!(js.contains("for (let i = 0;i < n;i++)"));
+        assert!(js.contains("for (let i = n;i > 0;i--)"));
+        assert!(js.contains(
+            "for (let i = 0, __i_end = n, __i_step = s;__i_step > 0 ? i < __i_end : __i_step < 0 && i > __i_end;i += __i_step)"
+        ));
+        assert!(js.contains("for (let i = 10, __i_end = 0, __i_step = -(s);"));
+        assert!(js.contains("for (let [i,x] of ((v) => Array.isArray(v) || v instanceof Map ? v.entries() : Object.entries(v))(xs))"));
+        assert!(js.contains("for (let [k,v] of Object.entries({a:1}))"));
+        assert!(js.contains("outer: for (let x of xs)"));
+        assert!(js.contains("break outer;"));
+        assert!(js.contains("} while (x < 10);"));
+    }
+
+    #[test]
+    fn test_pipelines() {
+        let input = "
+            x = 5 |> double |> add(_, 1) |> add(10)
+            y = [1, 2]
+                |> Array.from
+                |> console.log(\"items\", _)
+            z = comptime 3 |> fn(n) n + 1
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("add(add(double(5),1),10)"));
+        assert!(js.contains("console.log('items',Array.from([1,2]))"));
+        // a lambda is called, not returned.
+        assert_eq!(t.errors.len(), 0);
+        assert!(js.contains("let z = 4;"));
+    }
+
+    #[test]
+    fn test_kwargs() {
+        let input = "
+            greet(name=\"Ann\", greeting=\"Hi\")
+            fn greet(greeting=\"Hello\", name=\"you\", punct=\"!\") {}
+            greet(\"Yo\", punct=\".\")
+            fn add(kwargs) {
+                return kwargs.a + kwargs.b
+            }
+            add(a=1, b=2)
+            chart.draw(1, color=\"red\")
+            struct P[x: int, y: int] {}
+            P(y=1, x=2)
+            s = Pt(y=1, x=2)
+            struct Pt[x, y] {}
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.errors.len(), 0);
+        assert!(js.contains("greet('Hi','Ann')"));
+        assert!(js.contains("greet('Yo',undefined,'.')"));
+        assert!(js.contains("add({a:1,b:2})"));
+        assert!(js.contains("chart.draw(1,{color:'red'})"));
+        assert!(js.contains("P(2,1)"));
+        // structs can be called before they are declared.
+        assert!(js.contains("let s = Pt(2,1);"));
+    }
+
+    #[test]
+    fn test_kwargs_errors() {
+        let input = "
+            fn greet(greeting=\"Hello\", name=\"you\") {}
+            greet(nme=\"x\")
+            greet(\"a\", greeting=\"b\")
+            greet(name=\"a\", name=\"b\")
+            greet(name=\"a\", \"b\")
+            struct P[x: int, y: int] {}
+            P(x=1, z=2)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.transpile(program);
+
+        println!("{:#?}", t.errors);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.errors.len(), 5);
+    }
+
+    #[test]
+    fn test_macros() {
+        let input = "
+            macro last(array) {
+                #array[#array.length - 1]
+            }
+            macro swap(a, b) {
+                tmp = #a
+                #a = #b
+                #b = tmp
+            }
+            macro double(n) #n * 2
+            macro log(...msgs) {
+                console.log(\"log:\", #msgs)
+            }
+            macro to_string_fn(name) {
+                fn #name_to_string() {
+                    return \"#name\"
+                }
+            }
+            macro point(value) {
+                (fn() {
+                    x = #value
+                    return {x}
+                })()
+            }
+            macro raw(msg) {
+                javascript {
+                    console.log(#msg);
+                }
+            }
+
+            y = last!(get_items())
+            tmp = 1
+            x = 2
+            swap!(x, tmp)
+            z = double!(1 + 2)
+            log!(1, 2)
+            @log(3)
+            to_string_fn!(Color)
+            raw!(tmp)
+            point = point!(1)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        // side effects are only evaluated once.
+        assert!(js.contains("((__array_0) => {\nreturn __array_0[__array_0.length - 1]})(get_items())"));
+        // locals of the macro do not clash with the caller.
+        assert!(js.contains("let __tmp_1 = x;\nx = tmp;\ntmp = __tmp_1;"));
+        assert!(js.contains("let z = (1 + 2) * 2;"));
+        assert!(js.contains("console.log('log:',1,2);"));
+        assert!(js.contains("console.log('log:',3);"));
+        assert!(js.contains("function Color_to_string()"));
+        assert!(js.contains("return '#name';"));
+        assert!(js.contains("console.log(tmp);"));
+        // shorthand keys keep their name.
+        assert!(js.contains("return {x:__x_"));
+    }
+
+    #[test]
+    fn test_comptime_macros() {
+        let input = "
+            macro triple(n) {{
+                \"t = \" + #n * 3
+            }}
+            macro pick(items = [1, 2], i = 1) {{
+                \"p = \" + #items[#i]
+            }}
+            triple!(1 + 1)
+            pick!()
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.errors.len(), 0);
+        // arguments are bound as expressions, not pasted in as text.
+        assert!(js.contains("let t = 6;"));
+        assert!(js.contains("let p = 2;"));
+    }
+
+    #[test]
+    fn test_proc_macro() {
+        let input = "
+            proc macro count(list) {
+                items = list.ArrayLiteral[1]
+                return {IntegerLiteral: [{}, items.length]}
+            }
+            proc macro make_var(name) {
+                v = name.StringLiteral[1]
+                return {VariableStatement: [{}, {Identifier: [{}, v]}, null, {StringLiteral: [{}, v]}, false]}
+            }
+            proc macro nothing() {
+                return null
+            }
+            proc macro bad() {
+                return {NotANode: []}
+            }
+            proc macro broken() {
+                return not_defined()
+            }
+            n = count!([1, 2, 3])
+            make_var!(\"hello\")
+            nothing!()
+            bad!()
+            broken!()
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+        println!("{:?}", t.errors);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("let n = 3;"));
+        assert!(js.contains("let hello = 'hello';"));
+        assert_eq!(t.errors.len(), 2);
+        assert!(t.errors[0].contains("Proc macro bad did not return a valid AST"));
+        assert!(t.errors[1].contains("Proc macro broken failed") && t.errors[1].contains("not_defined"));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let input = "
+            macro print(msg) {
+                console.log(#msg)
+            }
+
+            @memoize
+            @route(\"/users\")
+            fn users() {}
+
+            @deprecated(\"use new_users instead\")
+            fn old_users() {}
+
+            @test
+            fn test_users() {}
+
+            @print(\"declared\")
+            fn declared() {}
+
+            @component
+            struct Point[x, y] {
+                @log
+                fn len(self) { return self.x }
+            }
+
+            class Button {
+                @log
+                pub fn click(self) {}
+                @log
+                fn hidden(self) {}
+            }
+
+            old_users()
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+        println!("{:?}", t.errors);
+        println!("{:?}", t.warnings);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("users = memoize(route('/users')(users));"));
+        assert!(js.contains("console.log('declared');"));
+        assert!(js.contains("Point = component(Point);"));
+        assert!(js.contains("len: log(function(){"));
+        assert!(js.contains(".click = log(this"));
+        // metadata attributes are not decorators.
+        assert!(!js.contains("deprecated(") && !js.contains("test("));
+        assert_eq!(t.tests, vec!["test_users".to_string()]);
+
+        assert_eq!(t.warnings.len(), 1);
+        assert!(t.warnings[0].contains("old_users is deprecated. use new_users instead"));
+        assert_eq!(t.errors.len(), 1);
+        assert!(t.errors[0].contains("hidden is private"));
+    }
+
+    #[test]
+    fn test_macro_visibility() {
+        let module = "
+            macro helper(x) {
+                #x * 2
+            }
+
+            pub macro double(x) {
+                helper!(#x)
+            }
+        "
+        .to_string();
+        let input = "
+            import 'math_macros' as m
+            a = m.@double(2)
+            b = m.double!(3)
+            c = m.@helper(1)
+            d = missing!(1)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::with_custom_libs(HashMap::from([("math_macros".to_string(), module)]));
+        let js = t.transpile(program);
+
+        println!("{}", js);
+        println!("{:?}", t.errors);
+
+        assert_eq!(p.errors.len(), 0);
+        // private macros can be used by the macros of their module.
+        assert!(js.contains("let a = 2 * 2;"));
+        assert!(js.contains("let b = 3 * 2;"));
+        assert_eq!(t.errors.len(), 2);
+        assert!(t.errors[0].contains("m.helper is private"));
+        assert!(t.errors[1].contains("missing is not defined"));
+    }
+
+    #[test]
+    fn test_native_loops() {
+        let input = "
+            native {
+                pub fn loops(n:int, items:array):int {
+                    total = 0
+                    outer: for i in 0..n {
+                        for j in 10..=0 step 2 {
+                            if j == 4 {
+                                continue outer
+                            }
+                            total += j
+                        }
+                    }
+                    for item in items {
+                        total += item
+                    }
+                    for total < 100 {
+                        total += 1
+                        if total == 50 {
+                            break
+                        }
+                    }
+                    return total
+                }
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("const __easyjs_native_module"));
+
+        // break outside of a loop does not compile.
+        let input = "
+            native {
+                pub fn no_loop():int {
+                    break
+                    return 1
+                }
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        assert!(!js.contains("const __easyjs_native_module"));
+    }
+
+    #[test]
+    fn test_native_structs() {
+        let input = "
+            native {
+                pub struct Vec3[x: int, y: float, z: int] {
+                    fn sum(self):int {
+                        return self.x + self.z
+                    }
+                }
+
+                pub fn make(x:int):Vec3 {
+                    v = Vec3(x, 1.5, 3)
+                    v.x = v.x * 2
+                    return v
+                }
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("const __easyjs_native_module"));
+        assert!(js.contains(
+            "'Vec3': {size: 12, fields: [['x', 'int', 0], ['y', 'float', 4], ['z', 'int', 8]]}"
+        ));
+        assert!(js.contains("sum: (...args) => __easyjs_native_call('Vec3_sum',['Vec3',], ['int'], ...args)"));
+    }
+
+    #[test]
+    fn test_native_numbers() {
+        let input = "
+            native {
+                big: i64 = 9007199254740993n
+
+                pub fn fact(n:int):i64 {
+                    total: i64 = 1
+                    for i in 1..=n {
+                        total *= i
+                    }
+                    return total + big
+                }
+
+                pub fn wrap(a:u8, b:u8):u8 {
+                    return a + b
+                }
+
+                pub fn truncate(x:f64):int {
+                    return (x * 2) as int
+                }
+
+                pub fn lowest():int {
+                    return -2147483648
+                }
+            }
+
+            fact(20)
+            wrap(200, 100)
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        assert_eq!(p.errors.len(), 0);
+        assert_eq!(t.errors.len(), 0);
+        assert!(js.contains("const __easyjs_native_module"));
+        assert!(js.contains("__easyjs_native_call('fact',['int',], ['i64']"));
+        assert!(js.contains("__easyjs_native_call('wrap',['u8','u8',], ['u8']"));
+
+        // a float does not go into a int without `as`.
+        let input = "
+            native {
+                pub fn bad(x:float):int {
+                    return x
+                }
+            }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        assert!(!js.contains("const __easyjs_native_module"));
+    }
+
+    #[test]
+    fn test_comptime() {
+        let input = "
+            squares = comptime {
+                result = []
+                for i in 0..4 {
+                    result.push(i * i)
+                }
+                return result
+            }
+            sum = comptime 1 + 2 * 3
+            config = comptime { return {name: \"easyjs\", debug: false} }
+            version = comptime compiler_version()
+            broken = comptime { return not_defined() }
+        "
+        .to_string();
+
+        let l = lex::Lex::new(input);
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+
+        println!("{}", js);
+        println!("{:?}", t.errors);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("let squares = [0,1,4,9];"));
+        assert!(js.contains("let sum = 7;"));
+        assert!(js.contains("let config = {\"name\":\"easyjs\",\"debug\":false};"));
+        assert!(js.contains("let version = \"") && !js.contains("let version = undefined;"));
+        assert!(js.contains("let broken = undefined;"));
+        assert_eq!(t.errors.len(), 1);
+        assert!(t.errors[0].contains("not_defined"));
+    }
+
+    #[test]
+    fn test_cfg() {
+        let input = "
+            @if(target == \"node\") {
+                fn read(path) {
+                    return require(\"fs\").readFileSync(path)
+                }
+            } else @if(target == \"deno\" or target == \"bun\") {
+                fn read(path) {
+                    return Deno.readTextFileSync(path)
+                }
+            } else {
+                fn read(path) {
+                    return null
+                }
+            }
+
+            fn main() {
+                @cfg(feature = \"logging\" and not (target == \"browser\")) {
+                    console.log(\"reading\")
+                }
+                return read(\"config.json\")
+            }
+        "
+        .to_string();
+
+        let transpile = |target: &str, features: Vec<String>| {
+            let l = lex::Lex::new(input.clone());
+            let mut p = par::Parser::new(l);
+            let program = p.parse_program();
+            assert_eq!(p.errors.len(), 0);
+
+            let mut t = Transpiler::new();
+            t.target = target.to_string();
+            t.features = features;
+            let js = t.transpile(program);
+            println!("{}", js);
+            assert_eq!(t.errors.len(), 0);
+            js
+        };
+
+        let js = transpile("node", vec![]);
+        assert!(js.contains("require('fs').readFileSync(path)"));
+        assert!(!js.contains("Deno"));
+        assert!(!js.contains("console.log"));
+
+        let js = transpile("bun", vec!["logging".to_string()]);
+        assert!(js.contains("Deno.readTextFileSync(path)"));
+        assert!(!js.contains("require"));
+        assert!(js.contains("function main(){\nconsole.log('reading');\nreturn read('config.json');"));
+
+        let js = transpile("browser", vec!["logging".to_string()]);
+        assert!(js.contains("return null;"));
+        assert!(!js.contains("console.log"));
+
+        // unknown targets are an error.
+        let l = lex::Lex::new("@if(target == \"nod\") {\n x = 1\n}".to_string());
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+        let mut t = Transpiler::new();
+        let js = t.transpile(program);
+        assert_eq!(t.errors.len(), 1);
+        assert!(t.errors[0].contains("Unknown target nod"));
+        assert!(!js.contains("x = 1"));
+    }
+
+    #[test]
+    fn test_macro_expansions() {
+        let input = "macro double(n) #n * 2
+macro log(...msgs) {
+    console.log(#msgs)
+}
+x = double!(2)
+log!(double!(x))
+"
+        .to_string();
+
+        let l = lex::Lex::new_with_file(input, "main.ej".to_string());
+        let mut p = par::Parser::new(l);
+        let program = p.parse_program();
+
+        let mut t = Transpiler::new();
+        t.annotate_macros = true;
+        let js = t.transpile(program);
+
+        println!("{}", js);
+
+        assert_eq!(p.errors.len(), 0);
+        assert!(js.contains("let x = /* double! at main.ej:5:5 */ 2 * 2;"));
+        assert!(js.contains("// log! at main.ej:6:1\nconsole.log(/* double! at main.ej:6:6 */ x * 2);"));
+
+        let expansions = t
+            .macro_expansions
+            .iter()
+            .map(|expansion| (expansion.name.as_str(), expansion.location.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expansions,
+            vec![("double", "main.ej:5:5"), ("double", "main.ej:6:6"), ("log", "main.ej:6:1")]
+        );
+        assert_eq!(t.macro_expansions[0].js, "2 * 2");
+    }
+}